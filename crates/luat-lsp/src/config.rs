@@ -0,0 +1,478 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Project configuration.
+//!
+//! Each workspace folder may have a `luat.toml` (or `.luatrc`, in TOML or
+//! JSON) at its root. Its settings are layered over the client's: first the
+//! `initializationOptions`, then the `luat` section the client reports for
+//! the folder through `workspace/configuration`, then the file, so a
+//! checked-in project file wins over editor settings.
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_lsp::lsp_types::Url;
+
+use crate::handlers::diagnostics::rules::{LintConfig, RULES};
+use crate::lua::LuaVersion;
+
+/// Project configuration files, in order of preference
+pub const CONFIG_FILES: &[&str] = &["luat.toml", ".luatrc"];
+
+/// Keys of the client's `luat` section that configure the editor extension
+/// rather than the server
+const CLIENT_KEYS: &[&str] = &["server", "trace"];
+
+/// Settings for one workspace folder. Unknown keys are errors, so a typo
+/// isn't silently ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub lua: LuaConfig,
+    pub modules: ModulesConfig,
+    pub lint: LintConfig,
+    pub format: FormatConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LuaConfig {
+    /// Dialect scripts and expressions are checked against
+    pub version: LuaVersion,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModulesConfig {
+    /// Directories `require` paths are resolved against, relative to the
+    /// folder. When empty, the document's directory, its two parents and
+    /// their `src` directories are searched.
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    pub indent_width: usize,
+    pub use_tabs: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            use_tabs: false,
+        }
+    }
+}
+
+impl FormatConfig {
+    /// One level of indentation
+    pub fn indent(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.indent_width)
+        }
+    }
+}
+
+impl Config {
+    /// Build a folder's configuration from its layers, later layers taking
+    /// precedence. Relative paths are resolved against `root`.
+    pub fn from_layers(root: Option<&Path>, layers: &[&Value]) -> Result<Self, String> {
+        let mut merged = Value::Object(Map::new());
+        for layer in layers {
+            merge(&mut merged, layer);
+        }
+        let mut config = Config::deserialize(&merged).map_err(|err| err.to_string())?;
+        if let Some(root) = root {
+            for module_root in &mut config.modules.roots {
+                *module_root = root.join(&*module_root);
+            }
+        }
+        config.lint.root = root.map(Path::to_path_buf);
+        Ok(config)
+    }
+}
+
+/// Merge `overlay` into `base`, recursing into tables so a layer can set
+/// one key of a section without replacing the rest. Null layers, e.g. from
+/// a client without `luat` settings, change nothing.
+fn merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (_, Value::Null) => {}
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Add a problem for each rule name in `config` that doesn't refer to any
+/// rule, once even if several folders share it
+fn report_unknown_rules(config: &Config, problems: &mut Vec<String>) {
+    for name in config.lint.unknown_rules() {
+        let problem = format!("Unknown rule '{}' in lint settings", name);
+        if !problems.contains(&problem) {
+            problems.push(problem);
+        }
+    }
+}
+
+/// Client settings with the extension's own keys left out
+fn without_client_keys(mut settings: Value) -> Value {
+    if let Value::Object(map) = &mut settings {
+        map.retain(|key, _| !CLIENT_KEYS.contains(&key.as_str()));
+    }
+    settings
+}
+
+/// The configuration file at the root of `dir`, if there is one
+pub fn config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Whether `path` is a configuration file, by name
+pub fn is_config_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| CONFIG_FILES.contains(&name))
+}
+
+/// Read a configuration file. `.luatrc` may be JSON as well as TOML.
+pub fn read_config_file(path: &Path) -> Result<Value, String> {
+    let source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    if source.trim_start().starts_with('{') {
+        serde_json::from_str(&source).map_err(|err| err.to_string())
+    } else {
+        toml::from_str(&source).map_err(|err| err.to_string())
+    }
+}
+
+/// A workspace folder and the layers of its configuration
+struct Folder {
+    uri: Url,
+    root: Option<PathBuf>,
+    /// The folder's `luat` section from the client
+    client: Value,
+    /// The contents of its configuration file
+    file: Value,
+    config: Arc<Config>,
+}
+
+/// The configuration of every workspace folder
+#[derive(Default)]
+pub struct Settings {
+    /// `initializationOptions`, the bottom layer of every folder
+    initialization: Value,
+    folders: Vec<Folder>,
+    /// Client settings for documents outside every folder
+    client: Value,
+    /// Configuration for documents outside every folder
+    fallback: Arc<Config>,
+}
+
+impl Settings {
+    pub fn new(initialization: Option<Value>) -> Self {
+        let mut settings = Self {
+            initialization: without_client_keys(initialization.unwrap_or_default()),
+            ..Default::default()
+        };
+        settings.reload();
+        settings
+    }
+
+    /// Replace the workspace folders, keeping the layers of the ones that
+    /// remain. Takes effect on the next [`Settings::reload`].
+    pub fn set_folders(&mut self, uris: Vec<Url>) {
+        let mut previous = std::mem::take(&mut self.folders);
+        for uri in uris {
+            let folder = match previous.iter().position(|folder| folder.uri == uri) {
+                Some(i) => previous.swap_remove(i),
+                None => Folder {
+                    root: uri.to_file_path().ok(),
+                    uri,
+                    client: Value::Null,
+                    file: Value::Null,
+                    config: Arc::default(),
+                },
+            };
+            self.folders.push(folder);
+        }
+    }
+
+    pub fn folders(&self) -> Vec<Url> {
+        self.folders
+            .iter()
+            .map(|folder| folder.uri.clone())
+            .collect()
+    }
+
+    /// Set the client's settings for a folder, or for every folder and
+    /// documents outside them when `folder` is `None`. Takes effect on the
+    /// next [`Settings::reload`].
+    pub fn set_client_settings(&mut self, folder: Option<&Url>, settings: Value) {
+        let settings = without_client_keys(settings);
+        if folder.is_none() {
+            self.client = settings.clone();
+        }
+        for entry in &mut self.folders {
+            if folder.is_none_or(|uri| *uri == entry.uri) {
+                entry.client = settings.clone();
+            }
+        }
+    }
+
+    /// Reread every folder's configuration file and rebuild the
+    /// configurations, returning the problems found
+    pub fn reload(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let fallback = Config::from_layers(None, &[&self.initialization, &self.client]);
+        self.fallback = Arc::new(fallback.unwrap_or_else(|err| {
+            problems.push(format!("Invalid settings: {}", err));
+            Config::default()
+        }));
+        report_unknown_rules(&self.fallback, &mut problems);
+
+        for folder in &mut self.folders {
+            let root = folder.root.as_deref();
+            folder.file = match root.and_then(config_file) {
+                Some(path) => read_config_file(&path).unwrap_or_else(|err| {
+                    problems.push(format!("{}: {}", path.display(), err));
+                    Value::Null
+                }),
+                None => Value::Null,
+            };
+            let layers = [&self.initialization, &folder.client, &folder.file];
+            let config = Config::from_layers(root, &layers).unwrap_or_else(|err| {
+                problems.push(format!("Invalid settings for {}: {}", folder.uri, err));
+                // Leave out the file rather than lose the client's settings too
+                Config::from_layers(root, &layers[..2]).unwrap_or_default()
+            });
+            report_unknown_rules(&config, &mut problems);
+            folder.config = Arc::new(config);
+        }
+        problems
+    }
+
+    /// The folder containing a document, the innermost one when nested
+    fn folder_for(&self, uri: &Url) -> Option<&Folder> {
+        let path = uri.to_file_path().ok()?;
+        self.folders
+            .iter()
+            .filter(|folder| {
+                folder
+                    .root
+                    .as_ref()
+                    .is_some_and(|root| path.starts_with(root))
+            })
+            .max_by_key(|folder| {
+                folder
+                    .root
+                    .as_ref()
+                    .map_or(0, |root| root.as_os_str().len())
+            })
+    }
+
+    /// The configuration that applies to a document
+    pub fn for_uri(&self, uri: &Url) -> Arc<Config> {
+        self.folder_for(uri)
+            .map_or_else(|| self.fallback.clone(), |folder| folder.config.clone())
+    }
+
+    /// The root of the folder containing a document
+    pub fn root_for(&self, uri: &Url) -> Option<PathBuf> {
+        self.folder_for(uri)?.root.clone()
+    }
+}
+
+/// JSON Schema for `luat.toml`, `.luatrc` and the client's `luat` settings
+pub fn schema() -> Value {
+    let level = json!({
+        "enum": ["off", "hint", "info", "warning", "error"]
+    });
+    let rules: Map<String, Value> = RULES
+        .iter()
+        .map(|rule| {
+            let mut property = level.clone();
+            property["description"] = json!(rule.description);
+            (rule.code.to_string(), property)
+        })
+        .collect();
+    let rule_levels = json!({
+        "type": "object",
+        "description": "Severities by rule. The luat/ prefix is optional, and a name ending in /* covers every rule with that prefix, e.g. a11y/*.",
+        "properties": rules,
+        "additionalProperties": level,
+    });
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Luat project configuration",
+        "type": "object",
+        "properties": {
+            "lua": {
+                "type": "object",
+                "properties": {
+                    "version": {
+                        "description": "Lua dialect scripts and expressions are checked against",
+                        "enum": ["5.1", "luajit", "5.4"],
+                        "default": "5.4"
+                    }
+                },
+                "additionalProperties": false
+            },
+            "modules": {
+                "type": "object",
+                "properties": {
+                    "roots": {
+                        "description": "Directories require paths are resolved against, relative to the project. Defaults to the document's directory, its two parents and their src directories.",
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                },
+                "additionalProperties": false
+            },
+            "lint": {
+                "type": "object",
+                "properties": {
+                    "rules": rule_levels,
+                    "overrides": {
+                        "description": "Severities for parts of the project, applied in order after rules",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "files": {
+                                    "description": "Globs relative to the project, e.g. legacy/**",
+                                    "type": "array",
+                                    "items": { "type": "string" }
+                                },
+                                "rules": rule_levels
+                            },
+                            "additionalProperties": false
+                        }
+                    },
+                    "sanitizers": {
+                        "description": "Functions that make a value from props safe, by name or dotted path",
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                },
+                "additionalProperties": false
+            },
+            "format": {
+                "type": "object",
+                "properties": {
+                    "indent-width": {
+                        "description": "Spaces per indentation level",
+                        "type": "integer",
+                        "minimum": 0,
+                        "default": 2
+                    },
+                    "use-tabs": {
+                        "description": "Indent with tabs instead of spaces",
+                        "type": "boolean",
+                        "default": false
+                    }
+                },
+                "additionalProperties": false
+            }
+        },
+        "additionalProperties": false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::diagnostics::rules::RuleLevel;
+
+    #[test]
+    fn test_layers() {
+        let root = Path::new("/project");
+        let client = json!({
+            "lua": { "version": "5.1" },
+            "lint": { "rules": { "unused-import": "off", "a11y/*": "error" } },
+            "format": { "indent-width": 4 }
+        });
+        let file: Value = toml::from_str(
+            "[modules]\nroots = [\"src\"]\n\n[lint.rules]\nunused-import = \"warning\"\n",
+        )
+        .unwrap();
+        let config = Config::from_layers(Some(root), &[&Value::Null, &client, &file]).unwrap();
+
+        assert_eq!(config.lua.version, LuaVersion::Lua51);
+        assert_eq!(config.modules.roots, vec![root.join("src")]);
+        assert_eq!(config.format.indent(), "    ");
+        assert_eq!(
+            config.lint.rules.get("unused-import"),
+            Some(&RuleLevel::Warning)
+        );
+        assert_eq!(config.lint.rules.get("a11y/*"), Some(&RuleLevel::Error));
+        assert_eq!(config.lint.root.as_deref(), Some(root));
+
+        let without_client = Config::from_layers(None, &[&client, &Value::Null]).unwrap();
+        assert_eq!(without_client.lua.version, LuaVersion::Lua51);
+
+        let invalid = json!({ "lua": { "version": "6.0" } });
+        assert!(Config::from_layers(None, &[&invalid]).is_err());
+        let invalid = json!({ "lint": { "rules": { "unused-import": "warn" } } });
+        assert!(Config::from_layers(None, &[&invalid]).is_err());
+
+        // Misspelled keys are errors rather than ignored
+        let typo: Value = toml::from_str("[lint]\nrulez = {}\n").unwrap();
+        let err = Config::from_layers(None, &[&typo]).unwrap_err();
+        assert!(err.contains("rulez"), "{}", err);
+        let typo = json!({ "modules": { "root": ["src"] } });
+        assert!(Config::from_layers(None, &[&typo]).is_err());
+    }
+
+    #[test]
+    fn test_client_keys_are_not_settings() {
+        let mut settings = Settings::new(None);
+        let client = json!({
+            "server": { "path": "" },
+            "trace": { "server": "off" },
+            "lua": { "version": "5.1" }
+        });
+        settings.set_client_settings(None, client);
+        assert!(settings.reload().is_empty());
+
+        settings.set_client_settings(None, json!({ "lint": { "rule": {} } }));
+        assert_eq!(settings.reload().len(), 1);
+
+        let client = json!({ "lint": { "rules": { "unused-imports": "off" } } });
+        settings.set_client_settings(None, client);
+        assert_eq!(
+            settings.reload(),
+            vec!["Unknown rule 'unused-imports' in lint settings"]
+        );
+    }
+
+    #[test]
+    fn test_schema_is_published() {
+        let published: Value = serde_json::from_str(include_str!(
+            "../../../editors/vscode/schemas/luat.schema.json"
+        ))
+        .unwrap();
+        assert_eq!(
+            published,
+            schema(),
+            "run `luat-lsp --config-schema > editors/vscode/schemas/luat.schema.json`"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! CSS support for `<style>` blocks
+
+pub mod properties;
+pub mod scanner;
+
+pub use properties::{property, GLOBAL_VALUES, PROPERTIES};
+pub use scanner::{check, context_at, property_at, CssContext, CssSeverity};
@@ -0,0 +1,500 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Known CSS properties and their keyword values
+
+/// A CSS property with a short description and its keyword values
+#[derive(Debug)]
+pub struct Property {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Keyword values, not counting the global ones every property accepts
+    pub values: &'static [&'static str],
+}
+
+/// Keywords accepted by every property
+pub const GLOBAL_VALUES: &[&str] = &["inherit", "initial", "unset", "revert", "revert-layer"];
+
+const COLORS: &[&str] = &[
+    "currentColor",
+    "transparent",
+    "black",
+    "white",
+    "red",
+    "blue",
+];
+const LENGTHS: &[&str] = &["auto", "0", "fit-content", "max-content", "min-content"];
+const BORDER_STYLES: &[&str] = &[
+    "none", "solid", "dashed", "dotted", "double", "groove", "ridge", "inset", "outset",
+];
+const ALIGN: &[&str] = &[
+    "normal",
+    "stretch",
+    "center",
+    "start",
+    "end",
+    "flex-start",
+    "flex-end",
+    "baseline",
+];
+const JUSTIFY: &[&str] = &[
+    "normal",
+    "center",
+    "start",
+    "end",
+    "flex-start",
+    "flex-end",
+    "space-between",
+    "space-around",
+    "space-evenly",
+    "stretch",
+];
+const OVERFLOW: &[&str] = &["visible", "hidden", "clip", "scroll", "auto"];
+
+macro_rules! property {
+    ($name:literal, $description:literal, $values:expr) => {
+        Property {
+            name: $name,
+            description: $description,
+            values: $values,
+        }
+    };
+}
+
+pub const PROPERTIES: &[Property] = &[
+    property!(
+        "align-content",
+        "Distributes space between flex or grid lines on the cross axis.",
+        JUSTIFY
+    ),
+    property!(
+        "align-items",
+        "Aligns flex or grid items on the cross axis.",
+        ALIGN
+    ),
+    property!(
+        "align-self",
+        "Overrides `align-items` for a single item.",
+        ALIGN
+    ),
+    property!(
+        "animation",
+        "Shorthand for the animation properties.",
+        &["none"]
+    ),
+    property!(
+        "appearance",
+        "Controls native styling of form controls.",
+        &["none", "auto"]
+    ),
+    property!(
+        "aspect-ratio",
+        "Preferred aspect ratio of the box.",
+        &["auto"]
+    ),
+    property!(
+        "background",
+        "Shorthand for the background properties.",
+        &["none"]
+    ),
+    property!(
+        "background-color",
+        "Background color of the element.",
+        COLORS
+    ),
+    property!(
+        "background-image",
+        "Background images of the element.",
+        &["none"]
+    ),
+    property!(
+        "background-position",
+        "Initial position of background images.",
+        &["top", "right", "bottom", "left", "center"]
+    ),
+    property!(
+        "background-repeat",
+        "How background images are repeated.",
+        &[
+            "repeat",
+            "repeat-x",
+            "repeat-y",
+            "no-repeat",
+            "space",
+            "round"
+        ]
+    ),
+    property!(
+        "background-size",
+        "Size of background images.",
+        &["auto", "cover", "contain"]
+    ),
+    property!(
+        "border",
+        "Shorthand for border width, style and color.",
+        BORDER_STYLES
+    ),
+    property!(
+        "border-bottom",
+        "Shorthand for the bottom border.",
+        BORDER_STYLES
+    ),
+    property!(
+        "border-collapse",
+        "Whether table cell borders are shared.",
+        &["collapse", "separate"]
+    ),
+    property!("border-color", "Color of the borders.", COLORS),
+    property!(
+        "border-left",
+        "Shorthand for the left border.",
+        BORDER_STYLES
+    ),
+    property!("border-radius", "Rounds the corners of the border.", &[]),
+    property!(
+        "border-right",
+        "Shorthand for the right border.",
+        BORDER_STYLES
+    ),
+    property!("border-style", "Line style of the borders.", BORDER_STYLES),
+    property!("border-top", "Shorthand for the top border.", BORDER_STYLES),
+    property!(
+        "border-width",
+        "Width of the borders.",
+        &["thin", "medium", "thick"]
+    ),
+    property!(
+        "bottom",
+        "Offset from the bottom of the containing block.",
+        &["auto"]
+    ),
+    property!(
+        "box-shadow",
+        "Shadows around the element's frame.",
+        &["none"]
+    ),
+    property!(
+        "box-sizing",
+        "Whether width and height include padding and border.",
+        &["content-box", "border-box"]
+    ),
+    property!(
+        "clear",
+        "Whether the element moves below preceding floats.",
+        &[
+            "none",
+            "left",
+            "right",
+            "both",
+            "inline-start",
+            "inline-end"
+        ]
+    ),
+    property!("color", "Foreground color of text.", COLORS),
+    property!("column-gap", "Gap between columns.", &["normal"]),
+    property!(
+        "content",
+        "Content generated by `::before` and `::after`.",
+        &["none", "normal", "open-quote", "close-quote"]
+    ),
+    property!(
+        "cursor",
+        "Mouse cursor shown over the element.",
+        &[
+            "auto",
+            "default",
+            "pointer",
+            "text",
+            "move",
+            "not-allowed",
+            "wait",
+            "help",
+            "crosshair",
+            "grab",
+            "grabbing"
+        ]
+    ),
+    property!(
+        "display",
+        "Display type of the box and its children.",
+        &[
+            "block",
+            "inline",
+            "inline-block",
+            "flex",
+            "inline-flex",
+            "grid",
+            "inline-grid",
+            "contents",
+            "none",
+            "table",
+            "list-item"
+        ]
+    ),
+    property!("fill", "Fill color of SVG shapes.", COLORS),
+    property!(
+        "filter",
+        "Graphical effects such as blur or color shifts.",
+        &["none"]
+    ),
+    property!(
+        "flex",
+        "Shorthand for flex grow, shrink and basis.",
+        &["none", "auto"]
+    ),
+    property!("flex-basis", "Initial main size of a flex item.", LENGTHS),
+    property!(
+        "flex-direction",
+        "Direction of the flex container's main axis.",
+        &["row", "row-reverse", "column", "column-reverse"]
+    ),
+    property!("flex-grow", "How much a flex item grows.", &[]),
+    property!("flex-shrink", "How much a flex item shrinks.", &[]),
+    property!(
+        "flex-wrap",
+        "Whether flex items wrap onto multiple lines.",
+        &["nowrap", "wrap", "wrap-reverse"]
+    ),
+    property!(
+        "float",
+        "Places the element on the left or right side of its container.",
+        &["none", "left", "right", "inline-start", "inline-end"]
+    ),
+    property!(
+        "font",
+        "Shorthand for the font properties.",
+        &["caption", "icon", "menu", "small-caption"]
+    ),
+    property!(
+        "font-family",
+        "Prioritized list of font families.",
+        &["serif", "sans-serif", "monospace", "cursive", "system-ui"]
+    ),
+    property!(
+        "font-size",
+        "Size of the font.",
+        &["small", "medium", "large", "x-large", "smaller", "larger"]
+    ),
+    property!(
+        "font-style",
+        "Normal, italic or oblique face.",
+        &["normal", "italic", "oblique"]
+    ),
+    property!(
+        "font-weight",
+        "Weight (boldness) of the font.",
+        &["normal", "bold", "bolder", "lighter", "100", "400", "700", "900"]
+    ),
+    property!("gap", "Gaps between rows and columns.", &["normal"]),
+    property!(
+        "grid-area",
+        "Places a grid item in a named area or lines.",
+        &["auto"]
+    ),
+    property!(
+        "grid-column",
+        "Places a grid item between column lines.",
+        &["auto"]
+    ),
+    property!(
+        "grid-row",
+        "Places a grid item between row lines.",
+        &["auto"]
+    ),
+    property!("grid-template-areas", "Named grid areas.", &["none"]),
+    property!(
+        "grid-template-columns",
+        "Column track sizes of the grid.",
+        &["none", "subgrid"]
+    ),
+    property!(
+        "grid-template-rows",
+        "Row track sizes of the grid.",
+        &["none", "subgrid"]
+    ),
+    property!("height", "Height of the element.", LENGTHS),
+    property!(
+        "inset",
+        "Shorthand for top, right, bottom and left.",
+        &["auto"]
+    ),
+    property!(
+        "justify-content",
+        "Distributes space between items on the main axis.",
+        JUSTIFY
+    ),
+    property!(
+        "justify-items",
+        "Default `justify-self` for all items.",
+        ALIGN
+    ),
+    property!(
+        "justify-self",
+        "Aligns a single item inside its area on the inline axis.",
+        ALIGN
+    ),
+    property!(
+        "left",
+        "Offset from the left of the containing block.",
+        &["auto"]
+    ),
+    property!("letter-spacing", "Spacing between characters.", &["normal"]),
+    property!("line-height", "Height of a line box.", &["normal"]),
+    property!(
+        "list-style",
+        "Shorthand for the list style properties.",
+        &["none", "disc", "circle", "square", "decimal", "inside", "outside"]
+    ),
+    property!("margin", "Margin on all four sides.", &["auto", "0"]),
+    property!("margin-bottom", "Bottom margin.", &["auto", "0"]),
+    property!("margin-left", "Left margin.", &["auto", "0"]),
+    property!("margin-right", "Right margin.", &["auto", "0"]),
+    property!("margin-top", "Top margin.", &["auto", "0"]),
+    property!(
+        "max-height",
+        "Maximum height of the element.",
+        &["none", "fit-content", "max-content", "min-content"]
+    ),
+    property!(
+        "max-width",
+        "Maximum width of the element.",
+        &["none", "fit-content", "max-content", "min-content"]
+    ),
+    property!("min-height", "Minimum height of the element.", LENGTHS),
+    property!("min-width", "Minimum width of the element.", LENGTHS),
+    property!(
+        "object-fit",
+        "How replaced content such as images fits its box.",
+        &["fill", "contain", "cover", "none", "scale-down"]
+    ),
+    property!("opacity", "Opacity of the element, from 0 to 1.", &[]),
+    property!(
+        "outline",
+        "Shorthand for outline width, style and color.",
+        BORDER_STYLES
+    ),
+    property!(
+        "overflow",
+        "What happens to content that overflows the box.",
+        OVERFLOW
+    ),
+    property!("overflow-x", "Horizontal overflow behavior.", OVERFLOW),
+    property!("overflow-y", "Vertical overflow behavior.", OVERFLOW),
+    property!("padding", "Padding on all four sides.", &["0"]),
+    property!("padding-bottom", "Bottom padding.", &["0"]),
+    property!("padding-left", "Left padding.", &["0"]),
+    property!("padding-right", "Right padding.", &["0"]),
+    property!("padding-top", "Top padding.", &["0"]),
+    property!(
+        "pointer-events",
+        "Whether the element can be the target of pointer events.",
+        &["auto", "none"]
+    ),
+    property!(
+        "position",
+        "Positioning scheme of the element.",
+        &["static", "relative", "absolute", "fixed", "sticky"]
+    ),
+    property!(
+        "right",
+        "Offset from the right of the containing block.",
+        &["auto"]
+    ),
+    property!("row-gap", "Gap between rows.", &["normal"]),
+    property!("stroke", "Stroke color of SVG shapes.", COLORS),
+    property!(
+        "text-align",
+        "Horizontal alignment of inline content.",
+        &["left", "right", "center", "justify", "start", "end"]
+    ),
+    property!(
+        "text-decoration",
+        "Decorative lines on text.",
+        &["none", "underline", "overline", "line-through"]
+    ),
+    property!(
+        "text-overflow",
+        "How overflowing inline content is signaled.",
+        &["clip", "ellipsis"]
+    ),
+    property!(
+        "text-transform",
+        "Capitalization of text.",
+        &["none", "capitalize", "uppercase", "lowercase"]
+    ),
+    property!(
+        "top",
+        "Offset from the top of the containing block.",
+        &["auto"]
+    ),
+    property!(
+        "transform",
+        "Transformations such as rotate, scale and translate.",
+        &["none"]
+    ),
+    property!(
+        "transition",
+        "Shorthand for the transition properties.",
+        &["none", "all"]
+    ),
+    property!(
+        "user-select",
+        "Whether the user can select text.",
+        &["auto", "none", "text", "all"]
+    ),
+    property!(
+        "vertical-align",
+        "Vertical alignment of inline or table-cell boxes.",
+        &[
+            "baseline",
+            "top",
+            "middle",
+            "bottom",
+            "text-top",
+            "text-bottom",
+            "sub",
+            "super"
+        ]
+    ),
+    property!(
+        "visibility",
+        "Shows or hides the element without changing layout.",
+        &["visible", "hidden", "collapse"]
+    ),
+    property!(
+        "white-space",
+        "How white space inside the element is handled.",
+        &[
+            "normal",
+            "nowrap",
+            "pre",
+            "pre-wrap",
+            "pre-line",
+            "break-spaces"
+        ]
+    ),
+    property!("width", "Width of the element.", LENGTHS),
+    property!(
+        "word-break",
+        "Where lines may break inside words.",
+        &["normal", "break-all", "keep-all", "break-word"]
+    ),
+    property!(
+        "z-index",
+        "Stacking order of positioned elements.",
+        &["auto"]
+    ),
+];
+
+/// Look up a property by name
+pub fn property(name: &str) -> Option<&'static Property> {
+    PROPERTIES
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Whether a property name is valid even though it isn't in [`PROPERTIES`]:
+/// custom properties and vendor-prefixed ones
+pub fn is_extension(name: &str) -> bool {
+    name.starts_with("--") || (name.starts_with('-') && name[1..].contains('-'))
+}
@@ -0,0 +1,395 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! A small structural scanner for CSS.
+//!
+//! It doesn't build a full CSS tree; it only tracks blocks, comments and
+//! strings, which is enough to tell selectors from declarations, find the
+//! property under the cursor and report syntax errors. Nested rules are
+//! supported: text inside a block that runs into a `{` is a selector,
+//! text that runs into `;` or `}` is a declaration.
+
+use super::properties;
+use crate::syntax::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CssSeverity {
+    Error,
+    Warning,
+}
+
+/// A syntax problem in a style sheet. Spans are relative to the CSS source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssError {
+    pub span: Span,
+    pub message: String,
+    pub severity: CssSeverity,
+}
+
+/// What the cursor is on inside a style sheet
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssContext {
+    /// A selector or at-rule prelude
+    Selector,
+    /// A property name inside a declaration block
+    Property,
+    /// The value of a declaration
+    Value { property: String },
+    /// A comment or string
+    Other,
+}
+
+/// A point where a segment of CSS text ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    /// `{` opening a block
+    Open(usize),
+    /// `}` closing a block
+    Close(usize),
+    /// `;` ending a declaration or at-rule statement
+    Semicolon(usize),
+}
+
+struct Scanner<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    errors: Vec<CssError>,
+    /// Comments and strings skipped so far, and whether they were closed
+    skipped: Vec<(Span, bool)>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            bytes: src.as_bytes(),
+            pos: 0,
+            errors: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, span: Span, message: &str) {
+        self.errors.push(CssError {
+            span,
+            message: message.to_string(),
+            severity: CssSeverity::Error,
+        });
+    }
+
+    /// Advance to the next event, skipping comments and strings
+    fn next_event(&mut self) -> Option<Event> {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            let start = self.pos;
+            match b {
+                b'/' if self.bytes.get(self.pos + 1) == Some(&b'*') => {
+                    let closed = match self.src[self.pos + 2..].find("*/") {
+                        Some(i) => {
+                            self.pos += i + 4;
+                            true
+                        }
+                        None => {
+                            self.pos = self.bytes.len();
+                            self.error(Span::new(start, self.pos), "Unclosed comment");
+                            false
+                        }
+                    };
+                    self.skipped.push((Span::new(start, self.pos), closed));
+                }
+                b'"' | b'\'' => self.string(b),
+                b'\\' => self.pos += 2,
+                b'{' => {
+                    self.pos += 1;
+                    return Some(Event::Open(start));
+                }
+                b'}' => {
+                    self.pos += 1;
+                    return Some(Event::Close(start));
+                }
+                b';' => {
+                    self.pos += 1;
+                    return Some(Event::Semicolon(start));
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.pos = self.bytes.len();
+        None
+    }
+
+    fn string(&mut self, quote: u8) {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'\\' => self.pos += 2,
+                b'\n' => break,
+                _ if b == quote => {
+                    self.pos += 1;
+                    self.skipped.push((Span::new(start, self.pos), true));
+                    return;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.pos = self.pos.min(self.bytes.len());
+        self.skipped.push((Span::new(start, self.pos), false));
+        self.error(Span::new(start, self.pos), "Unclosed string");
+    }
+}
+
+/// Check a style sheet for syntax errors and unknown properties
+pub fn check(src: &str) -> Vec<CssError> {
+    let mut scanner = Scanner::new(src);
+    let mut open: Vec<usize> = Vec::new();
+    let mut segment_start = 0;
+    let mut problems = Vec::new();
+
+    while let Some(event) = scanner.next_event() {
+        match event {
+            Event::Open(pos) => {
+                if src[segment_start..pos].trim().is_empty() {
+                    problems.push(error(
+                        Span::new(pos, pos + 1),
+                        "Expected a selector before '{'",
+                    ));
+                }
+                open.push(pos);
+                segment_start = pos + 1;
+            }
+            Event::Close(pos) => {
+                if open.pop().is_some() {
+                    check_declaration(src, Span::new(segment_start, pos), &mut problems);
+                } else {
+                    problems.push(error(Span::new(pos, pos + 1), "Unexpected '}'"));
+                }
+                segment_start = pos + 1;
+            }
+            Event::Semicolon(pos) => {
+                if !open.is_empty() {
+                    check_declaration(src, Span::new(segment_start, pos), &mut problems);
+                }
+                segment_start = pos + 1;
+            }
+        }
+    }
+
+    for pos in open {
+        problems.push(error(Span::new(pos, pos + 1), "Unclosed '{' - missing '}'"));
+    }
+
+    let mut errors = scanner.errors;
+    errors.extend(problems);
+    errors.sort_by_key(|e| e.span.start);
+    errors
+}
+
+fn error(span: Span, message: &str) -> CssError {
+    CssError {
+        span,
+        message: message.to_string(),
+        severity: CssSeverity::Error,
+    }
+}
+
+/// Check a `property: value` declaration inside a block
+fn check_declaration(src: &str, segment: Span, problems: &mut Vec<CssError>) {
+    let text = strip_comments(segment.text(src));
+    let trimmed = text.trim();
+    // Empty declarations (`;;`) are allowed, at-rules like `@apply` are not ours to check
+    if trimmed.is_empty() || trimmed.starts_with('@') {
+        return;
+    }
+    let leading = text.len() - text.trim_start().len();
+    let start = segment.start + leading;
+
+    let Some(colon) = trimmed.find(':') else {
+        problems.push(error(
+            Span::new(start, start + trimmed.len()),
+            "Expected ':' after property name",
+        ));
+        return;
+    };
+
+    let name = trimmed[..colon].trim_end();
+    let name_span = Span::new(start, start + name.len());
+    if name.is_empty() {
+        problems.push(error(
+            Span::new(start, start + 1),
+            "Expected a property name",
+        ));
+        return;
+    }
+    if !name
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        problems.push(error(
+            name_span,
+            &format!("Invalid property name '{}'", name),
+        ));
+        return;
+    }
+
+    let value = trimmed[colon + 1..].trim();
+    if value.is_empty() || value == "!important" {
+        problems.push(error(
+            Span::new(start, start + trimmed.len()),
+            &format!("Expected a value for '{}'", name),
+        ));
+        return;
+    }
+
+    if properties::property(name).is_none() && !properties::is_extension(name) {
+        problems.push(CssError {
+            span: name_span,
+            message: format!("Unknown property '{}'", name),
+            severity: CssSeverity::Warning,
+        });
+    }
+}
+
+/// Blank out comments, keeping byte offsets intact
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("*/")
+            .map_or(rest.len(), |i| start + i + 2);
+        out.extend(std::iter::repeat_n(' ', end - start));
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Work out what the cursor is on
+pub fn context_at(src: &str, offset: usize) -> CssContext {
+    let mut scanner = Scanner::new(src);
+    let mut depth = 0usize;
+    let mut segment_start = 0;
+
+    while let Some(event) = scanner.next_event() {
+        let (Event::Open(pos) | Event::Close(pos) | Event::Semicolon(pos)) = event;
+        if pos >= offset {
+            break;
+        }
+        match event {
+            Event::Open(_) => depth += 1,
+            Event::Close(_) => depth = depth.saturating_sub(1),
+            Event::Semicolon(_) => {}
+        }
+        segment_start = pos + 1;
+    }
+
+    // An unclosed comment or string runs to the end, including its last position
+    let in_skipped = scanner
+        .skipped
+        .iter()
+        .any(|(span, closed)| span.start < offset && (offset < span.end || !closed));
+    if in_skipped {
+        return CssContext::Other;
+    }
+
+    if depth == 0 {
+        return CssContext::Selector;
+    }
+    let segment = strip_comments(&src[segment_start..offset.min(src.len())]);
+    match segment.find(':') {
+        Some(colon) => CssContext::Value {
+            property: segment[..colon].trim().to_string(),
+        },
+        None => CssContext::Property,
+    }
+}
+
+/// The property name at the offset, if the cursor is on one
+pub fn property_at(src: &str, offset: usize) -> Option<(Span, &str)> {
+    let bytes = src.as_bytes();
+    let is_name = |b: u8| b.is_ascii_alphanumeric() || b == b'-' || b == b'_';
+
+    let mut start = offset.min(bytes.len());
+    while start > 0 && is_name(bytes[start - 1]) {
+        start -= 1;
+    }
+    let mut end = offset.min(bytes.len());
+    while end < bytes.len() && is_name(bytes[end]) {
+        end += 1;
+    }
+    if start == end || context_at(src, start) != CssContext::Property {
+        return None;
+    }
+
+    // Must be followed by a colon to be a property rather than a nested selector
+    if !src[end..].trim_start().starts_with(':') {
+        return None;
+    }
+    Some((Span::new(start, end), &src[start..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(src: &str) -> Vec<(&str, CssSeverity)> {
+        let errors = check(src);
+        errors
+            .iter()
+            .map(|e| (e.span.text(src), e.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_valid_css() {
+        let src = ".card {\n  color: red;\n  --gap: 4px;\n  -webkit-appearance: none;\n}\n@media (max-width: 600px) {\n  .card { display: none }\n}\n.a { &:hover { color: blue } }";
+        assert_eq!(check(src), vec![]);
+    }
+
+    #[test]
+    fn test_check_errors() {
+        let src = ".a { colr: red; margin }\n.b { color: ; }\n/* open";
+        assert_eq!(
+            messages(src),
+            vec![
+                ("colr", CssSeverity::Warning),
+                ("margin", CssSeverity::Error),
+                ("color:", CssSeverity::Error),
+                ("/* open", CssSeverity::Error),
+            ]
+        );
+
+        assert_eq!(messages(".a { color: red"), vec![("{", CssSeverity::Error)]);
+    }
+
+    #[test]
+    fn test_context_at() {
+        let src = ".a { color: re } /* x */ .b { dis }";
+        assert_eq!(context_at(src, 1), CssContext::Selector);
+        assert_eq!(
+            context_at(src, src.find("re ").unwrap() + 2),
+            CssContext::Value {
+                property: "color".to_string()
+            }
+        );
+        assert_eq!(
+            context_at(src, src.find("x */").unwrap()),
+            CssContext::Other
+        );
+        assert_eq!(
+            context_at(src, src.find("dis").unwrap() + 3),
+            CssContext::Property
+        );
+
+        let (span, name) = property_at(src, 7).unwrap();
+        assert_eq!((span, name), (Span::new(5, 10), "color"));
+    }
+
+    #[test]
+    fn test_property_at_past_the_end() {
+        let src = "\n.card { color: red;\n";
+        assert_eq!(property_at(src, src.len() + 4), None);
+    }
+}
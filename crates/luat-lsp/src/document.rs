@@ -1,27 +1,91 @@
 // Copyright 2026 Maravilla Labs
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use ropey::Rope;
-use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+use ropey::{Rope, RopeSlice};
+use std::sync::Arc;
+use tower_lsp::lsp_types::{
+    Position, PositionEncodingKind, Range, TextDocumentContentChangeEvent, Url,
+};
 
+use crate::config::Config;
+use crate::lua::{LuaVersion, ScopeModel};
 use crate::regions::{DocumentRegions, Region};
+use crate::syntax::{self, Edit, Replaced, Span, SyntaxTree};
 
-/// Represents an open .luat document
+/// How the `character` field of an LSP position is counted.
+///
+/// Internally all offsets are byte offsets into the document text; the
+/// encoding only matters when converting to and from LSP positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionEncoding {
+    Utf8,
+    /// The LSP default when the client doesn't negotiate anything else
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Pick the encoding to use from those the client supports, preferring
+    /// UTF-8 since it matches our internal offsets
+    pub fn negotiate(client: Option<&[PositionEncodingKind]>) -> Self {
+        let supported = client.unwrap_or_default();
+        if supported.contains(&PositionEncodingKind::UTF8) {
+            PositionEncoding::Utf8
+        } else if supported.contains(&PositionEncodingKind::UTF32) {
+            PositionEncoding::Utf32
+        } else {
+            PositionEncoding::Utf16
+        }
+    }
+
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+            PositionEncoding::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+}
+
+/// Represents an open .luat document.
+///
+/// The server keeps each document behind an `Arc`, so a snapshot handed to
+/// background work stays unchanged while edits go to a copy.
+#[derive(Clone)]
 pub struct Document {
     uri: Url,
+    /// Version from the client, increasing with every change
+    version: Option<i32>,
     /// The document text stored as a rope for efficient edits
     rope: Rope,
+    /// The same text in one piece, for parsing and analysis to borrow
+    text: String,
+    /// How LSP positions for this document are encoded
+    encoding: PositionEncoding,
+    /// Syntax tree of the current text
+    tree: SyntaxTree,
     /// Cached regions (invalidated on change)
     regions: Option<DocumentRegions>,
+    /// Scopes and bindings of the Lua scripts
+    lua: ScopeModel,
+    /// Settings of the workspace folder the document is in
+    config: Arc<Config>,
 }
 
 impl Document {
-    pub fn new(uri: Url, text: String) -> Self {
+    pub fn new(uri: Url, text: String, encoding: PositionEncoding) -> Self {
         let rope = Rope::from_str(&text);
         let mut doc = Self {
             uri,
+            version: None,
             rope,
+            text,
+            encoding,
+            tree: SyntaxTree::default(),
             regions: None,
+            lua: ScopeModel::default(),
+            config: Arc::default(),
         };
         doc.parse_regions();
         doc
@@ -31,8 +95,16 @@ impl Document {
         &self.uri
     }
 
-    pub fn text(&self) -> String {
-        self.rope.to_string()
+    pub fn version(&self) -> Option<i32> {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: i32) {
+        self.version = Some(version);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
     }
 
     /// Get the underlying rope for advanced text operations
@@ -41,31 +113,88 @@ impl Document {
         &self.rope
     }
 
-    pub fn regions(&self) -> Option<&DocumentRegions> {
-        self.regions.as_ref()
+    pub fn tree(&self) -> &SyntaxTree {
+        &self.tree
+    }
+
+    pub fn lua(&self) -> &ScopeModel {
+        &self.lua
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn lua_version(&self) -> LuaVersion {
+        self.config.lua.version
+    }
+
+    /// Use new settings, rebuilding the scope model if the Lua version changed
+    pub fn set_config(&mut self, config: Arc<Config>) {
+        let version = self.lua_version();
+        self.config = config;
+        if self.lua_version() != version {
+            self.lua = ScopeModel::for_template(&self.tree, &self.text, self.lua_version());
+        }
     }
 
     /// Apply an incremental change to the document
     pub fn apply_change(&mut self, change: &TextDocumentContentChangeEvent) {
-        if let Some(range) = change.range {
+        let edit = if let Some(range) = change.range {
             let start_idx = self.position_to_offset(range.start);
             let end_idx = self.position_to_offset(range.end);
 
-            if let (Some(start), Some(end)) = (start_idx, end_idx) {
-                self.rope.remove(start..end);
-                self.rope.insert(start, &change.text);
-            }
+            let (Some(start), Some(end)) = (start_idx, end_idx) else {
+                return;
+            };
+            let start_char = self.rope.byte_to_char(start);
+            let end_char = self.rope.byte_to_char(end);
+            self.rope.remove(start_char..end_char);
+            self.rope.insert(start_char, &change.text);
+            self.text.replace_range(start..end, &change.text);
+
+            Some(Edit {
+                start,
+                old_end: end,
+                new_end: start + change.text.len(),
+            })
         } else {
             // Full document replace
             self.rope = Rope::from_str(&change.text);
-        }
+            self.text = change.text.clone();
+            None
+        };
 
-        // Invalidate and reparse regions
-        self.regions = None;
-        self.parse_regions();
+        // Reparse only the part of the tree affected by the edit
+        let replaced = match edit {
+            Some(edit) => {
+                syntax::reparse(&mut self.tree, &self.text, edit).map(|replaced| (edit, replaced))
+            }
+            None => {
+                self.tree = syntax::parse(&self.text);
+                None
+            }
+        };
+        let Some((edit, replaced)) = replaced else {
+            self.regions = Some(DocumentRegions::from_tree(&self.tree, &self.text));
+            self.lua = ScopeModel::for_template(&self.tree, &self.text, self.lua_version());
+            return;
+        };
+
+        // Everything after the replaced node only moved, and so did the Lua
+        // in it unless the edit touched some
+        if let Some(regions) = &mut self.regions {
+            regions.splice(replaced.old.span(), &replaced.new, &self.text);
+        }
+        if lua_changed(&replaced, edit) {
+            self.lua =
+                ScopeModel::reanalyze(&self.lua, &self.tree, &self.text, self.lua_version(), edit);
+        } else {
+            self.lua.shift(edit.old_end, edit.delta());
+        }
     }
 
-    /// Convert LSP position to rope char offset
+    /// Convert LSP position to a byte offset into the document text
     pub fn position_to_offset(&self, pos: Position) -> Option<usize> {
         let line = pos.line as usize;
         if line >= self.rope.len_lines() {
@@ -73,21 +202,41 @@ impl Document {
         }
 
         let line_start = self.rope.line_to_char(line);
+        let line_slice = self.rope.line(line);
+        let line_len = line_len_chars(line_slice);
         let col = pos.character as usize;
-        let line_len = self.rope.line(line).len_chars();
 
-        if col > line_len {
-            Some(line_start + line_len)
-        } else {
-            Some(line_start + col)
-        }
+        // Columns past the end of the line (or inside a multi-unit
+        // character) snap back to a character boundary on the line
+        let char_in_line = match self.encoding {
+            PositionEncoding::Utf8 => line_slice.byte_to_char(col.min(line_slice.len_bytes())),
+            PositionEncoding::Utf16 => {
+                line_slice.utf16_cu_to_char(col.min(line_slice.len_utf16_cu()))
+            }
+            PositionEncoding::Utf32 => col,
+        };
+
+        Some(
+            self.rope
+                .char_to_byte(line_start + char_in_line.min(line_len)),
+        )
     }
 
-    /// Convert rope char offset to LSP position
+    /// Convert a byte offset into the document text to an LSP position
     pub fn offset_to_position(&self, offset: usize) -> Position {
-        let line = self.rope.char_to_line(offset);
+        let char_idx = self.rope.byte_to_char(offset.min(self.rope.len_bytes()));
+        let line = self.rope.char_to_line(char_idx);
         let line_start = self.rope.line_to_char(line);
-        let col = offset - line_start;
+
+        let col = match self.encoding {
+            PositionEncoding::Utf8 => {
+                self.rope.char_to_byte(char_idx) - self.rope.line_to_byte(line)
+            }
+            PositionEncoding::Utf16 => {
+                self.rope.char_to_utf16_cu(char_idx) - self.rope.char_to_utf16_cu(line_start)
+            }
+            PositionEncoding::Utf32 => char_idx - line_start,
+        };
 
         Position {
             line: line as u32,
@@ -95,6 +244,14 @@ impl Document {
         }
     }
 
+    /// Convert a byte span to an LSP range
+    pub fn span_to_range(&self, span: Span) -> Range {
+        Range {
+            start: self.offset_to_position(span.start),
+            end: self.offset_to_position(span.end),
+        }
+    }
+
     /// Get the region at a given position
     pub fn region_at_position(&self, pos: Position) -> Option<&Region> {
         let offset = self.position_to_offset(pos)?;
@@ -106,16 +263,17 @@ impl Document {
     pub fn get_text_range(&self, range: Range) -> Option<String> {
         let start = self.position_to_offset(range.start)?;
         let end = self.position_to_offset(range.end)?;
-        Some(self.rope.slice(start..end).to_string())
+        Some(self.rope.byte_slice(start..end).to_string())
     }
 
     /// Get the word at a position
     pub fn word_at_position(&self, pos: Position) -> Option<(String, Range)> {
         let offset = self.position_to_offset(pos)?;
-        let text = self.rope.to_string();
+        let text = &self.text;
         let bytes = text.as_bytes();
 
-        // Find word boundaries
+        // Find word boundaries. Word characters are ASCII, so the boundaries
+        // always fall on character boundaries.
         let mut start = offset;
         while start > 0 && is_word_char(bytes[start - 1]) {
             start -= 1;
@@ -131,21 +289,177 @@ impl Document {
         }
 
         let word = text[start..end].to_string();
-        let range = Range {
-            start: self.offset_to_position(start),
-            end: self.offset_to_position(end),
-        };
+        let range = self.span_to_range(Span::new(start, end));
 
         Some((word, range))
     }
 
-    /// Parse document into regions
+    /// Parse document into a syntax tree and regions
     fn parse_regions(&mut self) {
-        let text = self.text();
-        self.regions = Some(DocumentRegions::parse(&text));
+        self.tree = syntax::parse(&self.text);
+        self.regions = Some(DocumentRegions::from_tree(&self.tree, &self.text));
+        self.lua = ScopeModel::for_template(&self.tree, &self.text, self.lua_version());
     }
 }
 
+/// Length of a line in chars, not counting its line break
+/// Whether an edit changed the Lua in the node it was confined to: it
+/// touched a piece of Lua, or the pieces don't line up with the old ones
+/// moved past the edit
+fn lua_changed(replaced: &Replaced, edit: Edit) -> bool {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    replaced.old.lua_pieces(&mut old);
+    replaced.new.lua_pieces(&mut new);
+    old.len() != new.len()
+        || old.iter().zip(&new).any(|(&old, &new)| {
+            let mut moved = old;
+            if old.start >= edit.old_end {
+                moved.shift(edit.old_end, edit.delta());
+            }
+            (old.start < edit.old_end && old.end > edit.start) || moved != new
+        })
+}
+
+fn line_len_chars(line: RopeSlice) -> usize {
+    let len = line.len_chars();
+    let trailing = line
+        .chars_at(len)
+        .reversed()
+        .take(2)
+        .take_while(|c| matches!(c, '\n' | '\r'))
+        .count();
+    len - trailing
+}
+
 fn is_word_char(b: u8) -> bool {
     b.is_ascii_alphanumeric() || b == b'_'
 }
+
+/// The URI of documents built in tests
+#[cfg(test)]
+pub(crate) fn test_uri() -> Url {
+    Url::parse("file:///test.luat").unwrap()
+}
+
+/// A document with UTF-8 positions, for tests
+#[cfg(test)]
+pub(crate) fn test_document(text: &str) -> Document {
+    Document::new(test_uri(), text.to_string(), PositionEncoding::Utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(text: &str, encoding: PositionEncoding) -> Document {
+        Document::new(test_uri(), text.to_string(), encoding)
+    }
+
+    #[test]
+    fn test_position_round_trip_per_encoding() {
+        // "Grüße 😀 {name}": ü/ß are 2 bytes, the emoji is 4 bytes / 2 UTF-16 units
+        let text = "<p>\nGrüße 😀 {name}</p>";
+        let name = text.find("name").unwrap();
+
+        for (encoding, character) in [
+            (PositionEncoding::Utf8, 14),
+            (PositionEncoding::Utf16, 10),
+            (PositionEncoding::Utf32, 9),
+        ] {
+            let doc = doc(text, encoding);
+            let pos = Position::new(1, character);
+            assert_eq!(doc.offset_to_position(name), pos, "{:?}", encoding);
+            assert_eq!(doc.position_to_offset(pos), Some(name), "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_position_past_line_end_clamps() {
+        let doc = doc("ä\nb", PositionEncoding::Utf16);
+        assert_eq!(doc.position_to_offset(Position::new(0, 99)), Some(2));
+        assert_eq!(doc.position_to_offset(Position::new(5, 0)), None);
+    }
+
+    #[test]
+    fn test_word_and_change_with_multibyte_text() {
+        let mut doc = doc("Über {ünd + count}", PositionEncoding::Utf16);
+        let (word, range) = doc.word_at_position(Position::new(0, 13)).unwrap();
+        assert_eq!(word, "count");
+        assert_eq!(
+            range,
+            Range::new(Position::new(0, 12), Position::new(0, 17))
+        );
+
+        doc.apply_change(&TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(0, 12), Position::new(0, 17))),
+            range_length: None,
+            text: "zähler".to_string(),
+        });
+        assert_eq!(doc.text(), "Über {ünd + zähler}");
+        assert_eq!(doc.tree(), &syntax::parse(doc.text()));
+    }
+
+    #[test]
+    fn test_changes_match_a_fresh_analysis() {
+        let text = "<script>\nlocal title = props.title\n</script>\n\
+            <h1>{title}</h1>\n<p>Hello <b>there</b></p>\n\
+            {#each props.items as item}<li>{item}</li>{/each}\n\
+            <script module>\nlocal count = 1\n</script>";
+        let mut doc = test_document(text);
+        // Markup only, markup gaining Lua, then Lua
+        for (old, new) in [
+            ("there", "you"),
+            ("Hello", "Hi {title},"),
+            ("count = 1", "count = 10"),
+        ] {
+            let at = doc.text().find(old).unwrap();
+            doc.apply_change(&TextDocumentContentChangeEvent {
+                range: Some(doc.span_to_range(Span::new(at, at + old.len()))),
+                range_length: None,
+                text: new.to_string(),
+            });
+            let fresh = test_document(doc.text());
+            assert_eq!(doc.tree(), fresh.tree());
+            assert_eq!(doc.regions, fresh.regions);
+            assert_eq!(doc.lua(), fresh.lua());
+        }
+    }
+
+    #[test]
+    fn test_markup_edits_next_to_lua_only_shift_the_scope_model() {
+        let text = "<script>\nlocal Card = require('card')\nlocal title = props.title\n</script>\n\
+            <h1>{title}!</h1>\n{#each props.items as item}<li>{item}</li>{/each}\n\
+            <Card label={title} />\n<script module>\nlocal broken = \n</script>";
+        let mut doc = test_document(text);
+        // Inserting and replacing text touching expressions, tags and names
+        for (at, old, new, lua) in [
+            ("{title}!", "", "Hi ", false),
+            ("!</h1>", "!", "!!", false),
+            ("<li>", "", " ", false),
+            ("{/each}", "", "x", false),
+            (" label", "", " open", false),
+            ("{item}", "{item}", "{item.name}", true),
+        ] {
+            let at = doc.text().find(at).unwrap();
+            let edit = Edit {
+                start: at,
+                old_end: at + old.len(),
+                new_end: at + new.len(),
+            };
+            let mut text = doc.text().to_string();
+            text.replace_range(at..at + old.len(), new);
+            let mut tree = doc.tree().clone();
+            let replaced = syntax::reparse(&mut tree, &text, edit).unwrap();
+            assert_eq!(lua_changed(&replaced, edit), lua, "{new:?}");
+
+            doc.apply_change(&TextDocumentContentChangeEvent {
+                range: Some(doc.span_to_range(Span::new(at, at + old.len()))),
+                range_length: None,
+                text: new.to_string(),
+            });
+            let fresh = test_document(doc.text());
+            assert_eq!(doc.tree(), fresh.tree());
+            assert_eq!(doc.lua(), fresh.lua(), "{new:?}");
+        }
+    }
+}
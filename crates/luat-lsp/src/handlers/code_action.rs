@@ -0,0 +1,205 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, Range, TextEdit, WorkspaceEdit,
+};
+
+use crate::document::Document;
+use crate::handlers::definition::require_path_for;
+use crate::handlers::diagnostics::components::unbound_components;
+use crate::handlers::files::find_files;
+
+/// Quick fixes for the diagnostics in `range`: importing components that
+/// are used without a `require`, from matching files under `roots`
+pub fn get_code_actions(
+    doc: &Document,
+    range: Range,
+    diagnostics: &[Diagnostic],
+    roots: &[PathBuf],
+) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+
+    for component in unbound_components(doc) {
+        let component_range = doc.span_to_range(component.span);
+        if component_range.end < range.start || range.end < component_range.start {
+            continue;
+        }
+
+        let mut files = Vec::new();
+        for root in roots {
+            let file_name = format!("{}.luat", component.name);
+            find_files(root, &|name| name == file_name, &mut files);
+        }
+        let mut paths: Vec<String> = files
+            .iter()
+            .filter_map(|file| require_path_for(file, doc))
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        let fixed: Vec<Diagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.range == component_range && d.source.as_deref() == Some("luat"))
+            .cloned()
+            .collect();
+        let preferred = paths.len() == 1;
+        for path in paths {
+            let edit = import_edit(doc, &component.name, &path);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Import '{}' from \"{}\"", component.name, path),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(fixed.clone()),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(doc.uri().clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                is_preferred: Some(preferred),
+                ..Default::default()
+            }));
+        }
+    }
+
+    actions
+}
+
+/// Insert `local Name = require("path")` after the last import of the
+/// instance script, at its start, or in a new script if there is none
+fn import_edit(doc: &Document, name: &str, path: &str) -> TextEdit {
+    let text = doc.text();
+    let statement = format!("local {} = require(\"{}\")", name, path);
+    let scripts = doc.tree().lua_scripts();
+
+    let Some(script) = scripts.iter().find(|script| !script.is_module()) else {
+        // A new instance script goes after the module script, if any
+        let indent = doc.config().format.indent();
+        let (offset, new_text) = match scripts.first() {
+            Some(module) => (
+                module.span.end,
+                format!("\n\n<script>\n{}{}\n</script>", indent, statement),
+            ),
+            None => (
+                0,
+                format!("<script>\n{}{}\n</script>\n\n", indent, statement),
+            ),
+        };
+        return insert(doc, offset, new_text);
+    };
+
+    let content = script.content;
+    let last_import = doc
+        .lua()
+        .symbols
+        .iter()
+        .filter(|symbol| symbol.require.is_some() && content.contains(symbol.declaration.start))
+        .map(|symbol| symbol.declaration)
+        .max_by_key(|declaration| declaration.end);
+    if let Some(declaration) = last_import {
+        let line_start = text[..declaration.start].rfind('\n').map_or(0, |i| i + 1);
+        let indent: String = text[line_start..]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        return insert(doc, declaration.end, format!("\n{}{}", indent, statement));
+    }
+
+    // Before the first line of the script, with its indentation
+    let body = content.text(text);
+    match body.strip_prefix('\n') {
+        Some(rest) => {
+            let indent: String = rest
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect();
+            insert(doc, content.start + 1, format!("{}{}\n", indent, statement))
+        }
+        None => insert(doc, content.start, format!("\n{}\n", statement)),
+    }
+}
+
+fn insert(doc: &Document, offset: usize, new_text: String) -> TextEdit {
+    let position = doc.offset_to_position(offset);
+    TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{test_document, PositionEncoding};
+    use tower_lsp::lsp_types::{Position, Url};
+
+    fn apply(text: &str, edit: &TextEdit) -> String {
+        let doc = test_document(text);
+        let offset = doc.position_to_offset(edit.range.start).unwrap();
+        format!("{}{}{}", &text[..offset], edit.new_text, &text[offset..])
+    }
+
+    fn fix(text: &str) -> String {
+        let doc = test_document(text);
+        apply(text, &import_edit(&doc, "Card", "components/Card"))
+    }
+
+    #[test]
+    fn test_import_edit() {
+        assert_eq!(
+            fix("<script>\n  local Badge = require(\"components/Badge\")\n  local x = 1\n</script>\n<Card />"),
+            "<script>\n  local Badge = require(\"components/Badge\")\n  local Card = require(\"components/Card\")\n  local x = 1\n</script>\n<Card />"
+        );
+        assert_eq!(
+            fix("<script>\n\tlocal x = 1\n</script>"),
+            "<script>\n\tlocal Card = require(\"components/Card\")\n\tlocal x = 1\n</script>"
+        );
+        assert_eq!(
+            fix("<script module>\nlocal M = {}\n</script>\n<Card />"),
+            "<script module>\nlocal M = {}\n</script>\n\n<script>\n  local Card = require(\"components/Card\")\n</script>\n<Card />"
+        );
+        assert_eq!(
+            fix("<Card />"),
+            "<script>\n  local Card = require(\"components/Card\")\n</script>\n\n<Card />"
+        );
+    }
+
+    #[test]
+    fn test_finds_component_files() {
+        let root = std::env::temp_dir().join(format!("luat-code-action-{}", std::process::id()));
+        let pages = root.join("src/pages");
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/lib")).unwrap();
+        std::fs::create_dir_all(&pages).unwrap();
+        std::fs::write(root.join("src/components/Card.luat"), "<div />").unwrap();
+        std::fs::write(root.join("node_modules/lib/Card.luat"), "<div />").unwrap();
+
+        let text = "<Card />";
+        let uri = Url::from_file_path(pages.join("index.luat")).unwrap();
+        let doc = Document::new(uri, text.to_string(), PositionEncoding::Utf8);
+        let range = Range {
+            start: Position {
+                line: 0,
+                character: 2,
+            },
+            end: Position {
+                line: 0,
+                character: 2,
+            },
+        };
+        let actions = get_code_actions(&doc, range, &[], std::slice::from_ref(&root));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let titles: Vec<_> = actions
+            .iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.title.as_str(),
+                CodeActionOrCommand::Command(command) => command.title.as_str(),
+            })
+            .collect();
+        assert_eq!(titles, vec!["Import 'Card' from \"components/Card\""]);
+    }
+}
@@ -6,27 +6,85 @@ use tower_lsp::lsp_types::{
     Position,
 };
 
+use crate::css::{self, CssContext};
 use crate::document::Document;
+use crate::handlers::definition;
+use crate::lua::scope::Symbol;
+use crate::lua::virtual_document::split_each;
+use crate::lua::SymbolKind;
 use crate::regions::RegionType;
+use crate::scheduler::Cancellation;
+use crate::syntax::{Element, Node, Tag};
 
 /// Get completions at a position
-pub fn get_completions(doc: &Document, position: Position) -> Vec<CompletionItem> {
+pub fn get_completions(
+    doc: &Document,
+    position: Position,
+    cancellation: &Cancellation,
+) -> Vec<CompletionItem> {
     let mut completions = Vec::new();
 
     // Determine context from region
-    if let Some(region) = doc.region_at_position(position) {
+    let region = doc.region_at_position(position);
+    // Building the items may walk the scopes or read component files
+    if cancellation.is_cancelled() {
+        return completions;
+    }
+    if let Some(region) = region {
         match region.region_type {
             RegionType::LuaScript | RegionType::LuaScriptModule => {
-                // Inside script - would delegate to lua-language-server
-                // For now, provide basic Lua completions
+                // Inside script - locals in scope, then basic Lua completions.
+                // lua-language-server results are merged in by the server.
+                if let Some(offset) = doc.position_to_offset(position) {
+                    completions.extend(local_completions(doc, offset));
+                }
                 completions.extend(lua_basic_completions());
             }
-            RegionType::LuaExpression => {
-                // Inside expression - Lua completions + props
+            RegionType::LuaExpression | RegionType::AttributeExpression => {
+                // Inside expression - bindings in scope + props
+                if let Some(offset) = doc.position_to_offset(position) {
+                    completions.extend(local_completions(doc, offset));
+                }
                 completions.extend(expression_completions());
             }
+            RegionType::StartTag => {
+                // Between attributes - attribute names, or props for components
+                let element = doc
+                    .position_to_offset(position)
+                    .and_then(|offset| doc.tree().element_at_start_tag(offset));
+                if let Some(element) = element {
+                    if element.is_component() {
+                        completions.extend(prop_completions(doc, element));
+                    } else {
+                        completions.extend(attribute_completions(element));
+                    }
+                }
+            }
+            RegionType::AttributeValue => {
+                // Static attribute text - nothing to complete
+            }
+            RegionType::Style => {
+                // Inside a style sheet - CSS properties and values
+                if let (Some(offset), Some(sheet)) =
+                    (doc.position_to_offset(position), region.content.as_deref())
+                {
+                    let offset = offset.saturating_sub(region.start).min(sheet.len());
+                    completions.extend(css_completions(sheet, offset));
+                }
+            }
+            RegionType::ClientScript | RegionType::ScriptData => {
+                // JavaScript or data - not ours to complete
+            }
             RegionType::ControlFlow | RegionType::Directive => {
-                // Inside control flow or directive - limited completions
+                // Inside the Lua part of a block or directive tag - bindings
+                // in scope + props
+                let offset = doc
+                    .position_to_offset(position)
+                    .filter(|&offset| in_tag_expression(doc, offset));
+                if let Some(offset) = offset {
+                    completions.extend(local_completions(doc, offset));
+                    completions.extend(expression_completions());
+                }
             }
             _ => {
                 // Template context
@@ -39,7 +97,7 @@ pub fn get_completions(doc: &Document, position: Position) -> Vec<CompletionItem
         if let Some(offset) = doc.position_to_offset(position) {
             let text = doc.text();
             if offset > 0 {
-                let prev_char = text.chars().nth(offset - 1);
+                let prev_char = text[..offset].chars().next_back();
                 match prev_char {
                     Some('{') => {
                         // After { - provide control flow and expression completions
@@ -205,6 +263,105 @@ fn tag_completions() -> Vec<CompletionItem> {
         .collect()
 }
 
+/// Global HTML attributes, plus common ones for specific elements
+const HTML_ATTRIBUTES: &[(&str, &str)] = &[
+    ("*", "class"),
+    ("*", "id"),
+    ("*", "style"),
+    ("*", "title"),
+    ("*", "hidden"),
+    ("*", "tabindex"),
+    ("*", "role"),
+    ("*", "lang"),
+    ("a", "href"),
+    ("a", "target"),
+    ("a", "rel"),
+    ("img", "src"),
+    ("img", "alt"),
+    ("img", "width"),
+    ("img", "height"),
+    ("input", "type"),
+    ("input", "name"),
+    ("input", "value"),
+    ("input", "placeholder"),
+    ("input", "disabled"),
+    ("input", "required"),
+    ("button", "type"),
+    ("button", "disabled"),
+    ("form", "action"),
+    ("form", "method"),
+    ("label", "for"),
+    ("td", "colspan"),
+    ("td", "rowspan"),
+    ("th", "scope"),
+];
+
+/// Attribute name completions for an HTML element
+fn attribute_completions(element: &Element) -> Vec<CompletionItem> {
+    HTML_ATTRIBUTES
+        .iter()
+        .filter(|(on, _)| *on == "*" || *on == element.name)
+        .filter(|(_, name)| !has_attribute(element, name))
+        .map(|(_, name)| CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::PROPERTY),
+            insert_text: Some(format!("{}=\"$1\"", name)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Prop completions for a component, from the props its source reads
+fn prop_completions(doc: &Document, element: &Element) -> Vec<CompletionItem> {
+    definition::component_props(&element.name, doc)
+        .into_iter()
+        .filter(|prop| !has_attribute(element, prop))
+        .map(|prop| CompletionItem {
+            detail: Some(format!("Prop of <{}>", element.name)),
+            kind: Some(CompletionItemKind::FIELD),
+            insert_text: Some(format!("{}={{$1}}", prop)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            label: prop,
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn has_attribute(element: &Element, name: &str) -> bool {
+    element.attributes.iter().any(|a| a.name == name)
+}
+
+/// CSS property or value completions inside a style sheet
+fn css_completions(sheet: &str, offset: usize) -> Vec<CompletionItem> {
+    match css::context_at(sheet, offset) {
+        CssContext::Property => css::PROPERTIES
+            .iter()
+            .map(|property| CompletionItem {
+                label: property.name.to_string(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: Some(property.description.to_string()),
+                insert_text: Some(format!("{}: $0;", property.name)),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                ..Default::default()
+            })
+            .collect(),
+        CssContext::Value { property } => {
+            let values = css::property(&property).map_or(&[][..], |p| p.values);
+            values
+                .iter()
+                .chain(css::GLOBAL_VALUES)
+                .map(|value| CompletionItem {
+                    label: value.to_string(),
+                    kind: Some(CompletionItemKind::VALUE),
+                    ..Default::default()
+                })
+                .collect()
+        }
+        CssContext::Selector | CssContext::Other => Vec::new(),
+    }
+}
+
 /// General template context completions
 fn template_completions() -> Vec<CompletionItem> {
     vec![
@@ -230,7 +387,101 @@ fn template_completions() -> Vec<CompletionItem> {
     ]
 }
 
-/// Basic Lua completions (placeholder until lua-ls integration)
+/// Locals visible at the offset: script locals and functions, `{@local}`
+/// names and `{#each}` bindings of enclosing blocks
+fn local_completions(doc: &Document, offset: usize) -> Vec<CompletionItem> {
+    doc.lua()
+        .visible_at(offset)
+        .into_iter()
+        .map(|symbol| {
+            let kind = match symbol.kind {
+                SymbolKind::Function => CompletionItemKind::FUNCTION,
+                SymbolKind::Import => CompletionItemKind::MODULE,
+                SymbolKind::Local
+                | SymbolKind::Parameter
+                | SymbolKind::EachItem
+                | SymbolKind::EachIndex => CompletionItemKind::VARIABLE,
+            };
+            CompletionItem {
+                label: symbol.name.clone(),
+                kind: Some(kind),
+                detail: Some(declared_at(doc, symbol)),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// What a symbol is and where it's declared, e.g. `local function in
+/// <script module>` or `{@local} on line 4`
+fn declared_at(doc: &Document, symbol: &Symbol) -> String {
+    let script = doc
+        .tree()
+        .lua_scripts()
+        .into_iter()
+        .find(|script| script.content.contains(symbol.span.start));
+    if let Some(script) = script {
+        let what = match symbol.kind {
+            SymbolKind::Function => "local function",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Import => "require",
+            // `{#each}` bindings are only declared in markup
+            SymbolKind::Local | SymbolKind::EachItem | SymbolKind::EachIndex => "local",
+        };
+        let tag = if script.is_module() {
+            "<script module>"
+        } else {
+            "<script>"
+        };
+        return format!("{} in {}", what, tag);
+    }
+
+    let what = match symbol.kind {
+        SymbolKind::EachItem => "each item of {#each}",
+        SymbolKind::EachIndex => "each index of {#each}",
+        _ => "{@local}",
+    };
+    let line = doc.offset_to_position(symbol.span.start).line + 1;
+    format!("{} on line {}", what, line)
+}
+
+/// Whether the offset is in the Lua part of a block or directive tag: the
+/// condition of `{#if}`/`{:else if}`, the iterable of `{#each}`, the values
+/// of `{@local}` or the argument of `{@html}`/`{@render}`
+fn in_tag_expression(doc: &Document, offset: usize) -> bool {
+    let mut found: Option<&Tag> = None;
+    doc.tree().walk(&mut |node| {
+        let tags: Vec<&Tag> = match node {
+            Node::Block(block) => block.branches.iter().map(|branch| &branch.tag).collect(),
+            Node::Directive(tag) => vec![tag],
+            _ => return,
+        };
+        if let Some(tag) = tags.into_iter().find(|tag| tag.span.contains(offset)) {
+            found = Some(tag);
+        }
+    });
+    let Some(tag) = found else {
+        return false;
+    };
+    // Past the keyword and the space after it
+    if offset <= tag.keyword_span.end {
+        return false;
+    }
+
+    let text = doc.text();
+    match tag.keyword.as_str() {
+        "if" | "else if" | "html" | "render" => true,
+        "each" => match tag.expression.and_then(|each| split_each(text, each)) {
+            Some((iterable, _)) => offset <= iterable.end,
+            // No `as` yet
+            None => true,
+        },
+        "local" => text[tag.keyword_span.end..offset].contains('='),
+        _ => false,
+    }
+}
+
+/// Basic Lua keyword completions
 fn lua_basic_completions() -> Vec<CompletionItem> {
     vec![
         CompletionItem {
@@ -293,3 +544,102 @@ fn expression_completions() -> Vec<CompletionItem> {
         },
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+
+    /// Completions at `|`, as (label, kind, detail)
+    fn complete(text: &str) -> Vec<(String, CompletionItemKind, String)> {
+        let offset = text.find('|').unwrap();
+        let text = text.replacen('|', "", 1);
+        let doc = test_document(&text);
+        let position = doc.offset_to_position(offset);
+        get_completions(&doc, position, &Cancellation::default())
+            .into_iter()
+            .map(|item| {
+                (
+                    item.label,
+                    item.kind.unwrap(),
+                    item.detail.unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn labels(text: &str) -> Vec<String> {
+        complete(text)
+            .into_iter()
+            .map(|(label, ..)| label)
+            .collect()
+    }
+
+    #[test]
+    fn test_bindings_in_expressions() {
+        let text = "<script module>\nlocal function format(x) return x end\n</script>\n\
+            <script>\nlocal Card = require(\"components/Card\")\nlocal title = \"Hi\"\n</script>\n\
+            {@local count = 1}\n\
+            {#each items as item, i}\n<p>{|}</p>\n{/each}";
+        let items = complete(text);
+        let find = |label: &str| items.iter().find(|(l, ..)| l == label).cloned();
+        assert_eq!(
+            find("format"),
+            Some((
+                "format".to_string(),
+                CompletionItemKind::FUNCTION,
+                "local function in <script module>".to_string()
+            ))
+        );
+        assert_eq!(
+            find("Card").map(|(_, kind, detail)| (kind, detail)),
+            Some((
+                CompletionItemKind::MODULE,
+                "require in <script>".to_string()
+            ))
+        );
+        assert_eq!(
+            find("title").map(|(_, kind, detail)| (kind, detail)),
+            Some((
+                CompletionItemKind::VARIABLE,
+                "local in <script>".to_string()
+            ))
+        );
+        assert_eq!(
+            find("count").map(|(.., detail)| detail),
+            Some("{@local} on line 8".to_string())
+        );
+        assert_eq!(
+            find("item").map(|(.., detail)| detail),
+            Some("each item of {#each} on line 9".to_string())
+        );
+        assert_eq!(
+            find("i").map(|(.., detail)| detail),
+            Some("each index of {#each} on line 9".to_string())
+        );
+        assert!(find("props").is_some());
+        assert!(find("x").is_none());
+    }
+
+    #[test]
+    fn test_bindings_in_tags() {
+        let script = "<script>\nlocal user = {}\n</script>\n";
+        let scoped = |text: &str| labels(&format!("{}{}", script, text));
+        assert!(scoped("{#if |}{/if}").contains(&"user".to_string()));
+        assert!(scoped("{@html |}").contains(&"user".to_string()));
+        assert!(scoped("{#each |}{/each}").contains(&"user".to_string()));
+        assert!(scoped("{@local name = |}").contains(&"user".to_string()));
+        // Not the keyword, the names being bound or closing tags
+        assert!(scoped("{#i|f user}{/if}").is_empty());
+        assert!(scoped("{#each xs as |}{/each}").is_empty());
+        assert!(scoped("{#if user}{/i|f}").is_empty());
+    }
+
+    #[test]
+    fn test_later_bindings_are_not_offered() {
+        let text = "<p>{|}</p>\n{@local later = 1}\n{#each xs as x}{/each}";
+        let labels = labels(text);
+        assert!(!labels.contains(&"later".to_string()));
+        assert!(!labels.contains(&"x".to_string()));
+    }
+}
@@ -1,38 +1,38 @@
 // Copyright 2026 Maravilla Labs
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use regex::Regex;
-use std::sync::LazyLock;
+use std::path::{Path, PathBuf};
 use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Position, Range, Url};
 
 use crate::document::Document;
-
-static REQUIRE_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r#"local\s+(\w+)\s*=\s*require\s*\(\s*["']([^"']+)["']\s*\)"#).unwrap()
-});
-
-// Useful for finding component usages in template
-#[allow(dead_code)]
-static COMPONENT_TAG_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"<([A-Z][A-Za-z0-9_]*)"#).unwrap());
+use crate::lua::ast::{Block, Expr, ExprKind, Field, Name, StatKind};
+use crate::lua::ScopeModel;
+use crate::syntax;
 
 /// Get definition for symbol at position
 pub fn get_definition(doc: &Document, position: Position) -> Option<GotoDefinitionResponse> {
-    let text = doc.text();
+    let offset = doc.position_to_offset(position)?;
+    let model = doc.lua();
+
+    // Check if cursor is on a require path
+    if let Some(require) = model.require_at(offset) {
+        return resolve_require_path(&require.path, doc);
+    }
 
     // Get the word at position
     let (word, _range) = doc.word_at_position(position)?;
 
     // Check if it's a component tag
-    if word.chars().next()?.is_uppercase() {
-        return find_component_definition(&word, &text, doc);
+    if word.chars().next()?.is_uppercase() && model.import(&word).is_some() {
+        return find_component_definition(&word, doc);
     }
 
-    // Check if cursor is on a require path
-    if let Some(offset) = doc.position_to_offset(position) {
-        if let Some(path) = find_require_path_at_offset(&text, offset) {
-            return resolve_require_path(&path, doc);
-        }
+    // A local in a script: jump to its declaration
+    if let Some(symbol) = model.symbol_at(offset) {
+        return Some(GotoDefinitionResponse::Scalar(Location {
+            uri: doc.uri().clone(),
+            range: doc.span_to_range(symbol.span),
+        }));
     }
 
     // For other symbols, we would delegate to lua-language-server
@@ -42,70 +42,277 @@ pub fn get_definition(doc: &Document, position: Position) -> Option<GotoDefiniti
 /// Find component definition from imports
 fn find_component_definition(
     component_name: &str,
-    text: &str,
     doc: &Document,
 ) -> Option<GotoDefinitionResponse> {
-    // Look for: local ComponentName = require("path")
-    for cap in REQUIRE_RE.captures_iter(text) {
-        if let (Some(name), Some(path)) = (cap.get(1), cap.get(2)) {
-            if name.as_str() == component_name {
-                return resolve_require_path(path.as_str(), doc);
-            }
+    let path = component_file(component_name, doc)?;
+    location_response(&path)
+}
+
+/// Find the file a component is imported from:
+/// `local ComponentName = require("path")`
+pub fn component_file(component_name: &str, doc: &Document) -> Option<PathBuf> {
+    let model = doc.lua();
+    let import = model.import(component_name)?;
+    resolve_require_file(model.require_path(import)?, doc)
+}
+
+/// Props a component reads, in order of first use.
+///
+/// Empty when the component can't be resolved or read.
+pub fn component_props(component_name: &str, doc: &Document) -> Vec<String> {
+    let Some(path) = component_file(component_name, doc) else {
+        return Vec::new();
+    };
+    let Ok(source) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let tree = syntax::parse(&source);
+    let model = ScopeModel::for_template(&tree, &source, doc.lua_version());
+
+    let mut reads = PropReads::default();
+    for block in &model.scripts {
+        reads.block(block);
+    }
+    for expr in model.expressions.values() {
+        reads.expr(expr);
+    }
+    for local in model.template_locals.values() {
+        reads.exprs(&local.values);
+    }
+    reads.names.sort_by_key(|name| name.span.start);
+
+    let mut props: Vec<String> = Vec::new();
+    for name in reads.names {
+        // Slot content is passed as the component's children, not as an attribute
+        if name.name != "children" && !props.contains(&name.name) {
+            props.push(name.name.clone());
         }
     }
+    props
+}
 
-    None
+/// The fields read from `props`, e.g. `title` in `props.title`
+#[derive(Default)]
+struct PropReads<'a> {
+    names: Vec<&'a Name>,
 }
 
-/// Find if cursor is on a require path string
-fn find_require_path_at_offset(text: &str, offset: usize) -> Option<String> {
-    // Find require calls and check if offset is within the path string
-    for cap in REQUIRE_RE.captures_iter(text) {
-        if let Some(path_match) = cap.get(2) {
-            if offset >= path_match.start() && offset <= path_match.end() {
-                return Some(path_match.as_str().to_string());
+impl<'a> PropReads<'a> {
+    fn block(&mut self, block: &'a Block) {
+        for stat in &block.stats {
+            match &stat.kind {
+                StatKind::Local { exprs, .. } | StatKind::Return(exprs) => self.exprs(exprs),
+                StatKind::Assign { targets, exprs } => {
+                    self.exprs(targets);
+                    self.exprs(exprs);
+                }
+                StatKind::GenericFor { exprs, block, .. } => {
+                    self.exprs(exprs);
+                    self.block(block);
+                }
+                StatKind::NumericFor {
+                    start,
+                    end,
+                    step,
+                    block,
+                    ..
+                } => {
+                    self.expr(start);
+                    self.expr(end);
+                    if let Some(step) = step {
+                        self.expr(step);
+                    }
+                    self.block(block);
+                }
+                StatKind::LocalFunction { body, .. } | StatKind::Function { body, .. } => {
+                    self.block(&body.block)
+                }
+                StatKind::Call(expr) => self.expr(expr),
+                StatKind::Do(block) => self.block(block),
+                StatKind::While { cond, block } | StatKind::Repeat { block, cond } => {
+                    self.expr(cond);
+                    self.block(block);
+                }
+                StatKind::If {
+                    clauses,
+                    else_block,
+                } => {
+                    for (cond, block) in clauses {
+                        self.expr(cond);
+                        self.block(block);
+                    }
+                    if let Some(block) = else_block {
+                        self.block(block);
+                    }
+                }
+                StatKind::Break | StatKind::Goto(_) | StatKind::Label(_) | StatKind::Empty => {}
             }
         }
     }
-    None
+
+    fn exprs(&mut self, exprs: &'a [Expr]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Field { obj, name } => {
+                if matches!(&obj.kind, ExprKind::Name(obj) if obj == "props") {
+                    self.names.push(name);
+                }
+                self.expr(obj);
+            }
+            ExprKind::Function(body) => self.block(&body.block),
+            ExprKind::Table(fields) => {
+                for field in fields {
+                    match field {
+                        Field::Named(_, value) | Field::Positional(value) => self.expr(value),
+                        Field::Indexed(key, value) => {
+                            self.expr(key);
+                            self.expr(value);
+                        }
+                    }
+                }
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Paren(expr) => self.expr(expr),
+            ExprKind::Index { obj, key } => {
+                self.expr(obj);
+                self.expr(key);
+            }
+            ExprKind::Call { callee: obj, args } | ExprKind::MethodCall { obj, args, .. } => {
+                self.expr(obj);
+                self.exprs(args);
+            }
+            _ => {}
+        }
+    }
 }
 
 /// Resolve a require path to a file location
 fn resolve_require_path(path: &str, doc: &Document) -> Option<GotoDefinitionResponse> {
-    // Get the document's directory
-    let doc_path = doc.uri().to_file_path().ok()?;
-    let doc_dir = doc_path.parent()?;
+    location_response(&resolve_require_file(path, doc)?)
+}
+
+fn location_response(path: &std::path::Path) -> Option<GotoDefinitionResponse> {
+    let uri = Url::from_file_path(path).ok()?;
+    Some(GotoDefinitionResponse::Scalar(Location {
+        uri,
+        range: Range::default(),
+    }))
+}
 
+/// Resolve a require path to an existing file
+fn resolve_require_file(path: &str, doc: &Document) -> Option<PathBuf> {
+    require_candidates(path, doc)
+        .into_iter()
+        .find(|candidate| candidate.exists())
+}
+
+/// Every file a require path may resolve to, in the order they're tried.
+///
+/// Empty when the document isn't a file on disk.
+pub fn require_candidates(path: &str, doc: &Document) -> Vec<PathBuf> {
     // Try common extensions
     let extensions = ["luat", "lua"];
-    let search_dirs = [
-        doc_dir.to_path_buf(),
-        doc_dir.join(".."),
-        doc_dir.join("../.."),
-    ];
 
-    for dir in &search_dirs {
+    let mut candidates = Vec::new();
+    for dirs in search_dirs(doc) {
         for ext in &extensions {
-            let candidate = dir.join(format!("{}.{}", path, ext));
-            if candidate.exists() {
-                let uri = Url::from_file_path(&candidate).ok()?;
-                return Some(GotoDefinitionResponse::Scalar(Location {
-                    uri,
-                    range: Range::default(),
-                }));
-            }
-
-            // Also try with src/ prefix
-            let candidate = dir.join("src").join(format!("{}.{}", path, ext));
-            if candidate.exists() {
-                let uri = Url::from_file_path(&candidate).ok()?;
-                return Some(GotoDefinitionResponse::Scalar(Location {
-                    uri,
-                    range: Range::default(),
-                }));
+            for dir in &dirs {
+                candidates.push(dir.join(format!("{}.{}", path, ext)));
             }
         }
     }
+    candidates
+}
 
-    None
+/// Directories require paths are resolved against, in groups tried one
+/// extension at a time: the configured module roots, or else the
+/// document's directory and its two parents, each with its `src` directory
+fn search_dirs(doc: &Document) -> Vec<Vec<PathBuf>> {
+    let roots = &doc.config().modules.roots;
+    if !roots.is_empty() {
+        return roots.iter().map(|root| vec![root.clone()]).collect();
+    }
+    let Some(doc_dir) = document_dir(doc) else {
+        return Vec::new();
+    };
+    [doc_dir.clone(), doc_dir.join(".."), doc_dir.join("../..")]
+        .into_iter()
+        .map(|dir| {
+            let src = dir.join("src");
+            vec![dir, src]
+        })
+        .collect()
+}
+
+/// The path to `require` a module file by from the document: the shortest
+/// one whose resolution lands on that file
+pub fn require_path_for(file: &Path, doc: &Document) -> Option<String> {
+    let file = file.canonicalize().ok()?;
+    let extension = file.extension()?.to_str()?;
+    if extension != "luat" && extension != "lua" {
+        return None;
+    }
+
+    let mut paths: Vec<String> = search_dirs(doc)
+        .into_iter()
+        .flatten()
+        .filter_map(|base| {
+            let relative = file
+                .strip_prefix(base.canonicalize().ok()?)
+                .ok()?
+                .with_extension("");
+            let parts: Vec<&str> = relative
+                .iter()
+                .map(|part| part.to_str())
+                .collect::<Option<_>>()?;
+            Some(parts.join("/"))
+        })
+        .collect();
+    paths.sort_by_key(|path| path.len());
+    paths.into_iter().find(|path| {
+        resolve_require_file(path, doc).and_then(|resolved| resolved.canonicalize().ok())
+            == Some(file.clone())
+    })
+}
+
+/// The directory containing the document, if it's a file on disk
+pub fn document_dir(doc: &Document) -> Option<PathBuf> {
+    let doc_path = doc.uri().to_file_path().ok()?;
+    Some(doc_path.parent()?.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::PositionEncoding;
+
+    #[test]
+    fn test_component_props() {
+        let root = std::env::temp_dir().join(format!("luat-props-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::write(
+            root.join("src/components/Card.luat"),
+            "<script>\n-- props.commented\nlocal title = props.title or \"props.quoted\"\n\
+             </script>\n<h1 class={props.class}>{title}</h1>\n\
+             {#each props.items as item}{props.title}{item}{/each}\n{@render props.children}",
+        )
+        .unwrap();
+
+        let text = "<script>\nlocal Card = require(\"components/Card\")\n</script>\n<Card />";
+        let uri = Url::from_file_path(root.join("src/index.luat")).unwrap();
+        let doc = Document::new(uri, text.to_string(), PositionEncoding::Utf8);
+        let props = component_props("Card", &doc);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // Only field reads count, in order of first use, without `children`
+        assert_eq!(props, ["title", "class", "items"]);
+    }
 }
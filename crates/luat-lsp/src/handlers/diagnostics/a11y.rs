@@ -0,0 +1,500 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Accessibility checks on the template tree. Every rule reports with its
+//! own diagnostic code so it can be configured on its own.
+//!
+//! Values computed by expressions or set through `{...spread}` attributes
+//! are assumed to be fine, and components are opaque.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use super::rules::{
+    self, A11Y_ACCESSIBLE_NAME, A11Y_ARIA_ATTRIBUTE, A11Y_ARIA_ROLE, A11Y_CLICK_ON_STATIC,
+    A11Y_FORM_LABEL, A11Y_HEADING_ORDER, A11Y_HTML_LANG, A11Y_IMG_ALT,
+};
+use super::variables::closest;
+use crate::document::Document;
+use crate::syntax::{AttributeKind, AttributeValue, Element, Node, Span};
+
+/// WAI-ARIA 1.2 roles that may be set with `role`
+const ARIA_ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "application",
+    "article",
+    "banner",
+    "blockquote",
+    "button",
+    "caption",
+    "cell",
+    "checkbox",
+    "code",
+    "columnheader",
+    "combobox",
+    "complementary",
+    "contentinfo",
+    "definition",
+    "deletion",
+    "dialog",
+    "document",
+    "emphasis",
+    "feed",
+    "figure",
+    "form",
+    "generic",
+    "grid",
+    "gridcell",
+    "group",
+    "heading",
+    "img",
+    "insertion",
+    "link",
+    "list",
+    "listbox",
+    "listitem",
+    "log",
+    "main",
+    "marquee",
+    "math",
+    "menu",
+    "menubar",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "meter",
+    "navigation",
+    "none",
+    "note",
+    "option",
+    "paragraph",
+    "presentation",
+    "progressbar",
+    "radio",
+    "radiogroup",
+    "region",
+    "row",
+    "rowgroup",
+    "rowheader",
+    "scrollbar",
+    "search",
+    "searchbox",
+    "separator",
+    "slider",
+    "spinbutton",
+    "status",
+    "strong",
+    "subscript",
+    "superscript",
+    "switch",
+    "tab",
+    "table",
+    "tablist",
+    "tabpanel",
+    "term",
+    "textbox",
+    "time",
+    "timer",
+    "toolbar",
+    "tooltip",
+    "tree",
+    "treegrid",
+    "treeitem",
+];
+
+/// WAI-ARIA 1.2 states and properties, without the `aria-` prefix
+const ARIA_ATTRIBUTES: &[&str] = &[
+    "activedescendant",
+    "atomic",
+    "autocomplete",
+    "braillelabel",
+    "brailleroledescription",
+    "busy",
+    "checked",
+    "colcount",
+    "colindex",
+    "colindextext",
+    "colspan",
+    "controls",
+    "current",
+    "describedby",
+    "description",
+    "details",
+    "disabled",
+    "errormessage",
+    "expanded",
+    "flowto",
+    "haspopup",
+    "hidden",
+    "invalid",
+    "keyshortcuts",
+    "label",
+    "labelledby",
+    "level",
+    "live",
+    "modal",
+    "multiline",
+    "multiselectable",
+    "orientation",
+    "owns",
+    "placeholder",
+    "posinset",
+    "pressed",
+    "readonly",
+    "relevant",
+    "required",
+    "roledescription",
+    "rowcount",
+    "rowindex",
+    "rowindextext",
+    "rowspan",
+    "selected",
+    "setsize",
+    "sort",
+    "valuemax",
+    "valuemin",
+    "valuenow",
+    "valuetext",
+];
+
+/// Elements that can be focused and activated without extra attributes
+const INTERACTIVE_ELEMENTS: &[&str] = &[
+    "a", "button", "input", "select", "textarea", "option", "summary", "details", "label",
+];
+
+/// `<input>` types that need no label
+const UNLABELED_INPUT_TYPES: &[&str] = &["hidden", "submit", "reset", "button", "image"];
+
+pub fn check_a11y(doc: &Document) -> Vec<Diagnostic> {
+    let text = doc.text();
+    let mut checker = Checker {
+        doc,
+        text,
+        labelled_ids: Vec::new(),
+        in_label: 0,
+        heading: None,
+        diagnostics: Vec::new(),
+    };
+
+    // `<label for="id">` anywhere labels the control with that id
+    doc.tree().walk(&mut |node| {
+        if let Node::Element(element) = node {
+            if element.name == "label" {
+                if let Some(id) = element.attribute("for").and_then(|a| a.literal()) {
+                    checker.labelled_ids.push(id.trim().to_string());
+                }
+            }
+        }
+    });
+
+    checker.nodes(&doc.tree().nodes);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    doc: &'a Document,
+    text: &'a str,
+    labelled_ids: Vec<String>,
+    /// Depth of enclosing `<label>` elements
+    in_label: usize,
+    /// Level of the previous heading, in document order
+    heading: Option<u8>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => self.element(element),
+                Node::Block(block) => {
+                    for branch in &block.branches {
+                        self.nodes(&branch.children);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: &Element) {
+        if !element.is_component() {
+            self.aria(element);
+            match element.name.as_str() {
+                "img" => self.img(element),
+                "button" => self.accessible_name(element, "Button"),
+                "a" if element.attribute("href").is_some() => self.accessible_name(element, "Link"),
+                "input" | "select" | "textarea" => self.form_label(element),
+                "html" if element.attribute("lang").is_none() && !element.has_spread() => {
+                    self.warning(
+                        element.name_span,
+                        A11Y_HTML_LANG,
+                        "<html> should have a lang attribute, e.g. lang=\"en\", so screen readers use the right pronunciation".to_string(),
+                    );
+                }
+                _ => {}
+            }
+            self.heading(element);
+            self.click(element);
+        }
+
+        let label = usize::from(element.name == "label");
+        self.in_label += label;
+        self.nodes(&element.children);
+        self.in_label -= label;
+    }
+
+    fn img(&mut self, element: &Element) {
+        if element.attribute("alt").is_none() && !element.has_spread() {
+            self.warning(
+                element.name_span,
+                A11Y_IMG_ALT,
+                "<img> should have an alt attribute - use alt=\"\" for decorative images"
+                    .to_string(),
+            );
+        }
+    }
+
+    /// Buttons and links need text a screen reader can announce
+    fn accessible_name(&mut self, element: &Element, what: &str) {
+        if has_label(element) || element.has_spread() || self.has_content(&element.children) {
+            return;
+        }
+        self.warning(
+            element.name_span,
+            A11Y_ACCESSIBLE_NAME,
+            format!(
+                "{} has no accessible name - add text content or an aria-label",
+                what
+            ),
+        );
+    }
+
+    /// Whether the nodes produce text, or might at runtime
+    fn has_content(&self, nodes: &[Node]) -> bool {
+        nodes.iter().any(|node| match node {
+            Node::Text(span) => !span.text(self.text).trim().is_empty(),
+            Node::Expression(_) | Node::Directive(_) => true,
+            Node::Block(block) => block
+                .branches
+                .iter()
+                .any(|branch| self.has_content(&branch.children)),
+            Node::Element(element) if element.is_component() => true,
+            Node::Element(element) => {
+                let hidden = element
+                    .attribute("aria-hidden")
+                    .and_then(|a| a.literal())
+                    .is_some_and(|value| value == "true");
+                if hidden {
+                    return false;
+                }
+                match element.name.as_str() {
+                    "img" => element.attribute("alt").is_some_and(|alt| {
+                        alt.literal().is_none_or(|text| !text.trim().is_empty())
+                    }),
+                    _ => has_label(element) || self.has_content(&element.children),
+                }
+            }
+            _ => false,
+        })
+    }
+
+    fn form_label(&mut self, element: &Element) {
+        if element.name == "input" {
+            let input_type = element.attribute("type").map(|a| a.literal());
+            match input_type {
+                // Computed type
+                Some(None) => return,
+                Some(Some(ty)) if UNLABELED_INPUT_TYPES.contains(&ty.trim()) => return,
+                _ => {}
+            }
+        }
+        if self.in_label > 0 || has_label(element) || element.has_spread() {
+            return;
+        }
+        match element.attribute("id").map(|a| a.literal()) {
+            // A computed id may be referenced by a computed `for`
+            Some(None) => return,
+            Some(Some(id)) if self.labelled_ids.iter().any(|l| l == id.trim()) => return,
+            _ => {}
+        }
+        self.warning(
+            element.name_span,
+            A11Y_FORM_LABEL,
+            format!(
+                "<{}> has no label - wrap it in a <label>, add <label for=\"...\"> or an aria-label",
+                element.name
+            ),
+        );
+    }
+
+    fn aria(&mut self, element: &Element) {
+        for attribute in &element.attributes {
+            if attribute.kind != AttributeKind::Normal {
+                continue;
+            }
+            let name = attribute.name.to_ascii_lowercase();
+
+            if name == "role" {
+                let Some(roles) = attribute.literal() else {
+                    continue;
+                };
+                // Later roles are fallbacks for the first
+                for role in roles.split_whitespace() {
+                    if ARIA_ROLES.contains(&role) {
+                        continue;
+                    }
+                    let message = match closest(role, ARIA_ROLES) {
+                        Some(suggestion) => format!(
+                            "'{}' is not an ARIA role - did you mean '{}'?",
+                            role, suggestion
+                        ),
+                        None => format!("'{}' is not an ARIA role", role),
+                    };
+                    let span = match &attribute.value {
+                        Some(AttributeValue::Text { span, .. }) => *span,
+                        _ => attribute.span,
+                    };
+                    self.warning(span, A11Y_ARIA_ROLE, message);
+                }
+            } else if let Some(property) = name.strip_prefix("aria-") {
+                if ARIA_ATTRIBUTES.contains(&property) {
+                    continue;
+                }
+                let message = match closest(property, ARIA_ATTRIBUTES) {
+                    Some(suggestion) => format!(
+                        "'{}' is not an ARIA attribute - did you mean 'aria-{}'?",
+                        attribute.name, suggestion
+                    ),
+                    None => format!("'{}' is not an ARIA attribute", attribute.name),
+                };
+                self.warning(attribute.name_span, A11Y_ARIA_ATTRIBUTE, message);
+            }
+        }
+    }
+
+    /// Click handlers on elements keyboard users can't reach
+    fn click(&mut self, element: &Element) {
+        let Some(onclick) = element.attribute("onclick") else {
+            return;
+        };
+        if INTERACTIVE_ELEMENTS.contains(&element.name.as_str())
+            || element.attribute("role").is_some()
+            || element.attribute("tabindex").is_some()
+        {
+            return;
+        }
+        self.warning(
+            onclick.name_span,
+            A11Y_CLICK_ON_STATIC,
+            format!(
+                "Click handler on non-interactive <{}> - use a <button>, or add a role, tabindex and keyboard handler",
+                element.name
+            ),
+        );
+    }
+
+    fn heading(&mut self, element: &Element) {
+        let level = match element.name.as_str() {
+            "h1" => 1,
+            "h2" => 2,
+            "h3" => 3,
+            "h4" => 4,
+            "h5" => 5,
+            "h6" => 6,
+            _ => return,
+        };
+        if let Some(previous) = self.heading {
+            if level > previous + 1 {
+                self.warning(
+                    element.name_span,
+                    A11Y_HEADING_ORDER,
+                    format!(
+                        "Heading level skips from <h{}> to <h{}> - use <h{}>",
+                        previous,
+                        level,
+                        previous + 1
+                    ),
+                );
+            }
+        }
+        self.heading = Some(level);
+    }
+
+    fn warning(&mut self, span: Span, code: &str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            range: self.doc.span_to_range(span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(code),
+            message,
+            source: Some("luat".to_string()),
+            ..Default::default()
+        });
+    }
+}
+
+/// Whether the element is labelled by an attribute
+fn has_label(element: &Element) -> bool {
+    ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|name| element.attribute(name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::diagnostics::test_support::{codes, messages};
+
+    #[test]
+    fn test_names_and_labels() {
+        assert_eq!(
+            codes(
+                check_a11y,
+                "<img src=\"a.png\"><img src=\"b.png\" alt=\"\"><img {...props}>\
+                 <button><svg aria-hidden=\"true\"></svg></button><button>{label}</button>\
+                 <a href=\"/\"><img src=\"logo.png\" alt=\"Home\"></a><a href=\"/x\"> </a>\
+                 <input name=\"q\"><label>Name <input name=\"n\"></label>\
+                 <label for=\"email\">Email</label><input id=\"email\"><input type=\"submit\">\
+                 <textarea aria-label=\"Notes\"></textarea><Field><input id={id}></Field>"
+            ),
+            vec![
+                A11Y_IMG_ALT,
+                A11Y_ACCESSIBLE_NAME,
+                A11Y_ACCESSIBLE_NAME,
+                A11Y_FORM_LABEL
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aria_and_structure() {
+        let text = "<html><div role=\"buton\" aria-labeledby=\"x\"></div>\
+            <nav role=\"navigation presentation\" aria-label=\"Main\"></nav>\
+            <div onclick=\"go()\">x</div><div role=\"button\" tabindex=\"0\" onclick=\"go()\">y</div>\
+            <h1>A</h1><h2>B</h2><h4>C</h4><h2>D</h2><h3>E</h3></html>";
+        assert_eq!(
+            codes(check_a11y, text),
+            vec![
+                A11Y_HTML_LANG,
+                A11Y_ARIA_ROLE,
+                A11Y_ARIA_ATTRIBUTE,
+                A11Y_CLICK_ON_STATIC,
+                A11Y_HEADING_ORDER,
+            ]
+        );
+
+        let messages = messages(check_a11y, text);
+        assert_eq!(
+            messages[1],
+            "'buton' is not an ARIA role - did you mean 'button'?"
+        );
+        assert_eq!(
+            messages[2],
+            "'aria-labeledby' is not an ARIA attribute - did you mean 'aria-labelledby'?"
+        );
+        assert_eq!(
+            messages[4],
+            "Heading level skips from <h2> to <h4> - use <h3>"
+        );
+    }
+}
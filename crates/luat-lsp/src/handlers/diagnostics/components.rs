@@ -0,0 +1,67 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Component tags with no matching import
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use super::rules::{self, UNKNOWN_COMPONENT};
+use crate::document::Document;
+use crate::lua::ast::Name;
+
+/// Report every `<Card>` whose name isn't bound in the template's scope.
+/// The code action handler offers to import them.
+pub fn check_components(doc: &Document) -> Vec<Diagnostic> {
+    unbound_components(doc)
+        .map(|component| Diagnostic {
+            range: doc.span_to_range(component.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(UNKNOWN_COMPONENT),
+            message: format!(
+                "Component '{0}' isn't imported - add local {0} = require(\"...\") to a script",
+                component.name
+            ),
+            source: Some("luat".to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Component tags named after neither a local nor a global the scripts assign
+pub fn unbound_components(doc: &Document) -> impl Iterator<Item = &Name> {
+    let model = doc.lua();
+    model
+        .unbound_components
+        .iter()
+        .filter(|component| !model.global_writes.iter().any(|w| w.name == component.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_unbound_components() {
+        let text = "<script>\nlocal Card = require(\"components/Card\")\nUI = {}\n</script>\n\
+            <Card /><Badge>x</Badge><UI.Button /><Nav.Link />";
+        let doc = test_document(text);
+        let diagnostics = check_components(&doc);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Component 'Badge' isn't imported - add local Badge = require(\"...\") to a script",
+                "Component 'Nav' isn't imported - add local Nav = require(\"...\") to a script",
+            ]
+        );
+        assert_eq!(
+            diagnostics[1].range.start,
+            Position {
+                line: 4,
+                character: 38
+            }
+        );
+    }
+}
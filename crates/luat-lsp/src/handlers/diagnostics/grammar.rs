@@ -0,0 +1,451 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Grammar of block tags, clauses and directives: which clauses a block
+//! accepts and in what order, and what each tag expects after its keyword.
+//! The Lua inside expressions is checked separately.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use super::rules::{self, CLAUSE_PLACEMENT, TAG_SYNTAX, UNKNOWN_TAG};
+use crate::document::Document;
+use crate::lua::ast::ExprKind;
+use crate::lua::lexer::{Lexer, TokenKind};
+use crate::lua::parser::parse_expression;
+use crate::syntax::{Block, Node, Span, Tag};
+
+const BLOCKS: &[&str] = &["if", "each"];
+const DIRECTIVES: &[&str] = &["html", "local", "render"];
+
+pub fn check_grammar(doc: &Document) -> Vec<Diagnostic> {
+    let text = doc.text();
+    let mut checker = Checker {
+        doc,
+        text,
+        diagnostics: Vec::new(),
+    };
+    doc.tree().walk(&mut |node| match node {
+        Node::Block(block) => checker.block(block),
+        Node::Directive(tag) => checker.directive(tag),
+        Node::StrayTag(tag) if tag.sigil == ':' => checker.stray_clause(tag),
+        _ => {}
+    });
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    doc: &'a Document,
+    text: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn error(&mut self, code: &str, span: Span, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            range: self.doc.span_to_range(span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(code),
+            message: message.into(),
+            source: Some("luat".to_string()),
+            ..Default::default()
+        });
+    }
+
+    /// The span to report a missing part of a tag on
+    fn tag_span(tag: &Tag) -> Span {
+        Span::new(tag.span.start, tag.keyword_span.end)
+    }
+
+    fn block(&mut self, block: &Block) {
+        let open = &block.branches[0].tag;
+        match block.keyword.as_str() {
+            "if" => {
+                if open.expression.is_none() && open.closed {
+                    self.error(
+                        TAG_SYNTAX,
+                        Self::tag_span(open),
+                        "Expected a condition after {#if}, e.g. {#if user}",
+                    );
+                }
+            }
+            "each" => self.each(open),
+            "" => self.error(
+                TAG_SYNTAX,
+                open.span,
+                "Expected a block name after {#, e.g. {#if} or {#each}",
+            ),
+            keyword => {
+                self.error(
+                    UNKNOWN_TAG,
+                    open.keyword_span,
+                    format!(
+                        "Unknown block {{#{}}} - expected {}",
+                        keyword,
+                        one_of(BLOCKS, "#")
+                    ),
+                );
+                // Its clauses can't be checked against anything
+                return;
+            }
+        }
+
+        let mut else_tag: Option<&Tag> = None;
+        let mut empty_tag: Option<&Tag> = None;
+        for branch in &block.branches[1..] {
+            let tag = &branch.tag;
+            match (block.keyword.as_str(), tag.keyword.as_str()) {
+                ("if", "else if") => {
+                    if else_tag.is_some() {
+                        self.error(
+                            CLAUSE_PLACEMENT,
+                            tag.keyword_span,
+                            "{:else if} cannot follow {:else} - {:else} must be the last clause",
+                        );
+                    }
+                    if tag.expression.is_none() && tag.closed {
+                        self.error(
+                            TAG_SYNTAX,
+                            Self::tag_span(tag),
+                            "Expected a condition after {:else if}",
+                        );
+                    }
+                }
+                ("if", "else") => {
+                    if else_tag.is_some() {
+                        self.error(CLAUSE_PLACEMENT, tag.keyword_span, "Duplicate {:else} in {#if} block");
+                    }
+                    self.no_expression(tag);
+                    else_tag = Some(tag);
+                }
+                ("each", "empty") => {
+                    if empty_tag.is_some() {
+                        self.error(CLAUSE_PLACEMENT, tag.keyword_span, "Duplicate {:empty} in {#each} block");
+                    }
+                    self.no_expression(tag);
+                    empty_tag = Some(tag);
+                }
+                ("if", "empty") => self.error(
+                    CLAUSE_PLACEMENT,
+                    tag.keyword_span,
+                    "{:empty} is only allowed in {#each} blocks - use {:else} in {#if}",
+                ),
+                ("each", "else" | "else if") => self.error(
+                    CLAUSE_PLACEMENT,
+                    tag.keyword_span,
+                    format!(
+                        "{{:{}}} is only allowed in {{#if}} blocks - use {{:empty}} for an empty list",
+                        tag.keyword
+                    ),
+                ),
+                (_, keyword) => self.error(
+                    UNKNOWN_TAG,
+                    tag.keyword_span,
+                    format!(
+                        "Unknown clause {{:{}}} - expected {}",
+                        keyword,
+                        if block.keyword == "each" {
+                            "{:empty}"
+                        } else {
+                            "{:else if} or {:else}"
+                        }
+                    ),
+                ),
+            }
+        }
+
+        if let Some(close) = &block.close {
+            if let Some(extra) = close.expression {
+                self.error(
+                    TAG_SYNTAX,
+                    extra,
+                    format!("Unexpected content in {{/{}}}", close.keyword),
+                );
+            }
+        }
+    }
+
+    /// `{#each iterable as item, index}`
+    fn each(&mut self, tag: &Tag) {
+        let Some(expression) = tag.expression else {
+            if tag.closed {
+                self.error(
+                    TAG_SYNTAX,
+                    Self::tag_span(tag),
+                    "Expected a list after {#each}, e.g. {#each items as item}",
+                );
+            }
+            return;
+        };
+
+        let tokens: Vec<_> = Lexer::at(&self.text[..expression.end], expression.start)
+            .filter(|t| t.kind != TokenKind::Comment)
+            .collect();
+        let mut depth = 0usize;
+        let as_index = tokens.iter().position(|token| {
+            match (token.kind, token.text(self.text)) {
+                (TokenKind::Symbol, "(" | "[" | "{") => depth += 1,
+                (TokenKind::Symbol, ")" | "]" | "}") => depth = depth.saturating_sub(1),
+                (TokenKind::Name, "as") => return depth == 0,
+                _ => {}
+            }
+            false
+        });
+
+        let Some(as_index) = as_index else {
+            self.error(
+                TAG_SYNTAX,
+                expression,
+                "Expected 'as' in {#each} - name the item, e.g. {#each items as item}",
+            );
+            return;
+        };
+        if as_index == 0 {
+            self.error(TAG_SYNTAX, tokens[0].span, "Expected a list before 'as'");
+        }
+
+        // `item` or `item, index`
+        let bindings = &tokens[as_index + 1..];
+        let valid = match bindings {
+            [item] => item.kind == TokenKind::Name,
+            [item, comma, index] => {
+                item.kind == TokenKind::Name
+                    && comma.text(self.text) == ","
+                    && index.kind == TokenKind::Name
+            }
+            _ => false,
+        };
+        if !valid {
+            let span = match (bindings.first(), bindings.last()) {
+                (Some(first), Some(last)) => Span::new(first.span.start, last.span.end),
+                _ => tokens[as_index].span,
+            };
+            self.error(
+                TAG_SYNTAX,
+                span,
+                "Expected an item name and optional index after 'as', e.g. as item, i",
+            );
+        }
+    }
+
+    /// Clauses like `{:else}` take nothing after the keyword
+    fn no_expression(&mut self, tag: &Tag) {
+        if let Some(extra) = tag.expression {
+            let message = if tag.keyword == "else" && self.text[extra.start..].starts_with("if") {
+                "Unexpected content in {:else} - did you mean {:else if ...}?".to_string()
+            } else {
+                format!("Unexpected content in {{:{}}}", tag.keyword)
+            };
+            self.error(TAG_SYNTAX, extra, message);
+        }
+    }
+
+    fn stray_clause(&mut self, tag: &Tag) {
+        let (code, message) = match tag.keyword.as_str() {
+            "else" | "else if" => (
+                CLAUSE_PLACEMENT,
+                format!("{{:{}}} must be inside an {{#if}} block", tag.keyword),
+            ),
+            "empty" => (
+                CLAUSE_PLACEMENT,
+                "{:empty} must be inside an {#each} block".to_string(),
+            ),
+            keyword => (
+                UNKNOWN_TAG,
+                format!("Unknown clause {{:{}}} outside of a block", keyword),
+            ),
+        };
+        self.error(code, tag.span, message);
+    }
+
+    fn directive(&mut self, tag: &Tag) {
+        let expression = tag.expression.filter(|_| tag.closed);
+        match tag.keyword.as_str() {
+            "html" => {
+                if expression.is_none() && tag.closed {
+                    self.error(
+                        TAG_SYNTAX,
+                        Self::tag_span(tag),
+                        "Expected an expression after {@html}, e.g. {@html props.content}",
+                    );
+                }
+            }
+            "local" => self.local(tag, expression),
+            "render" => {
+                let Some(expression) = expression else {
+                    if tag.closed {
+                        self.error(
+                            TAG_SYNTAX,
+                            Self::tag_span(tag),
+                            "Expected a call after {@render}, e.g. {@render props.children()}",
+                        );
+                    }
+                    return;
+                };
+                let (expr, errors) =
+                    parse_expression(self.text, expression, self.doc.lua_version());
+                // Syntax errors are reported with the other Lua diagnostics
+                if errors.is_empty()
+                    && !matches!(
+                        expr.kind,
+                        ExprKind::Call { .. } | ExprKind::MethodCall { .. }
+                    )
+                {
+                    let source = expression.text(self.text);
+                    self.error(
+                        TAG_SYNTAX,
+                        expression,
+                        format!("{{@render}} expects a call, e.g. {{@render {}()}}", source),
+                    );
+                }
+            }
+            "" => self.error(
+                TAG_SYNTAX,
+                tag.span,
+                "Expected a directive name after {@, e.g. {@html}",
+            ),
+            keyword => self.error(
+                UNKNOWN_TAG,
+                tag.keyword_span,
+                format!(
+                    "Unknown directive {{@{}}} - expected {}",
+                    keyword,
+                    one_of(DIRECTIVES, "@")
+                ),
+            ),
+        }
+    }
+
+    /// `{@local name = value}` or `{@local a, b = x, y}`
+    fn local(&mut self, tag: &Tag, expression: Option<Span>) {
+        let Some(expression) = expression else {
+            if tag.closed {
+                self.error(
+                    TAG_SYNTAX,
+                    Self::tag_span(tag),
+                    "Expected an assignment after {@local}, e.g. {@local name = value}",
+                );
+            }
+            return;
+        };
+
+        let tokens: Vec<_> = Lexer::at(&self.text[..expression.end], expression.start)
+            .filter(|t| t.kind != TokenKind::Comment)
+            .collect();
+        let Some(assign) = tokens
+            .iter()
+            .position(|t| t.kind == TokenKind::Symbol && t.text(self.text) == "=")
+        else {
+            self.error(
+                TAG_SYNTAX,
+                expression,
+                "{@local} needs an assignment, e.g. {@local name = value}",
+            );
+            return;
+        };
+
+        // Names separated by commas
+        let names = &tokens[..assign];
+        let valid = !names.is_empty()
+            && names.iter().enumerate().all(|(i, token)| {
+                if i % 2 == 0 {
+                    token.kind == TokenKind::Name
+                } else {
+                    token.text(self.text) == ","
+                }
+            })
+            && names.len() % 2 == 1;
+        if !valid {
+            let span = match (names.first(), names.last()) {
+                (Some(first), Some(last)) => Span::new(first.span.start, last.span.end),
+                _ => tokens[assign].span,
+            };
+            self.error(
+                TAG_SYNTAX,
+                span,
+                "Expected variable names before '=' in {@local}",
+            );
+        }
+        if assign + 1 == tokens.len() {
+            self.error(
+                TAG_SYNTAX,
+                tokens[assign].span,
+                "Expected a value after '=' in {@local}",
+            );
+        }
+    }
+}
+
+/// `{#if} or {#each}`
+fn one_of(keywords: &[&str], sigil: &str) -> String {
+    let tags: Vec<String> = keywords
+        .iter()
+        .map(|k| format!("{{{}{}}}", sigil, k))
+        .collect();
+    match tags.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::diagnostics::test_support::messages;
+
+    #[test]
+    fn test_valid_templates() {
+        let text = "{#if a}x{:else if b}y{:else}z{/if}\
+            {#each items as item, i}{item}{:empty}none{/each}\
+            {#each pairs(t) as k}{k}{/each}\
+            {@local a, b = 1, 2}{@html a}{@render props.children()}";
+        assert_eq!(messages(check_grammar, text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_clause_placement() {
+        assert_eq!(
+            messages(check_grammar, "{:else}"),
+            vec!["{:else} must be inside an {#if} block"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{#if a}{:empty}{/if}"),
+            vec!["{:empty} is only allowed in {#each} blocks - use {:else} in {#if}"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{#if a}{:else}{:else if b}{/if}"),
+            vec!["{:else if} cannot follow {:else} - {:else} must be the last clause"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{#each xs as x}{:else}{/each}"),
+            vec!["{:else} is only allowed in {#if} blocks - use {:empty} for an empty list"]
+        );
+    }
+
+    #[test]
+    fn test_tag_contents() {
+        assert_eq!(
+            messages(check_grammar, "{#each items}{/each}"),
+            vec!["Expected 'as' in {#each} - name the item, e.g. {#each items as item}"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{#each items as}{/each}"),
+            vec!["Expected an item name and optional index after 'as', e.g. as item, i"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{@local x}"),
+            vec!["{@local} needs an assignment, e.g. {@local name = value}"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{@render props.children}"),
+            vec!["{@render} expects a call, e.g. {@render props.children()}"]
+        );
+        assert_eq!(
+            messages(check_grammar, "{#foo}{/foo}{@bar x}"),
+            vec![
+                "Unknown block {#foo} - expected {#if} or {#each}",
+                "Unknown directive {@bar} - expected {@html}, {@local} or {@render}",
+            ]
+        );
+    }
+}
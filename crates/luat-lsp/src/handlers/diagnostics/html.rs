@@ -0,0 +1,352 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! HTML content-model rules: elements the browser would move or close
+//! because they can't appear where they're written, and attribute values
+//! outside an attribute's allowed keywords.
+//!
+//! Components and `<svg>`/`<math>` content are opaque: what ends up around
+//! a component's children depends on the component.
+
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+};
+
+use super::rules::{self, INVALID_ATTRIBUTE_VALUE, INVALID_NESTING, INVALID_PARENT};
+use crate::document::Document;
+use crate::syntax::{closes_implicitly, AttributeValue, Element, Node, Span, P_CLOSERS};
+
+/// Elements that must be children of one of the listed parents
+const REQUIRED_PARENTS: &[(&str, &[&str])] = &[
+    ("li", &["ul", "ol", "menu"]),
+    ("dt", &["dl", "div"]),
+    ("dd", &["dl", "div"]),
+    ("tr", &["table", "thead", "tbody", "tfoot"]),
+    ("td", &["tr"]),
+    ("th", &["tr"]),
+    ("option", &["select", "datalist", "optgroup"]),
+];
+
+/// Elements that can't contain another element of the same name
+const NO_NESTING: &[&str] = &["a", "form", "button"];
+
+/// Attributes with a fixed set of keywords, by element (empty for global
+/// attributes)
+const ENUMERATED_ATTRIBUTES: &[(&[&str], &str, &[&str])] = &[
+    (&[], "dir", &["ltr", "rtl", "auto"]),
+    (&[], "draggable", &["true", "false"]),
+    (
+        &[],
+        "contenteditable",
+        &["", "true", "false", "plaintext-only"],
+    ),
+    (&[], "spellcheck", &["", "true", "false"]),
+    (&[], "translate", &["", "yes", "no"]),
+    (&[], "hidden", &["", "hidden", "until-found"]),
+    (
+        &[],
+        "inputmode",
+        &[
+            "none", "text", "decimal", "numeric", "tel", "search", "email", "url",
+        ],
+    ),
+    (
+        &[],
+        "enterkeyhint",
+        &["enter", "done", "go", "next", "previous", "search", "send"],
+    ),
+    (
+        &["input"],
+        "type",
+        &[
+            "button",
+            "checkbox",
+            "color",
+            "date",
+            "datetime-local",
+            "email",
+            "file",
+            "hidden",
+            "image",
+            "month",
+            "number",
+            "password",
+            "radio",
+            "range",
+            "reset",
+            "search",
+            "submit",
+            "tel",
+            "text",
+            "time",
+            "url",
+            "week",
+        ],
+    ),
+    (&["button"], "type", &["submit", "reset", "button"]),
+    (&["form"], "method", &["get", "post", "dialog"]),
+    (
+        &["form"],
+        "enctype",
+        &[
+            "application/x-www-form-urlencoded",
+            "multipart/form-data",
+            "text/plain",
+        ],
+    ),
+    (&["form"], "autocomplete", &["on", "off"]),
+    (&["img", "iframe"], "loading", &["eager", "lazy"]),
+    (&["img"], "decoding", &["sync", "async", "auto"]),
+    (
+        &["img", "script", "link", "audio", "video"],
+        "crossorigin",
+        &["", "anonymous", "use-credentials"],
+    ),
+    (
+        &["audio", "video"],
+        "preload",
+        &["", "none", "metadata", "auto"],
+    ),
+    (&["textarea"], "wrap", &["soft", "hard"]),
+    (&["th"], "scope", &["row", "col", "rowgroup", "colgroup"]),
+    (
+        &["track"],
+        "kind",
+        &[
+            "subtitles",
+            "captions",
+            "descriptions",
+            "chapters",
+            "metadata",
+        ],
+    ),
+];
+
+pub fn check_html(doc: &Document) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        doc,
+        ancestors: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    checker.nodes(&doc.tree().nodes);
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    doc: &'a Document,
+    /// Enclosing elements, outermost first
+    ancestors: Vec<&'a Element>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn nodes(&mut self, nodes: &'a [Node]) {
+        for (i, node) in nodes.iter().enumerate() {
+            match node {
+                Node::Element(element) => {
+                    self.element(element);
+                    // The parser ended a `<p>` at the block element after it
+                    if element.name == "p" && element.end_tag.is_none() {
+                        if let Some(Node::Element(next)) = nodes.get(i + 1) {
+                            if next.name != "p" && closes_implicitly("p", &next.name) {
+                                self.not_in_paragraph(next, element);
+                            }
+                        }
+                    }
+                }
+                Node::Block(block) => {
+                    for branch in &block.branches {
+                        self.nodes(&branch.children);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: &'a Element) {
+        if element.is_component() || matches!(element.name.as_str(), "svg" | "math") {
+            // Children are checked against an unknown context
+            let saved = std::mem::take(&mut self.ancestors);
+            self.nodes(&element.children);
+            self.ancestors = saved;
+            return;
+        }
+
+        let name = element.name.as_str();
+        if P_CLOSERS.contains(&name) {
+            if let Some(paragraph) = self.ancestors.iter().rev().find(|a| a.name == "p") {
+                self.not_in_paragraph(element, paragraph);
+            }
+        }
+
+        if let Some((_, parents)) = REQUIRED_PARENTS.iter().find(|(child, _)| *child == name) {
+            // At the root of a template the parent is wherever it's rendered
+            if let Some(parent) = self.ancestors.last() {
+                if !parents.contains(&parent.name.as_str()) {
+                    self.warning(
+                        INVALID_PARENT,
+                        element.name_span,
+                        format!("<{}> must be inside {}", name, one_of(parents)),
+                        None,
+                    );
+                }
+            }
+        }
+
+        if NO_NESTING.contains(&name) {
+            if let Some(outer) = self.ancestors.iter().find(|a| a.name == name) {
+                let related =
+                    self.related(outer.name_span, format!("Outer <{}> opened here", name));
+                self.warning(
+                    INVALID_NESTING,
+                    element.name_span,
+                    format!("<{0}> cannot be inside another <{0}>", name),
+                    Some(related),
+                );
+            }
+        }
+
+        self.attributes(element);
+
+        self.ancestors.push(element);
+        self.nodes(&element.children);
+        self.ancestors.pop();
+    }
+
+    fn not_in_paragraph(&mut self, element: &Element, paragraph: &Element) {
+        let related = self.related(paragraph.name_span, "<p> opened here".to_string());
+        self.warning(
+            INVALID_NESTING,
+            element.name_span,
+            format!(
+                "<{}> cannot be inside <p> - the browser ends the <p> before it",
+                element.name
+            ),
+            Some(related),
+        );
+    }
+
+    /// Literal values of enumerated attributes
+    fn attributes(&mut self, element: &Element) {
+        for attribute in &element.attributes {
+            let Some(AttributeValue::Text { span, value }) = &attribute.value else {
+                continue;
+            };
+            let name = attribute.name.to_ascii_lowercase();
+            let Some((_, _, keywords)) = ENUMERATED_ATTRIBUTES.iter().find(|(on, attr, _)| {
+                *attr == name && (on.is_empty() || on.contains(&element.name.as_str()))
+            }) else {
+                continue;
+            };
+            let value = value.trim();
+            if keywords.iter().any(|k| k.eq_ignore_ascii_case(value)) {
+                continue;
+            }
+
+            let expected: Vec<&str> = keywords.iter().copied().filter(|k| !k.is_empty()).collect();
+            self.warning(
+                INVALID_ATTRIBUTE_VALUE,
+                *span,
+                format!(
+                    "Invalid value '{}' for '{}' on <{}> - expected {}",
+                    value,
+                    attribute.name,
+                    element.name,
+                    join_or(&expected)
+                ),
+                None,
+            );
+        }
+    }
+
+    fn warning(
+        &mut self,
+        code: &str,
+        span: Span,
+        message: String,
+        related: Option<DiagnosticRelatedInformation>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            range: self.doc.span_to_range(span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(code),
+            message,
+            source: Some("luat".to_string()),
+            related_information: related.map(|related| vec![related]),
+            ..Default::default()
+        });
+    }
+
+    fn related(&self, span: Span, message: String) -> DiagnosticRelatedInformation {
+        DiagnosticRelatedInformation {
+            location: Location {
+                uri: self.doc.uri().clone(),
+                range: self.doc.span_to_range(span),
+            },
+            message,
+        }
+    }
+}
+
+/// `<ul>, <ol> or <menu>`
+fn one_of(elements: &[&str]) -> String {
+    let tags: Vec<String> = elements.iter().map(|e| format!("<{}>", e)).collect();
+    let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+    join_or(&tags)
+}
+
+/// `a, b or c`
+fn join_or(items: &[&str]) -> String {
+    match items.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::diagnostics::test_support::messages;
+
+    #[test]
+    fn test_content_model() {
+        assert_eq!(
+            messages(
+                check_html,
+                "<p>intro<div>x</div></p><p><span>{#if a}<ul></ul>{/if}</span></p>"
+            ),
+            vec![
+                "<div> cannot be inside <p> - the browser ends the <p> before it",
+                "<ul> cannot be inside <p> - the browser ends the <p> before it",
+            ]
+        );
+        assert_eq!(
+            messages(
+                check_html,
+                "<div><li>x</li></div><ul>{#each xs as x}<li>{x}</li>{/each}</ul><li>root</li>"
+            ),
+            vec!["<li> must be inside <ul>, <ol> or <menu>"]
+        );
+        assert_eq!(
+            messages(check_html, "<a href=\"/\"><span><a href=\"/x\">x</a></span></a><form><Card><form></form></Card></form>"),
+            vec!["<a> cannot be inside another <a>"]
+        );
+    }
+
+    #[test]
+    fn test_enumerated_attributes() {
+        assert_eq!(
+            messages(
+                check_html,
+                "<button type=\"sumbit\">Go</button><input type=\"EMAIL\" dir={d}>\
+                 <img loading=\"lazy\" decoding=\"fast\" hidden><Button type=\"primary\" />"
+            ),
+            vec![
+                "Invalid value 'sumbit' for 'type' on <button> - expected submit, reset or button",
+                "Invalid value 'fast' for 'decoding' on <img> - expected sync, async or auto",
+            ]
+        );
+    }
+}
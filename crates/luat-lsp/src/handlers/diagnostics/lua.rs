@@ -0,0 +1,96 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Lua syntax errors in scripts and in every Lua fragment of the markup:
+//! expressions, attribute values, block conditions, `{#each}` iterables and
+//! directive arguments.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use super::rules::{self, EMPTY_EXPRESSION, LUA_SYNTAX};
+use crate::document::Document;
+use crate::syntax::Node;
+
+pub fn check_lua(doc: &Document) -> Vec<Diagnostic> {
+    let text = doc.text();
+
+    // Scripts and markup fragments are parsed along with the scope model
+    let mut diagnostics: Vec<Diagnostic> = doc
+        .lua()
+        .errors
+        .iter()
+        .map(|error| Diagnostic {
+            range: doc.span_to_range(error.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(LUA_SYNTAX),
+            message: error.message.clone(),
+            source: Some("luat".to_string()),
+            ..Default::default()
+        })
+        .collect();
+
+    // Empty `{}` expressions in the markup aren't parsed, so they are reported here
+    doc.tree().walk(&mut |node| {
+        if let Node::Expression(expr) = node {
+            if expr.closed && expr.content.text(text).trim().is_empty() {
+                diagnostics.push(Diagnostic {
+                    range: doc.span_to_range(expr.span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: rules::code(EMPTY_EXPRESSION),
+                    message: "Empty expression".to_string(),
+                    source: Some("luat".to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+    });
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_errors_in_every_lua_region() {
+        let text = "<script>\nlocal x = = 1\n</script>\n\
+            <p class={cls ..}>{user.name ..}</p>\n\
+            {#if a and}{:else if b or}{/if}\n\
+            {#each list() + as item}{/each}\n\
+            {@local y = 1 +}{@html +}";
+        let diagnostics = check_lua(&test_document(text));
+        let lines: Vec<u32> = diagnostics.iter().map(|d| d.range.start.line).collect();
+        assert_eq!(lines, vec![1, 3, 3, 4, 4, 5, 6, 6]);
+        assert_eq!(
+            diagnostics[0].range.start,
+            Position {
+                line: 1,
+                character: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_statements_in_expressions() {
+        let diagnostics = check_lua(&test_document("<p>{local x = 1}</p>{x = 2}{}"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected an expression, found a 'local' statement - statements aren't allowed here",
+                "Unexpected '=' - assignments are statements and aren't allowed here",
+                "Empty expression",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_valid_lua() {
+        let text = "<script module>\nlocal M = {}\nreturn M\n</script>\n\
+            <a href=\"/u/{user.id}\" {...props}>{#each pairs(t) as k, v}{k}{/each}</a>\n\
+            {@local a, b = 1, 2}{@render props.children()}";
+        assert!(check_lua(&test_document(text)).is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use crate::css::{self, CssSeverity};
+use crate::document::Document;
+use crate::scheduler::Cancellation;
+use crate::syntax::{AttributeKind, Span};
+
+mod a11y;
+pub mod components;
+mod grammar;
+mod html;
+mod lua;
+pub mod pull;
+mod requires;
+pub mod rules;
+mod structure;
+mod suppress;
+mod taint;
+mod variables;
+
+use rules::{
+    CSS_LINT, CSS_SYNTAX, DUPLICATE_ATTRIBUTE, EMPTY_EXPRESSION, INVALID_SHORTHAND, SYNTAX_ERROR,
+    UNCLOSED_SCRIPT,
+};
+
+/// Compute diagnostics for a document, with the configured rule severities
+/// and its suppression comments applied. Once `cancellation` is set it stops
+/// between checks and returns nothing.
+pub fn compute_diagnostics(doc: &Document, cancellation: &Cancellation) -> Vec<Diagnostic> {
+    let config = &doc.config().lint;
+    let checks: &[&dyn Fn() -> Vec<Diagnostic>] = &[
+        // Check for syntax errors such as unclosed braces
+        &|| check_parse_errors(doc),
+        // Check nesting of blocks and elements
+        &|| structure::check_structure(doc),
+        // Check block clauses and directives
+        &|| grammar::check_grammar(doc),
+        // Check Lua syntax in scripts and template expressions
+        &|| lua::check_lua(doc),
+        // Check for require paths that don't resolve to a file
+        &|| requires::check_requires(doc),
+        // Check for component tags with no matching import
+        &|| components::check_components(doc),
+        // Check for variables used in the markup that are never defined
+        &|| variables::check_undefined(doc),
+        // Check for imports and locals that are never used
+        &|| variables::check_unused(doc),
+        // Check for unclosed tags
+        &|| check_unclosed_tags(doc),
+        // Check HTML content-model rules and enumerated attribute values
+        &|| html::check_html(doc),
+        // Check element attributes and component props
+        &|| check_attributes(doc),
+        // Check for values from props reaching {@html} and script-running attributes
+        &|| taint::check_taint(doc, &config.sanitizers),
+        // Check for common accessibility problems
+        &|| a11y::check_a11y(doc),
+        // Check CSS syntax in style blocks
+        &|| check_styles(doc),
+    ];
+
+    let mut diagnostics = Vec::new();
+    for check in checks {
+        if cancellation.is_cancelled() {
+            return Vec::new();
+        }
+        diagnostics.extend(check());
+    }
+
+    let diagnostics = suppress::apply_suppressions(doc, diagnostics);
+    config.apply(doc.uri(), diagnostics)
+}
+
+/// Report syntax errors found by the template parser, such as unclosed braces
+fn check_parse_errors(doc: &Document) -> Vec<Diagnostic> {
+    doc.tree()
+        .errors
+        .iter()
+        .map(|error| Diagnostic {
+            range: doc.span_to_range(error.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(SYNTAX_ERROR),
+            message: error.message.clone(),
+            source: Some("luat".to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn check_unclosed_tags(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    // Check for unclosed script tags. An unclosed client-side script swallows
+    // the rest of the document just like a Lua one, so check every kind.
+    for script in doc.tree().scripts() {
+        if script.end_tag.is_none() {
+            diagnostics.push(Diagnostic {
+                range: doc.span_to_range(script.start_tag),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: rules::code(UNCLOSED_SCRIPT),
+                message: "Unclosed <script> tag".to_string(),
+                source: Some("luat".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+fn check_attributes(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let text = doc.text();
+
+    for element in doc.tree().elements() {
+        let what = if element.is_component() {
+            "prop"
+        } else {
+            "attribute"
+        };
+
+        for (i, attribute) in element.attributes.iter().enumerate() {
+            // Spread attributes may legitimately repeat names
+            let duplicate = attribute.kind != AttributeKind::Spread
+                && !attribute.name.is_empty()
+                && element.attributes[..i]
+                    .iter()
+                    .any(|a| a.name.eq_ignore_ascii_case(&attribute.name));
+            if duplicate {
+                diagnostics.push(Diagnostic {
+                    range: doc.span_to_range(attribute.name_span),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: rules::code(DUPLICATE_ATTRIBUTE),
+                    message: format!("Duplicate {} '{}'", what, attribute.name),
+                    source: Some("luat".to_string()),
+                    ..Default::default()
+                });
+            }
+
+            if attribute.kind == AttributeKind::Shorthand
+                && !attribute.name.is_empty()
+                && !is_identifier(&attribute.name)
+            {
+                diagnostics.push(Diagnostic {
+                    range: doc.span_to_range(attribute.span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: rules::code(INVALID_SHORTHAND),
+                    message: format!(
+                        "Shorthand {} must be a variable name, e.g. {{title}}; use name={{...}} for expressions",
+                        what
+                    ),
+                    source: Some("luat".to_string()),
+                    ..Default::default()
+                });
+            }
+
+            let Some(value) = &attribute.value else {
+                continue;
+            };
+            for expr in value.expressions() {
+                if expr.closed && expr.content.text(text).trim().is_empty() {
+                    diagnostics.push(Diagnostic {
+                        range: doc.span_to_range(expr.span),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: rules::code(EMPTY_EXPRESSION),
+                        message: format!("Empty expression in {}", what),
+                        source: Some("luat".to_string()),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn check_styles(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let text = doc.text();
+
+    for element in doc.tree().elements() {
+        if element.name != "style" {
+            continue;
+        }
+        let start = element.start_tag.end;
+        let end = element
+            .end_tag
+            .as_ref()
+            .map_or(element.span.end, |end| end.span.start);
+
+        for error in css::check(&text[start..end]) {
+            diagnostics.push(Diagnostic {
+                range: doc
+                    .span_to_range(Span::new(start + error.span.start, start + error.span.end)),
+                severity: Some(match error.severity {
+                    CssSeverity::Error => DiagnosticSeverity::ERROR,
+                    CssSeverity::Warning => DiagnosticSeverity::WARNING,
+                }),
+                code: rules::code(match error.severity {
+                    CssSeverity::Error => CSS_SYNTAX,
+                    CssSeverity::Warning => CSS_LINT,
+                }),
+                message: error.message,
+                source: Some("luat".to_string()),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Helpers shared by the tests of every check
+#[cfg(test)]
+pub(crate) mod test_support {
+    use tower_lsp::lsp_types::{Diagnostic, NumberOrString};
+
+    use crate::document::{test_document, Document};
+    use crate::scheduler::Cancellation;
+
+    /// Every diagnostic of a document, as it's published
+    pub(crate) fn all(doc: &Document) -> Vec<Diagnostic> {
+        super::compute_diagnostics(doc, &Cancellation::default())
+    }
+
+    /// The messages `check` reports for a template
+    pub(crate) fn messages(
+        check: impl Fn(&Document) -> Vec<Diagnostic>,
+        text: &str,
+    ) -> Vec<String> {
+        check(&test_document(text))
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    /// The codes of the diagnostics `check` reports for a template
+    pub(crate) fn codes(check: impl Fn(&Document) -> Vec<Diagnostic>, text: &str) -> Vec<String> {
+        check(&test_document(text))
+            .into_iter()
+            .map(|d| match d.code {
+                Some(NumberOrString::String(code)) => code,
+                _ => String::new(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Reports for pulled diagnostics (`textDocument/diagnostic` and
+//! `workspace/diagnostic`).
+//!
+//! A report's result ID is a hash of its diagnostics, so a client asking
+//! with the ID it already has gets `Unchanged` without the server keeping
+//! track of what it sent.
+
+use dashmap::DashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tower_lsp::lsp_types::{
+    Diagnostic, DocumentDiagnosticReportKind, FullDocumentDiagnosticReport,
+    UnchangedDocumentDiagnosticReport, Url,
+};
+
+use super::compute_diagnostics;
+use crate::config::Config;
+use crate::document::{Document, PositionEncoding};
+use crate::handlers::files::find_files;
+use crate::scheduler::Cancellation;
+
+/// A report of `diagnostics`, or `Unchanged` if the client already has them
+pub fn report(
+    diagnostics: Vec<Diagnostic>,
+    previous: Option<&str>,
+) -> DocumentDiagnosticReportKind {
+    let result_id = result_id(&diagnostics);
+    if previous == Some(result_id.as_str()) {
+        DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport { result_id })
+    } else {
+        DocumentDiagnosticReportKind::Full(FullDocumentDiagnosticReport {
+            result_id: Some(result_id),
+            items: diagnostics,
+        })
+    }
+}
+
+fn result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    // Diagnostic doesn't implement Hash; its JSON form identifies it as well
+    serde_json::to_string(diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Every template under `root`
+pub fn workspace_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    find_files(root, &|name| name.ends_with(".luat"), &mut files);
+    files.sort();
+    files
+}
+
+/// Diagnostics of templates that aren't open, by path, kept until the file
+/// is modified or the cache is cleared
+#[derive(Default)]
+pub struct FileDiagnostics {
+    files: DashMap<PathBuf, (SystemTime, Vec<Diagnostic>)>,
+}
+
+impl FileDiagnostics {
+    /// Diagnostics for a template on disk, checked with `config`, or `None`
+    /// if it can't be read or the check was cancelled
+    pub fn get(
+        &self,
+        path: &Path,
+        config: Arc<Config>,
+        encoding: PositionEncoding,
+        cancellation: &Cancellation,
+    ) -> Option<Vec<Diagnostic>> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        if let Some(cached) = self.files.get(path) {
+            if cached.0 == modified {
+                return Some(cached.1.clone());
+            }
+        }
+
+        let text = std::fs::read_to_string(path).ok()?;
+        let uri = Url::from_file_path(path).ok()?;
+        let mut doc = Document::new(uri, text, encoding);
+        doc.set_config(config);
+        let diagnostics = compute_diagnostics(&doc, cancellation);
+        if cancellation.is_cancelled() {
+            return None;
+        }
+        self.files
+            .insert(path.to_path_buf(), (modified, diagnostics.clone()));
+        Some(diagnostics)
+    }
+
+    /// Forget everything, e.g. after the configuration or the set of
+    /// modules changed
+    pub fn clear(&self) {
+        self.files.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+    use crate::handlers::diagnostics::test_support::all;
+    use std::io::Write;
+    use std::time::Duration;
+
+    #[test]
+    fn test_unchanged_reports() {
+        let doc = test_document("<p>{user}</p>");
+
+        let DocumentDiagnosticReportKind::Full(full) = report(all(&doc), None) else {
+            panic!("expected a full report");
+        };
+        assert_eq!(full.items.len(), 1);
+        let result_id = full.result_id.unwrap();
+
+        let again = report(all(&doc), Some(&result_id));
+        assert_eq!(
+            again,
+            DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport {
+                result_id: result_id.clone()
+            })
+        );
+        assert!(matches!(
+            report(Vec::new(), Some(&result_id)),
+            DocumentDiagnosticReportKind::Full(_)
+        ));
+    }
+
+    #[test]
+    fn test_workspace_files() {
+        let root = std::env::temp_dir().join(format!("luat-workspace-{}", std::process::id()));
+        for dir in ["src/pages", "node_modules/pkg", ".cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "src/pages/index.luat",
+            "src/App.luat",
+            "src/util.lua",
+            "node_modules/pkg/Button.luat",
+            ".cache/Old.luat",
+        ] {
+            std::fs::write(root.join(file), "<p />").unwrap();
+        }
+        let files = workspace_files(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // Sorted templates, skipping dependencies and hidden directories
+        assert_eq!(
+            files,
+            [root.join("src/App.luat"), root.join("src/pages/index.luat")]
+        );
+    }
+
+    #[test]
+    fn test_file_diagnostics_are_cached_until_modified() {
+        let root = std::env::temp_dir().join(format!("luat-file-diags-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let path = root.join("index.luat");
+        let write = |text: &str, modified: SystemTime| {
+            let mut file = std::fs::File::create(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+            file.set_modified(modified).unwrap();
+        };
+        let cache = FileDiagnostics::default();
+        let get = || {
+            let config = Arc::new(Config::default());
+            let diagnostics = cache.get(
+                &path,
+                config,
+                PositionEncoding::Utf8,
+                &Cancellation::default(),
+            );
+            diagnostics.map(|diagnostics| diagnostics.len())
+        };
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        write("<p>{user}</p>", modified);
+        assert_eq!(get(), Some(1));
+        // Same modification time: the cached diagnostics are reused
+        write("<p>{user} {name}</p>", modified);
+        assert_eq!(get(), Some(1));
+        cache.clear();
+        assert_eq!(get(), Some(2));
+        // A newer file is checked again
+        write("<p></p>", modified + Duration::from_secs(1));
+        assert_eq!(get(), Some(0));
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(get(), None);
+    }
+}
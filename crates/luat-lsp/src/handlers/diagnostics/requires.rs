@@ -0,0 +1,125 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `require` calls in scripts whose path doesn't resolve to a file
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+
+use super::rules::{self, UNRESOLVED_REQUIRE};
+use crate::document::Document;
+use crate::handlers::definition::{document_dir, require_candidates};
+
+/// Report every `require("...")` in a script that resolves to no file,
+/// listing the locations tried. Documents that aren't on disk are skipped.
+pub fn check_requires(doc: &Document) -> Vec<Diagnostic> {
+    let Some(doc_dir) = document_dir(doc) else {
+        return Vec::new();
+    };
+    let model = doc.lua();
+    let mut diagnostics = Vec::new();
+
+    for require in &model.requires {
+        if !model.in_script(require.span.start) {
+            continue;
+        }
+        let candidates = require_candidates(&require.path, doc);
+        if candidates.iter().any(|candidate| candidate.exists()) {
+            continue;
+        }
+
+        let tried: Vec<String> = candidates
+            .iter()
+            .map(|candidate| {
+                let relative = candidate.strip_prefix(&doc_dir).unwrap_or(candidate);
+                format!("  {}", relative.display())
+            })
+            .collect();
+        diagnostics.push(Diagnostic {
+            range: doc.span_to_range(require.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(UNRESOLVED_REQUIRE),
+            message: format!(
+                "Cannot resolve module '{}' - tried:\n{}",
+                require.path,
+                tried.join("\n")
+            ),
+            source: Some("luat".to_string()),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::document::PositionEncoding;
+    use std::sync::Arc;
+    use tower_lsp::lsp_types::{Position, Url};
+
+    #[test]
+    fn test_unresolved_requires() {
+        let root = std::env::temp_dir().join(format!("luat-requires-{}", std::process::id()));
+        let pages = root.join("src/pages");
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::create_dir_all(&pages).unwrap();
+        std::fs::write(root.join("src/components/Card.luat"), "<div />").unwrap();
+
+        let text = "<script>\nlocal Card = require(\"components/Card\")\n\
+            local Crad = require(\"components/Crad\")\n</script>\n<Card /><Crad />";
+        let uri = Url::from_file_path(pages.join("index.luat")).unwrap();
+        let doc = Document::new(uri, text.to_string(), PositionEncoding::Utf8);
+        let diagnostics = check_requires(&doc);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(
+            diagnostic.range.start,
+            Position {
+                line: 2,
+                character: 21
+            }
+        );
+        assert!(diagnostic.message.starts_with(
+            "Cannot resolve module 'components/Crad' - tried:\n  components/Crad.luat\n"
+        ));
+        assert!(diagnostic.message.contains("\n  ../components/Crad.lua\n"));
+        assert_eq!(diagnostic.message.lines().count(), 13);
+    }
+
+    #[test]
+    fn test_module_roots() {
+        let root = std::env::temp_dir().join(format!("luat-module-roots-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("lib/ui")).unwrap();
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::write(root.join("lib/ui/Card.luat"), "<div />").unwrap();
+        std::fs::write(root.join("src/components/Badge.luat"), "<span />").unwrap();
+
+        let text = "<script>\nlocal Card = require(\"ui/Card\")\n\
+            local Badge = require(\"components/Badge\")\n</script>";
+        let uri = Url::from_file_path(root.join("index.luat")).unwrap();
+        let mut doc = Document::new(uri, text.to_string(), PositionEncoding::Utf8);
+        let settings = serde_json::json!({ "modules": { "roots": ["lib"] } });
+        let config = Config::from_layers(Some(&root), &[&settings]).unwrap();
+        doc.set_config(Arc::new(config));
+        let diagnostics = check_requires(&doc);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        // Only the configured roots are searched
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Cannot resolve module 'components/Badge'"));
+    }
+
+    #[test]
+    fn test_untitled_documents_are_skipped() {
+        let uri = Url::parse("untitled:Untitled-1").unwrap();
+        let text = "<script>local X = require(\"nowhere\")</script>";
+        let doc = Document::new(uri, text.to_string(), PositionEncoding::Utf8);
+        assert!(check_requires(&doc).is_empty());
+    }
+}
@@ -0,0 +1,476 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! The rule registry: every diagnostic carries one of these codes, and its
+//! severity comes from the rule's default unless the lint configuration
+//! overrides it for the project, a directory glob or the rule alone.
+
+use globset::Glob;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+
+pub const SYNTAX_ERROR: &str = "luat/syntax-error";
+pub const UNCLOSED_BLOCK: &str = "luat/unclosed-block";
+pub const UNCLOSED_ELEMENT: &str = "luat/unclosed-element";
+pub const UNCLOSED_SCRIPT: &str = "luat/unclosed-script";
+pub const MISMATCHED_CLOSER: &str = "luat/mismatched-closer";
+pub const UNEXPECTED_CLOSER: &str = "luat/unexpected-closer";
+pub const VOID_END_TAG: &str = "luat/void-end-tag";
+pub const UNKNOWN_TAG: &str = "luat/unknown-tag";
+pub const CLAUSE_PLACEMENT: &str = "luat/clause-placement";
+pub const TAG_SYNTAX: &str = "luat/tag-syntax";
+pub const LUA_SYNTAX: &str = "luat/lua-syntax";
+pub const EMPTY_EXPRESSION: &str = "luat/empty-expression";
+pub const UNRESOLVED_REQUIRE: &str = "luat/unresolved-require";
+pub const UNKNOWN_COMPONENT: &str = "luat/unknown-component";
+pub const UNDEFINED_VARIABLE: &str = "luat/undefined-variable";
+pub const UNUSED_IMPORT: &str = "luat/unused-import";
+pub const UNUSED_VARIABLE: &str = "luat/unused-variable";
+pub const DUPLICATE_ATTRIBUTE: &str = "luat/duplicate-attribute";
+pub const INVALID_SHORTHAND: &str = "luat/invalid-shorthand";
+pub const INVALID_NESTING: &str = "luat/invalid-nesting";
+pub const INVALID_PARENT: &str = "luat/invalid-parent";
+pub const INVALID_ATTRIBUTE_VALUE: &str = "luat/invalid-attribute-value";
+pub const UNSAFE_HTML: &str = "luat/unsafe-html";
+pub const UNSAFE_ATTRIBUTE: &str = "luat/unsafe-attribute";
+pub const CSS_SYNTAX: &str = "luat/css-syntax";
+pub const CSS_LINT: &str = "luat/css-lint";
+pub const UNUSED_SUPPRESSION: &str = "luat/unused-suppression";
+pub const A11Y_IMG_ALT: &str = "a11y/img-alt";
+pub const A11Y_ACCESSIBLE_NAME: &str = "a11y/accessible-name";
+pub const A11Y_FORM_LABEL: &str = "a11y/form-label";
+pub const A11Y_ARIA_ROLE: &str = "a11y/aria-role";
+pub const A11Y_ARIA_ATTRIBUTE: &str = "a11y/aria-attribute";
+pub const A11Y_CLICK_ON_STATIC: &str = "a11y/click-on-static";
+pub const A11Y_HTML_LANG: &str = "a11y/html-lang";
+pub const A11Y_HEADING_ORDER: &str = "a11y/heading-order";
+
+pub struct Rule {
+    pub code: &'static str,
+    pub severity: DiagnosticSeverity,
+    pub description: &'static str,
+}
+
+const fn rule(code: &'static str, severity: DiagnosticSeverity, description: &'static str) -> Rule {
+    Rule {
+        code,
+        severity,
+        description,
+    }
+}
+
+const ERROR: DiagnosticSeverity = DiagnosticSeverity::ERROR;
+const WARNING: DiagnosticSeverity = DiagnosticSeverity::WARNING;
+
+pub const RULES: &[Rule] = &[
+    rule(
+        SYNTAX_ERROR,
+        ERROR,
+        "Template syntax errors, such as unclosed braces",
+    ),
+    rule(
+        UNCLOSED_BLOCK,
+        ERROR,
+        "A block missing its closing tag, e.g. {#if} without {/if}",
+    ),
+    rule(UNCLOSED_ELEMENT, ERROR, "An element missing its end tag"),
+    rule(UNCLOSED_SCRIPT, ERROR, "A <script> missing its end tag"),
+    rule(
+        MISMATCHED_CLOSER,
+        ERROR,
+        "Blocks and elements closed out of order",
+    ),
+    rule(
+        UNEXPECTED_CLOSER,
+        ERROR,
+        "A closing tag with nothing open to close",
+    ),
+    rule(
+        VOID_END_TAG,
+        ERROR,
+        "An end tag for a void element, e.g. </br>",
+    ),
+    rule(UNKNOWN_TAG, ERROR, "An unknown block, clause or directive"),
+    rule(
+        CLAUSE_PLACEMENT,
+        ERROR,
+        "A clause in the wrong block or position, e.g. {:else} after {:else}",
+    ),
+    rule(
+        TAG_SYNTAX,
+        ERROR,
+        "A block or directive missing its condition, bindings or arguments",
+    ),
+    rule(
+        LUA_SYNTAX,
+        ERROR,
+        "Lua syntax errors in scripts and expressions",
+    ),
+    rule(EMPTY_EXPRESSION, ERROR, "An empty {} expression"),
+    rule(
+        UNRESOLVED_REQUIRE,
+        ERROR,
+        "A require path that doesn't resolve to a file",
+    ),
+    rule(
+        UNKNOWN_COMPONENT,
+        ERROR,
+        "A component tag with no matching import",
+    ),
+    rule(
+        UNDEFINED_VARIABLE,
+        WARNING,
+        "A variable used in the markup that isn't defined",
+    ),
+    rule(UNUSED_IMPORT, WARNING, "An import that is never used"),
+    rule(
+        UNUSED_VARIABLE,
+        WARNING,
+        "A local, function, {@local} value or index variable that is never used",
+    ),
+    rule(
+        DUPLICATE_ATTRIBUTE,
+        WARNING,
+        "The same attribute or prop given twice",
+    ),
+    rule(
+        INVALID_SHORTHAND,
+        ERROR,
+        "A shorthand attribute that isn't a variable name",
+    ),
+    rule(
+        INVALID_NESTING,
+        WARNING,
+        "Elements the browser won't nest as written, e.g. <div> in <p>",
+    ),
+    rule(
+        INVALID_PARENT,
+        WARNING,
+        "Elements outside their required parent, e.g. <li> outside a list",
+    ),
+    rule(
+        INVALID_ATTRIBUTE_VALUE,
+        WARNING,
+        "Invalid values for enumerated attributes such as type",
+    ),
+    rule(
+        UNSAFE_HTML,
+        WARNING,
+        "Unsanitized values from props rendered with {@html}",
+    ),
+    rule(
+        UNSAFE_ATTRIBUTE,
+        WARNING,
+        "Unsanitized values from props in URLs, styles, event handlers or spreads",
+    ),
+    rule(CSS_SYNTAX, ERROR, "CSS syntax errors in <style> blocks"),
+    rule(CSS_LINT, WARNING, "Likely mistakes in <style> blocks"),
+    rule(
+        UNUSED_SUPPRESSION,
+        WARNING,
+        "A luat-ignore or luat-disable comment that suppresses nothing",
+    ),
+    rule(A11Y_IMG_ALT, WARNING, "<img> without alt"),
+    rule(
+        A11Y_ACCESSIBLE_NAME,
+        WARNING,
+        "Buttons and links without text or a label",
+    ),
+    rule(A11Y_FORM_LABEL, WARNING, "Form controls without a label"),
+    rule(
+        A11Y_ARIA_ROLE,
+        WARNING,
+        "role values that aren't ARIA roles",
+    ),
+    rule(
+        A11Y_ARIA_ATTRIBUTE,
+        WARNING,
+        "aria-* attributes that don't exist",
+    ),
+    rule(
+        A11Y_CLICK_ON_STATIC,
+        WARNING,
+        "Click handlers on non-interactive elements",
+    ),
+    rule(A11Y_HTML_LANG, WARNING, "<html> without lang"),
+    rule(
+        A11Y_HEADING_ORDER,
+        WARNING,
+        "Heading levels that skip, e.g. <h2> then <h4>",
+    ),
+];
+
+/// The diagnostic code for a rule
+pub fn code(code: &str) -> Option<NumberOrString> {
+    Some(NumberOrString::String(code.to_string()))
+}
+
+/// The registered rule a name refers to. The `luat/` prefix is optional.
+pub fn find(name: &str) -> Option<&'static Rule> {
+    RULES
+        .iter()
+        .find(|rule| rule.code == name || rule.code.strip_prefix("luat/") == Some(name))
+}
+
+/// A rule's severity as configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Hint,
+    Info,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    fn severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Hint => Some(DiagnosticSeverity::HINT),
+            RuleLevel::Info => Some(DiagnosticSeverity::INFORMATION),
+            RuleLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            RuleLevel::Error => Some(DiagnosticSeverity::ERROR),
+        }
+    }
+}
+
+/// Rule severities for the files matching some globs
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Override {
+    /// Globs relative to the project root, e.g. `legacy/**`
+    pub files: Vec<String>,
+    pub rules: HashMap<String, RuleLevel>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Severities by rule name, e.g. `unused-import = "off"`. A name ending
+    /// in `/*` covers every rule with that prefix, e.g. `a11y/*`.
+    pub rules: HashMap<String, RuleLevel>,
+    /// Severities for parts of the project, applied in order after `rules`
+    pub overrides: Vec<Override>,
+    /// Functions that make a value from props safe, by name or dotted path
+    pub sanitizers: Vec<String>,
+    /// The directory override globs are relative to
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+}
+
+impl LintConfig {
+    /// The configured levels that apply to a document, most specific last
+    fn levels_for(&self, uri: &Url) -> Vec<&HashMap<String, RuleLevel>> {
+        let mut levels = vec![&self.rules];
+        let Ok(path) = uri.to_file_path() else {
+            return levels;
+        };
+        let relative = match &self.root {
+            Some(root) => path.strip_prefix(root).unwrap_or(&path),
+            None => &path,
+        };
+        for config in &self.overrides {
+            let matches = config.files.iter().any(|pattern| {
+                Glob::new(pattern)
+                    .map(|glob| glob.compile_matcher().is_match(relative))
+                    .unwrap_or(false)
+            });
+            if matches {
+                levels.push(&config.rules);
+            }
+        }
+        levels
+    }
+
+    /// The configured rule names that don't refer to any rule, in `rules`
+    /// and then in each override
+    pub fn unknown_rules(&self) -> Vec<&str> {
+        let known = |name: &str| match wildcard_prefix(name) {
+            Some(prefix) => RULES.iter().any(|rule| rule.code.starts_with(prefix)),
+            None => find(name).is_some(),
+        };
+        let mut unknown = Vec::new();
+        for levels in std::iter::once(&self.rules).chain(self.overrides.iter().map(|o| &o.rules)) {
+            let mut names: Vec<&str> = levels
+                .keys()
+                .map(String::as_str)
+                .filter(|name| !known(name))
+                .collect();
+            names.sort_unstable();
+            unknown.extend(names);
+        }
+        unknown
+    }
+
+    /// Set every diagnostic's severity from its rule, dropping the ones
+    /// whose rule is off
+    pub fn apply(&self, uri: &Url, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let levels = self.levels_for(uri);
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                    return Some(diagnostic);
+                };
+                let Some(rule) = find(code) else {
+                    return Some(diagnostic);
+                };
+                let configured = levels
+                    .iter()
+                    .rev()
+                    .find_map(|levels| configured_level(levels, rule.code));
+                diagnostic.severity = match configured {
+                    Some(level) => Some(level.severity()?),
+                    None => Some(rule.severity),
+                };
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+/// The level set for a rule: by its full code, then by its name without the
+/// `luat/` prefix, then by the longest matching `prefix/*`
+fn configured_level(levels: &HashMap<String, RuleLevel>, code: &str) -> Option<RuleLevel> {
+    let short = code.strip_prefix("luat/");
+    levels
+        .get(code)
+        .or_else(|| short.and_then(|short| levels.get(short)))
+        .or_else(|| {
+            levels
+                .iter()
+                .filter_map(|(name, level)| {
+                    let prefix = wildcard_prefix(name)?;
+                    code.starts_with(prefix).then_some((prefix.len(), level))
+                })
+                .max_by_key(|(len, _)| *len)
+                .map(|(_, level)| level)
+        })
+        .copied()
+}
+
+/// The `prefix/` of a `prefix/*` rule name
+fn wildcard_prefix(name: &str) -> Option<&str> {
+    name.strip_suffix('*')
+        .filter(|prefix| prefix.ends_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    fn diagnostic(code: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: super::code(code),
+            message: String::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_registry_codes_are_unique() {
+        for (i, rule) in RULES.iter().enumerate() {
+            assert!(
+                RULES[..i].iter().all(|r| r.code != rule.code),
+                "duplicate rule {}",
+                rule.code
+            );
+        }
+        assert_eq!(find("unused-import").unwrap().code, UNUSED_IMPORT);
+        assert_eq!(find("a11y/img-alt").unwrap().code, A11Y_IMG_ALT);
+        assert!(find("img-alt").is_none());
+    }
+
+    #[test]
+    fn test_levels() {
+        let config = LintConfig {
+            rules: HashMap::from([
+                ("unused-import".to_string(), RuleLevel::Off),
+                ("a11y/*".to_string(), RuleLevel::Error),
+                ("a11y/heading-order".to_string(), RuleLevel::Hint),
+            ]),
+            overrides: vec![Override {
+                files: vec!["legacy/**".to_string()],
+                rules: HashMap::from([("a11y/*".to_string(), RuleLevel::Off)]),
+            }],
+            sanitizers: Vec::new(),
+            root: Some(PathBuf::from("/project")),
+        };
+        let severities = |uri: &str| -> Vec<Option<DiagnosticSeverity>> {
+            let diagnostics = [
+                UNUSED_IMPORT,
+                UNDEFINED_VARIABLE,
+                A11Y_IMG_ALT,
+                A11Y_HEADING_ORDER,
+            ]
+            .map(diagnostic)
+            .to_vec();
+            let uri = Url::parse(uri).unwrap();
+            let applied = config.apply(&uri, diagnostics);
+            applied.into_iter().map(|d| d.severity).collect()
+        };
+
+        assert_eq!(
+            severities("file:///project/pages/index.luat"),
+            vec![
+                Some(DiagnosticSeverity::WARNING),
+                Some(DiagnosticSeverity::ERROR),
+                Some(DiagnosticSeverity::HINT),
+            ]
+        );
+        assert_eq!(
+            severities("file:///project/legacy/old/page.luat"),
+            vec![Some(DiagnosticSeverity::WARNING)]
+        );
+    }
+
+    #[test]
+    fn test_most_specific_level_wins() {
+        let levels = HashMap::from([
+            ("luat/unused-import".to_string(), RuleLevel::Error),
+            ("unused-import".to_string(), RuleLevel::Off),
+            ("luat/*".to_string(), RuleLevel::Hint),
+            ("a11y/*".to_string(), RuleLevel::Off),
+            ("a11y/img-alt".to_string(), RuleLevel::Info),
+        ]);
+        assert_eq!(
+            configured_level(&levels, UNUSED_IMPORT),
+            Some(RuleLevel::Error)
+        );
+        assert_eq!(
+            configured_level(&levels, UNDEFINED_VARIABLE),
+            Some(RuleLevel::Hint)
+        );
+        assert_eq!(
+            configured_level(&levels, A11Y_IMG_ALT),
+            Some(RuleLevel::Info)
+        );
+        assert_eq!(
+            configured_level(&levels, A11Y_HTML_LANG),
+            Some(RuleLevel::Off)
+        );
+    }
+
+    #[test]
+    fn test_unknown_rules() {
+        let config = LintConfig {
+            rules: HashMap::from([
+                ("unused-import".to_string(), RuleLevel::Off),
+                ("a11y/*".to_string(), RuleLevel::Error),
+                ("unused-imports".to_string(), RuleLevel::Off),
+            ]),
+            overrides: vec![Override {
+                files: vec!["legacy/**".to_string()],
+                rules: HashMap::from([("ally/*".to_string(), RuleLevel::Off)]),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(config.unknown_rules(), vec!["unused-imports", "ally/*"]);
+    }
+}
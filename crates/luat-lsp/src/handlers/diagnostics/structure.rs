@@ -0,0 +1,338 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Nesting of blocks and elements.
+//!
+//! The parser closes a block or element early when it meets a closer that
+//! belongs to something further out, and turns closers with no opener into
+//! stray tags. Walking the tree with a stack of open constructs recovers
+//! what went wrong: which closer cut a construct short, and which opener an
+//! orphan closer was probably meant for.
+
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+};
+
+use super::rules::{
+    self, MISMATCHED_CLOSER, UNCLOSED_BLOCK, UNCLOSED_ELEMENT, UNEXPECTED_CLOSER, VOID_END_TAG,
+};
+use crate::document::Document;
+use crate::syntax::{Node, Span, VOID_ELEMENTS};
+
+/// A block or element that is open at some point of the walk
+#[derive(Debug, Clone)]
+struct Open {
+    /// `{#if}` or `<div>`
+    label: String,
+    /// `{/if}` or `</div>`
+    closer: String,
+    /// The opening tag, up to the end of the keyword or name
+    span: Span,
+}
+
+/// Something that ended a construct before its own closer
+enum Terminator {
+    /// `{/kw}`, `</name>` or a `{:kw}` clause of an enclosing block
+    Tag {
+        text: String,
+        span: Span,
+    },
+    EndOfInput,
+}
+
+pub fn check_structure(doc: &Document) -> Vec<Diagnostic> {
+    let text = doc.text();
+    let mut checker = Checker {
+        doc,
+        text,
+        stack: Vec::new(),
+        unclosed: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    checker.nodes(&doc.tree().nodes);
+    checker
+        .diagnostics
+        .sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    doc: &'a Document,
+    text: &'a str,
+    stack: Vec<Open>,
+    /// Constructs cut short so far, for matching orphan closers
+    unclosed: Vec<Open>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match node {
+            Node::Block(block) => {
+                let open = &block.branches[0].tag;
+                let opener = Open {
+                    label: format!("{{#{}}}", block.keyword),
+                    closer: format!("{{/{}}}", block.keyword),
+                    span: Span::new(open.span.start, open.keyword_span.end),
+                };
+                self.stack.push(opener.clone());
+                for branch in &block.branches {
+                    self.nodes(&branch.children);
+                }
+                self.stack.pop();
+                if block.close.is_none() {
+                    self.cut_short(opener, block.span.end, "block");
+                }
+            }
+            Node::Element(element) => {
+                let opener = Open {
+                    label: format!("<{}>", element.name),
+                    closer: format!("</{}>", element.name),
+                    span: Span::new(element.span.start, element.name_span.end),
+                };
+                self.stack.push(opener.clone());
+                self.nodes(&element.children);
+                self.stack.pop();
+                if element.end_tag.is_none() && !element.is_void() {
+                    if element.has_optional_end_tag() {
+                        // Valid HTML, but a later `</p>` may still be meant for it
+                        self.unclosed.push(opener);
+                    } else {
+                        self.cut_short(opener, element.span.end, "element");
+                    }
+                }
+            }
+            Node::StrayTag(tag) if tag.sigil == '/' => {
+                let label = format!("{{#{}}}", tag.keyword);
+                let closer = format!("{{/{}}}", tag.keyword);
+                self.orphan(tag.span, &closer, &label, "block");
+            }
+            Node::StrayEndTag(end) if VOID_ELEMENTS.contains(&end.name.as_str()) => {
+                self.diagnostics.push(Diagnostic {
+                    range: self.doc.span_to_range(end.span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: rules::code(VOID_END_TAG),
+                    message: format!(
+                        "Unexpected </{0}> - <{0}> is a void element and has no end tag",
+                        end.name
+                    ),
+                    source: Some("luat".to_string()),
+                    ..Default::default()
+                });
+            }
+            Node::StrayEndTag(end) => {
+                let label = format!("<{}>", end.name);
+                let closer = format!("</{}>", end.name);
+                self.orphan(end.span, &closer, &label, "element");
+            }
+            _ => {}
+        }
+    }
+
+    /// Report a construct that ended at `end` without its closer
+    fn cut_short(&mut self, open: Open, end: usize, what: &str) {
+        match self.terminator(end) {
+            Terminator::EndOfInput => {
+                self.diagnostics.push(Diagnostic {
+                    range: self.doc.span_to_range(open.span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: rules::code(if what == "block" {
+                        UNCLOSED_BLOCK
+                    } else {
+                        UNCLOSED_ELEMENT
+                    }),
+                    message: format!("Unclosed {} {} - missing {}", open.label, what, open.closer),
+                    source: Some("luat".to_string()),
+                    ..Default::default()
+                });
+            }
+            Terminator::Tag { text, span } => {
+                let mut related =
+                    vec![self.related(open.span, format!("{} opened here", open.label))];
+                // The enclosing construct the closer actually belongs to
+                if let Some(owner) = self.stack.iter().rev().find(|o| o.closer == text) {
+                    related.push(
+                        self.related(owner.span, format!("{} closes this {}", text, owner.label)),
+                    );
+                }
+                self.diagnostics.push(Diagnostic {
+                    range: self.doc.span_to_range(span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: rules::code(MISMATCHED_CLOSER),
+                    message: format!(
+                        "Expected {} before {} - blocks and elements must be closed in reverse order of opening",
+                        open.closer, text
+                    ),
+                    source: Some("luat".to_string()),
+                    related_information: Some(related),
+                    ..Default::default()
+                });
+            }
+        }
+        self.unclosed.push(open);
+    }
+
+    /// Report a closer with no open construct to close
+    fn orphan(&mut self, span: Span, closer: &str, label: &str, what: &str) {
+        // Most likely meant for the latest construct of that kind that was
+        // cut short by an earlier closer
+        let intended = self.unclosed.iter().rev().find(|o| o.closer == closer);
+        let related = intended.map(|open| {
+            vec![self.related(
+                open.span,
+                format!("This {} was already ended by an earlier closer", open.label),
+            )]
+        });
+        self.diagnostics.push(Diagnostic {
+            range: self.doc.span_to_range(span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: rules::code(UNEXPECTED_CLOSER),
+            message: format!("Unexpected {} - no open {} {}", closer, label, what),
+            source: Some("luat".to_string()),
+            related_information: related,
+            ..Default::default()
+        });
+    }
+
+    /// What follows a construct that ended without its closer
+    fn terminator(&self, end: usize) -> Terminator {
+        let rest = &self.text[end..];
+        if rest.is_empty() {
+            return Terminator::EndOfInput;
+        }
+        let len = if rest.starts_with('{') {
+            lua_brace_len(rest)
+        } else {
+            rest.find('>').map_or(rest.len(), |i| i + 1)
+        };
+        let tag = &rest[..len];
+        let text = if let Some(clause) = tag.strip_prefix("{:") {
+            // Clauses are named by their keyword only
+            let keyword: String = clause
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect();
+            format!("{{:{}}}", keyword)
+        } else {
+            tag.split_whitespace().collect::<Vec<_>>().join(" ")
+        };
+        Terminator::Tag {
+            text,
+            span: Span::new(end, end + len),
+        }
+    }
+
+    fn related(&self, span: Span, message: String) -> DiagnosticRelatedInformation {
+        DiagnosticRelatedInformation {
+            location: Location {
+                uri: self.doc.uri().clone(),
+                range: self.doc.span_to_range(span),
+            },
+            message,
+        }
+    }
+}
+
+/// Length of the `{...}` tag at the start of `rest`
+fn lua_brace_len(rest: &str) -> usize {
+    crate::lua::find_closing_brace(rest, 0).map_or(rest.len(), |close| close + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+
+    #[test]
+    fn test_interleaved_blocks() {
+        let diagnostics = check_structure(&test_document("{#if a}{#each xs as x}{/if}{/each}"));
+        assert_eq!(diagnostics.len(), 2);
+
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Expected {/each} before {/if}"));
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(related[0].message, "{#each} opened here");
+        assert_eq!(related[1].message, "{/if} closes this {#if}");
+
+        assert_eq!(
+            diagnostics[1].message,
+            "Unexpected {/each} - no open {#each} block"
+        );
+        assert!(diagnostics[1].related_information.is_some());
+    }
+
+    #[test]
+    fn test_unclosed_and_orphans() {
+        let diagnostics = check_structure(&test_document(
+            "{#if a}\n{#each xs as x}\n<div>\n{/if}\n</span>\n",
+        ));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        // Every unclosed construct is reported, not just the first
+        assert!(messages[0].starts_with("Expected </div> before {/if}"));
+        assert!(messages[1].starts_with("Expected {/each} before {/if}"));
+        assert_eq!(messages[2], "Unexpected </span> - no open <span> element");
+
+        let diagnostics = check_structure(&test_document("<ul>{#if a}<li>x</li>"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unclosed <ul> element - missing </ul>",
+                "Unclosed {#if} block - missing {/if}",
+            ]
+        );
+
+        assert!(
+            check_structure(&test_document("<div>{#if a}<br><img/>{:else}x{/if}</div>")).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_void_and_optional_end_tags() {
+        assert!(check_structure(&test_document(
+            "<ul><li>a<li>b</ul><dl><dt>x<dd>y</dl><p>one<p>two"
+        ))
+        .is_empty());
+
+        let diagnostics = check_structure(&test_document(
+            "<br></br><img src=\"a.png\"></img><BR></BR><Img src=\"a.png\"></Img>",
+        ));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unexpected </br> - <br> is a void element and has no end tag",
+                "Unexpected </img> - <img> is a void element and has no end tag",
+            ]
+        );
+
+        // Tag names are case-sensitive, so these are components
+        let diagnostics = check_structure(&test_document("<Input>x</Input></Input><DIV></div>"));
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unexpected </Input> - no open <Input> element",
+                "Unclosed <DIV> element - missing </DIV>",
+                "Unexpected </div> - no open <div> element",
+            ]
+        );
+
+        // The `<div>` ended the paragraph, so `</p>` has nothing to close
+        let diagnostics = check_structure(&test_document("<p>text<div>x</div></p>"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Unexpected </p> - no open <p> element"
+        );
+        assert!(diagnostics[0].related_information.is_some());
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Inline suppression comments.
+//!
+//! `{/* luat-ignore-next-line unused-import */}` silences the named rules
+//! (or every rule, when none are named) on the following line, and
+//! `{-- luat-disable --}` ... `{-- luat-enable --}` silences them in between.
+//! The same directives work in Lua comments inside scripts. Comments that
+//! end up suppressing nothing are reported.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position};
+
+use super::rules::{self, UNUSED_SUPPRESSION};
+use crate::document::Document;
+use crate::lua::lexer::{Lexer, TokenKind};
+use crate::syntax::{CommentKind, Node, Span};
+
+const IGNORE_NEXT_LINE: &str = "luat-ignore-next-line";
+const DISABLE: &str = "luat-disable";
+const ENABLE: &str = "luat-enable";
+
+#[derive(Debug)]
+struct Suppression {
+    directive: &'static str,
+    /// Rule names as written; empty for every rule
+    rules: Vec<String>,
+    /// Rules a named `luat-enable` took out of a range for every rule
+    except: Vec<String>,
+    /// For the rest of a range split by a named `luat-enable`, the index
+    /// of the range before it
+    continues: Option<usize>,
+    /// The comment
+    span: Span,
+    /// Suppressed diagnostics start in `from..to`
+    from: Position,
+    to: Position,
+    used: bool,
+}
+
+impl Suppression {
+    fn covers(&self, diagnostic: &Diagnostic) -> bool {
+        let start = diagnostic.range.start;
+        if start < self.from || start >= self.to {
+            return false;
+        }
+        let names = |names: &[String]| {
+            let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                return false;
+            };
+            names
+                .iter()
+                .any(|name| rules::find(name).is_some_and(|rule| rule.code == code))
+        };
+        if names(&self.except) {
+            return false;
+        }
+        self.rules.is_empty() || names(&self.rules)
+    }
+}
+
+/// Drop diagnostics silenced by suppression comments, and add one for
+/// every comment that silenced nothing
+pub fn apply_suppressions(doc: &Document, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut suppressions = collect(doc);
+    if suppressions.is_empty() {
+        return diagnostics;
+    }
+
+    let mut kept: Vec<Diagnostic> = diagnostics
+        .into_iter()
+        .filter(|diagnostic| {
+            let mut suppressed = false;
+            for suppression in &mut suppressions {
+                if suppression.covers(diagnostic) {
+                    suppression.used = true;
+                    suppressed = true;
+                }
+            }
+            !suppressed
+        })
+        .collect();
+
+    // A split range is used if any of its parts is
+    for i in (0..suppressions.len()).rev() {
+        if let (true, Some(before)) = (suppressions[i].used, suppressions[i].continues) {
+            suppressions[before].used = true;
+        }
+    }
+
+    for suppression in suppressions
+        .iter()
+        .filter(|s| !s.used && s.continues.is_none())
+    {
+        let unknown = suppression
+            .rules
+            .iter()
+            .find(|name| rules::find(name).is_none());
+        let message = match unknown {
+            Some(name) => format!("Unknown rule '{}' in {}", name, suppression.directive),
+            None if suppression.directive == ENABLE => {
+                format!("{} without a matching {}", ENABLE, DISABLE)
+            }
+            None if suppression.rules.is_empty() => format!(
+                "Unused {} - there are no diagnostics to suppress",
+                suppression.directive
+            ),
+            None => format!(
+                "Unused {} - no {} diagnostic to suppress",
+                suppression.directive,
+                suppression.rules.join(" or ")
+            ),
+        };
+        kept.push(Diagnostic {
+            range: doc.span_to_range(suppression.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(UNUSED_SUPPRESSION),
+            message,
+            source: Some("luat".to_string()),
+            ..Default::default()
+        });
+    }
+    kept
+}
+
+/// Suppressions from template comments and Lua comments in scripts, in
+/// document order, with `luat-disable` ranges ended by their `luat-enable`
+fn collect(doc: &Document) -> Vec<Suppression> {
+    let text = doc.text();
+    let mut comments: Vec<(Span, &str)> = Vec::new();
+    doc.tree().walk(&mut |node| {
+        if let Node::Comment(comment) = node {
+            let (open, close) = match comment.kind {
+                CommentKind::LuatBlock => ("{/*", "*/}"),
+                CommentKind::LuatLine => ("{--", "--}"),
+                CommentKind::Html => return,
+            };
+            let body = comment.span.text(text);
+            let body = body.strip_prefix(open).unwrap_or(body);
+            comments.push((comment.span, body.strip_suffix(close).unwrap_or(body)));
+        }
+    });
+    for script in doc.tree().lua_scripts() {
+        let tokens = Lexer::at(&text[..script.content.end], script.content.start);
+        for token in tokens.filter(|t| t.kind == TokenKind::Comment) {
+            let body = token.text(text).trim_start_matches('-');
+            let body = match body.strip_prefix('[') {
+                // `--[[ ... ]]`
+                Some(long) => long.trim_start_matches('=').trim_start_matches('['),
+                None => body,
+            };
+            comments.push((token.span, body.trim_end_matches([']', '='])));
+        }
+    }
+    comments.sort_by_key(|(span, _)| span.start);
+
+    let end_of_document = doc.offset_to_position(text.len());
+    let mut suppressions: Vec<Suppression> = Vec::new();
+    for (span, body) in comments {
+        let mut words = body
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty());
+        let directive = match words.next() {
+            Some(IGNORE_NEXT_LINE) => IGNORE_NEXT_LINE,
+            Some(DISABLE) => DISABLE,
+            Some(ENABLE) => ENABLE,
+            _ => continue,
+        };
+        let names: Vec<String> = words.map(str::to_string).collect();
+        let end = doc.offset_to_position(span.end);
+
+        let (from, to) = match directive {
+            IGNORE_NEXT_LINE => (
+                Position::new(end.line + 1, 0),
+                Position::new(end.line + 2, 0),
+            ),
+            DISABLE => (end, end_of_document),
+            _ => {
+                // End the open ranges it names, or all of them. A range for
+                // every rule goes on without the named ones.
+                let start = doc.offset_to_position(span.start);
+                let mut ended = false;
+                let mut rest = Vec::new();
+                for (i, open) in suppressions.iter_mut().enumerate() {
+                    if open.directive != DISABLE || open.to != end_of_document {
+                        continue;
+                    }
+                    let named = open
+                        .rules
+                        .iter()
+                        .any(|r| names.iter().any(|n| same_rule(r, n)));
+                    if !names.is_empty() && !named && !open.rules.is_empty() {
+                        continue;
+                    }
+                    open.to = start;
+                    ended = true;
+                    if !names.is_empty() && open.rules.is_empty() {
+                        rest.push(Suppression {
+                            directive: DISABLE,
+                            rules: Vec::new(),
+                            except: [open.except.as_slice(), names.as_slice()].concat(),
+                            continues: Some(i),
+                            span: open.span,
+                            from: end,
+                            to: end_of_document,
+                            used: false,
+                        });
+                    }
+                }
+                suppressions.extend(rest);
+                if ended {
+                    continue;
+                }
+                // Reported as unmatched
+                (end, end)
+            }
+        };
+        suppressions.push(Suppression {
+            directive,
+            rules: names,
+            except: Vec::new(),
+            continues: None,
+            span,
+            from,
+            to,
+            used: false,
+        });
+    }
+    suppressions
+}
+
+/// Whether two rule names written in comments name the same rule, with or
+/// without their group prefix
+fn same_rule(a: &str, b: &str) -> bool {
+    match (rules::find(a), rules::find(b)) {
+        (Some(a), Some(b)) => a.code == b.code,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handlers::diagnostics::test_support::{all, messages};
+
+    #[test]
+    fn test_ignore_next_line() {
+        let text = "<script>\n\
+            -- luat-ignore-next-line unused-import unresolved-require\n\
+            local Card = require(\"components/Card\")\n\
+            </script>\n\
+            {/* luat-ignore-next-line undefined-variable, luat/unused-variable */}\n\
+            <p>{usre}</p>\n\
+            {/* luat-ignore-next-line */}\n\
+            <p>{missing}</p>";
+        assert_eq!(messages(all, text), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_disable_enable() {
+        let text = "{-- luat-disable undefined-variable --}\n<p>{a}</p>\n<p>{b}</p>\n\
+            {-- luat-enable --}\n<p>{c}</p>";
+        assert_eq!(messages(all, text), vec!["Undefined variable 'c'"]);
+    }
+
+    #[test]
+    fn test_enable_with_other_spelling() {
+        let text = "{-- luat-disable luat/undefined-variable --}\n<p>{a}</p>\n\
+            {-- luat-enable undefined-variable --}\n<p>{b}</p>";
+        assert_eq!(messages(all, text), vec!["Undefined variable 'b'"]);
+    }
+
+    #[test]
+    fn test_enable_one_rule_of_a_bare_disable() {
+        let text = "{-- luat-disable --}\n<p>{a}</p>\n\
+            {-- luat-enable undefined-variable --}\n<p>{b}</p>\n\
+            {-- luat-enable --}\n<p>{c}</p>";
+        assert_eq!(
+            messages(all, text),
+            vec!["Undefined variable 'b'", "Undefined variable 'c'"]
+        );
+    }
+
+    #[test]
+    fn test_unused_suppressions() {
+        let text = "{/* luat-ignore-next-line unused-import */}\n<p>{props.a}</p>\n\
+            {/* luat-ignore-next-line no-such-rule */}\n<p></p>\n\
+            {-- luat-enable --}";
+        assert_eq!(
+            messages(all, text),
+            vec![
+                "Unused luat-ignore-next-line - no unused-import diagnostic to suppress",
+                "Unknown rule 'no-such-rule' in luat-ignore-next-line",
+                "luat-enable without a matching luat-disable",
+            ]
+        );
+    }
+}
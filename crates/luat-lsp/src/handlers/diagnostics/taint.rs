@@ -0,0 +1,563 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Data flow from `props` into places where a value can run script or
+//! inject markup: `{@html}`, URL attributes, `style`, inline event handlers
+//! and attribute spreads.
+//!
+//! A value is tainted when it reads `props` or a local that holds a tainted
+//! value. Field access, concatenation, `and`/`or`, table constructors and
+//! calls keep the taint; arithmetic, comparisons and calls to a sanitizer
+//! drop it. Function parameters and return values aren't followed.
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+};
+
+use super::rules::{self, UNSAFE_ATTRIBUTE, UNSAFE_HTML};
+use crate::document::Document;
+use crate::lua::ast::{BinaryOp, Block, Expr, ExprKind, Field, Stat, StatKind};
+use crate::lua::virtual_document::split_each;
+use crate::lua::ScopeModel;
+use crate::syntax::{AttributeKind, AttributeValue, Element, Node, Span, ValuePart};
+
+/// Attributes holding a URL, where `javascript:` runs script
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "action", "formaction", "xlink:href"];
+
+/// Report tainted values reaching a sink, with the path from `props` as
+/// related information. Calls to any of `sanitizers`, by name or dotted
+/// path like `html.escape`, clean a value.
+pub fn check_taint(doc: &Document, sanitizers: &[String]) -> Vec<Diagnostic> {
+    let text = doc.text();
+    let model = doc.lua();
+    let mut symbols = HashMap::new();
+    for (i, symbol) in model.symbols.iter().enumerate() {
+        for span in std::iter::once(&symbol.span).chain(&symbol.references) {
+            symbols.entry(*span).or_insert(i);
+        }
+    }
+    let mut analysis = Analysis {
+        text,
+        model,
+        sanitizers,
+        symbols,
+        origins: vec![None; model.symbols.len()],
+        assignments: Vec::new(),
+        sinks: Vec::new(),
+    };
+    for block in &model.scripts {
+        analysis.block(block);
+    }
+    analysis.template_nodes(&doc.tree().nodes);
+    analysis.propagate();
+
+    let mut diagnostics = Vec::new();
+    for sink in &analysis.sinks {
+        // The first tainted expression; one diagnostic per sink is enough
+        let Some((expr, source)) = sink
+            .exprs
+            .iter()
+            .find_map(|expr| Some((expr, analysis.taint(expr)?)))
+        else {
+            continue;
+        };
+        let related = analysis
+            .path(source)
+            .into_iter()
+            .map(|(span, message)| DiagnosticRelatedInformation {
+                location: Location {
+                    uri: doc.uri().clone(),
+                    range: doc.span_to_range(span),
+                },
+                message,
+            })
+            .collect();
+        diagnostics.push(Diagnostic {
+            range: doc.span_to_range(expr.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(match sink.kind {
+                SinkKind::Html => UNSAFE_HTML,
+                _ => UNSAFE_ATTRIBUTE,
+            }),
+            message: sink.kind.message(),
+            source: Some("luat".to_string()),
+            related_information: Some(related),
+            ..Default::default()
+        });
+    }
+    diagnostics
+}
+
+enum SinkKind {
+    Html,
+    Url(String),
+    Style,
+    Handler(String),
+    Spread,
+}
+
+impl SinkKind {
+    fn message(&self) -> String {
+        match self {
+            SinkKind::Html => "Unsanitized value from props rendered with {@html} - this allows cross-site scripting; pass it through a sanitizer".to_string(),
+            SinkKind::Url(name) => format!(
+                "Unsanitized value from props in '{}' - a javascript: URL would run script; pass it through a sanitizer",
+                name
+            ),
+            SinkKind::Style => "Unsanitized value from props in 'style' - this allows CSS injection; pass it through a sanitizer".to_string(),
+            SinkKind::Handler(name) => format!(
+                "Unsanitized value from props in event handler '{}' - it runs as script; pass it through a sanitizer",
+                name
+            ),
+            SinkKind::Spread => "Attributes spread from props may set event handlers or javascript: URLs - pass only the attributes you need".to_string(),
+        }
+    }
+}
+
+/// A directive or attribute with the expressions whose values reach it
+struct Sink<'a> {
+    kind: SinkKind,
+    exprs: Vec<&'a Expr>,
+}
+
+/// Where a tainted value comes from
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    /// A read of `props`
+    Props(Span),
+    /// A read of a tainted local
+    Symbol(usize),
+}
+
+/// How a local became tainted
+#[derive(Debug, Clone, Copy)]
+struct Origin {
+    source: Source,
+    /// The name being assigned
+    at: Span,
+}
+
+struct Analysis<'a> {
+    text: &'a str,
+    model: &'a ScopeModel,
+    sanitizers: &'a [String],
+    /// The symbol each declaration and reference belongs to
+    symbols: HashMap<Span, usize>,
+    /// Per symbol, how it became tainted
+    origins: Vec<Option<Origin>>,
+    /// Symbol, the name assigned and the value assigned to it
+    assignments: Vec<(usize, Span, &'a Expr)>,
+    sinks: Vec<Sink<'a>>,
+}
+
+impl<'a> Analysis<'a> {
+    /// The symbol a name at `span` refers to
+    fn symbol(&self, span: Span) -> Option<usize> {
+        self.symbols.get(&span).copied()
+    }
+
+    fn assign(&mut self, name: Span, value: &'a Expr) {
+        if let Some(symbol) = self.symbol(name) {
+            self.assignments.push((symbol, name, value));
+        }
+    }
+
+    // ---- collecting assignments and sinks ---------------------------------
+
+    fn block(&mut self, block: &'a Block) {
+        for stat in &block.stats {
+            self.stat(stat);
+        }
+    }
+
+    fn stat(&mut self, stat: &'a Stat) {
+        match &stat.kind {
+            StatKind::Local { names, exprs, .. } => {
+                for (i, name) in names.iter().enumerate() {
+                    if let Some(value) = value_for(exprs, i) {
+                        self.assign(name.span, value);
+                    }
+                }
+                self.exprs(exprs);
+            }
+            StatKind::Assign { targets, exprs } => {
+                // `t.x = v` taints `t` as a whole
+                for (i, target) in targets.iter().enumerate() {
+                    if let (Some(root), Some(value)) = (root_name(target), value_for(exprs, i)) {
+                        self.assign(root, value);
+                    }
+                }
+                self.exprs(targets);
+                self.exprs(exprs);
+            }
+            StatKind::GenericFor {
+                names,
+                exprs,
+                block,
+            } => {
+                for name in names {
+                    for value in exprs {
+                        self.assign(name.span, value);
+                    }
+                }
+                self.exprs(exprs);
+                self.block(block);
+            }
+            StatKind::LocalFunction { body, .. } | StatKind::Function { body, .. } => {
+                self.block(&body.block)
+            }
+            StatKind::Call(expr) => self.expr(expr),
+            StatKind::Do(block) => self.block(block),
+            StatKind::While { cond, block } | StatKind::Repeat { block, cond } => {
+                self.expr(cond);
+                self.block(block);
+            }
+            StatKind::If {
+                clauses,
+                else_block,
+            } => {
+                for (cond, block) in clauses {
+                    self.expr(cond);
+                    self.block(block);
+                }
+                if let Some(block) = else_block {
+                    self.block(block);
+                }
+            }
+            StatKind::NumericFor { block, .. } => self.block(block),
+            StatKind::Return(exprs) => self.exprs(exprs),
+            StatKind::Break | StatKind::Goto(_) | StatKind::Label(_) | StatKind::Empty => {}
+        }
+    }
+
+    fn exprs(&mut self, exprs: &'a [Expr]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    /// Assignments inside function expressions
+    fn expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Function(body) => self.block(&body.block),
+            ExprKind::Table(fields) => {
+                for field in fields {
+                    match field {
+                        Field::Named(_, value) | Field::Positional(value) => self.expr(value),
+                        Field::Indexed(key, value) => {
+                            self.expr(key);
+                            self.expr(value);
+                        }
+                    }
+                }
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Paren(expr) => self.expr(expr),
+            ExprKind::Index { obj, key } => {
+                self.expr(obj);
+                self.expr(key);
+            }
+            ExprKind::Field { obj, .. } => self.expr(obj),
+            ExprKind::Call { callee: obj, args } | ExprKind::MethodCall { obj, args, .. } => {
+                self.expr(obj);
+                self.exprs(args);
+            }
+            _ => {}
+        }
+    }
+
+    /// The parsed expression at `span`; syntax errors are reported with the
+    /// other Lua diagnostics
+    fn parse(&self, span: Span) -> Option<&'a Expr> {
+        self.model.expressions.get(&span)
+    }
+
+    fn template_nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => {
+                    if !element.is_component() {
+                        self.attributes(element);
+                    }
+                    self.template_nodes(&element.children);
+                }
+                Node::Block(block) => {
+                    let open = &block.branches[0].tag;
+                    if let (Some(each), true) = (open.expression, block.keyword == "each") {
+                        if let Some((iterable, names)) = split_each(self.text, each) {
+                            // The item holds the iterable's values; the index doesn't
+                            if let (Some(value), Some(item)) = (self.parse(iterable), names.first())
+                            {
+                                self.assign(*item, value);
+                            }
+                        }
+                    }
+                    for branch in &block.branches {
+                        self.template_nodes(&branch.children);
+                    }
+                }
+                Node::Directive(tag) if tag.closed => {
+                    match (tag.keyword.as_str(), tag.expression) {
+                        ("html", Some(span)) => {
+                            if let Some(expr) = self.parse(span) {
+                                self.sinks.push(Sink {
+                                    kind: SinkKind::Html,
+                                    exprs: vec![expr],
+                                });
+                            }
+                        }
+                        ("local", Some(span)) => self.local(span),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// `{@local a, b = x, y}`
+    fn local(&mut self, expression: Span) {
+        let Some(local) = self.model.template_locals.get(&expression) else {
+            return;
+        };
+        for (i, name) in local.names.iter().enumerate() {
+            if let Some(value) = value_for(&local.values, i) {
+                self.assign(*name, value);
+            }
+        }
+    }
+
+    fn attributes(&mut self, element: &Element) {
+        for attribute in &element.attributes {
+            let Some(value) = &attribute.value else {
+                continue;
+            };
+            let name = attribute.name.to_ascii_lowercase();
+            let (kind, spans): (SinkKind, Vec<Span>) = if attribute.kind == AttributeKind::Spread {
+                let spans = value.expressions().iter().map(|e| e.content).collect();
+                (SinkKind::Spread, spans)
+            } else if URL_ATTRIBUTES.contains(&name.as_str()) {
+                // Only a value at the very start can choose the URL scheme
+                let leading = match value {
+                    AttributeValue::Expression(expr) => Some(expr),
+                    AttributeValue::Interpolated { parts, .. } => match parts.first() {
+                        Some(ValuePart::Expression(expr)) => Some(expr),
+                        _ => None,
+                    },
+                    AttributeValue::Text { .. } => None,
+                };
+                let spans = leading.map(|expr| expr.content).into_iter().collect();
+                (SinkKind::Url(attribute.name.clone()), spans)
+            } else if name == "style" {
+                let spans = value.expressions().iter().map(|e| e.content).collect();
+                (SinkKind::Style, spans)
+            } else if name.starts_with("on") {
+                let spans = value.expressions().iter().map(|e| e.content).collect();
+                (SinkKind::Handler(attribute.name.clone()), spans)
+            } else {
+                continue;
+            };
+
+            let exprs: Vec<&Expr> = spans
+                .into_iter()
+                .filter_map(|span| self.parse(span))
+                .collect();
+            if !exprs.is_empty() {
+                self.sinks.push(Sink { kind, exprs });
+            }
+        }
+    }
+
+    // ---- propagation -----------------------------------------------------
+
+    /// Taint locals assigned from tainted values until nothing changes
+    fn propagate(&mut self) {
+        loop {
+            let mut changed = false;
+            for i in 0..self.assignments.len() {
+                let (symbol, at, value) = self.assignments[i];
+                if self.origins[symbol].is_some() {
+                    continue;
+                }
+                if let Some(source) = self.taint(value) {
+                    self.origins[symbol] = Some(Origin { source, at });
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// Where the expression's value gets its taint from, if it's tainted
+    fn taint(&self, expr: &Expr) -> Option<Source> {
+        match &expr.kind {
+            ExprKind::Name(name) => match self.symbol(expr.span) {
+                Some(symbol) => self.origins[symbol].map(|_| Source::Symbol(symbol)),
+                None => (name == "props").then_some(Source::Props(expr.span)),
+            },
+            ExprKind::Field { obj, .. } | ExprKind::Index { obj, .. } => self.taint(obj),
+            ExprKind::Paren(inner) => self.taint(inner),
+            ExprKind::Call { callee, args } => {
+                if dotted_path(callee).is_some_and(|path| self.sanitizers.contains(&path)) {
+                    return None;
+                }
+                self.taint(callee)
+                    .or_else(|| args.iter().find_map(|arg| self.taint(arg)))
+            }
+            ExprKind::MethodCall { obj, args, .. } => self
+                .taint(obj)
+                .or_else(|| args.iter().find_map(|arg| self.taint(arg))),
+            ExprKind::Binary {
+                op: BinaryOp::Concat | BinaryOp::And | BinaryOp::Or,
+                lhs,
+                rhs,
+            } => self.taint(lhs).or_else(|| self.taint(rhs)),
+            ExprKind::Table(fields) => fields.iter().find_map(|field| match field {
+                Field::Named(_, value) | Field::Indexed(_, value) | Field::Positional(value) => {
+                    self.taint(value)
+                }
+            }),
+            _ => None,
+        }
+    }
+
+    /// The steps from `props` to the sink, source first
+    fn path(&self, mut source: Source) -> Vec<(Span, String)> {
+        let mut steps = Vec::new();
+        loop {
+            match source {
+                Source::Props(span) => {
+                    steps.push((span, "Value comes from props here".to_string()));
+                    break;
+                }
+                Source::Symbol(symbol) => {
+                    // Origins only point at symbols tainted before them
+                    let Some(origin) = self.origins[symbol] else {
+                        break;
+                    };
+                    let name = &self.model.symbols[symbol].name;
+                    steps.push((origin.at, format!("Flows into '{}' here", name)));
+                    source = origin.source;
+                }
+            }
+        }
+        steps.reverse();
+        steps
+    }
+}
+
+/// The value assigned to the `i`th name: the matching expression, or the
+/// trailing call's extra results
+fn value_for(exprs: &[Expr], i: usize) -> Option<&Expr> {
+    exprs.get(i).or_else(|| {
+        exprs.last().filter(|last| {
+            matches!(
+                last.kind,
+                ExprKind::Call { .. } | ExprKind::MethodCall { .. }
+            )
+        })
+    })
+}
+
+/// `t` in `t`, `t.x` or `t[k].y`
+fn root_name(expr: &Expr) -> Option<Span> {
+    match &expr.kind {
+        ExprKind::Name(_) => Some(expr.span),
+        ExprKind::Field { obj, .. } | ExprKind::Index { obj, .. } => root_name(obj),
+        _ => None,
+    }
+}
+
+/// `html.escape` for a callee like `html.escape`
+fn dotted_path(expr: &Expr) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Name(name) => Some(name.clone()),
+        ExprKind::Field { obj, name } => Some(format!("{}.{}", dotted_path(obj)?, name.name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+
+    fn check(text: &str, sanitizers: &[&str]) -> Vec<Diagnostic> {
+        let sanitizers: Vec<String> = sanitizers.iter().map(|s| s.to_string()).collect();
+        check_taint(&test_document(text), &sanitizers)
+    }
+
+    #[test]
+    fn test_flows_into_sinks() {
+        let text = "<script>\n\
+            local user = props.user\n\
+            local bio = \"<p>\" .. user.bio .. \"</p>\"\n\
+            local count = #props.items\n\
+            </script>\n\
+            {@html bio}{@html count}{@html \"<hr>\"}\n\
+            <a href={user.website}>x</a><a href=\"/users/{user.id}\">y</a>\n\
+            <div style=\"color: {props.color}\" onclick=\"{props.action}\" {...props}></div>\n\
+            <Card href={user.website} />";
+        let diagnostics = check(text, &[]);
+        let lines: Vec<u32> = diagnostics.iter().map(|d| d.range.start.line).collect();
+        assert_eq!(lines, vec![5, 6, 7, 7, 7]);
+        assert!(diagnostics[1]
+            .message
+            .starts_with("Unsanitized value from props in 'href'"));
+
+        // The path runs from props through every local
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        let steps: Vec<_> = related
+            .iter()
+            .map(|r| (r.location.range.start.line, r.message.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (1, "Value comes from props here"),
+                (1, "Flows into 'user' here"),
+                (2, "Flows into 'bio' here"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tainted_expression_after_a_clean_one() {
+        let text = "<script>\nlocal theme = \"dark\"\n</script>\n\
+            <div style=\"color: {theme}; background: {props.bg}\"></div>\n\
+            <button onclick=\"save({theme}, {props.id})\"></button>";
+        let diagnostics = check(text, &[]);
+        let ranges: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.range.start.line, d.range.start.character))
+            .collect();
+        assert_eq!(ranges, vec![(3, 41), (4, 32)]);
+    }
+
+    #[test]
+    fn test_sanitizers_and_template_locals() {
+        let text =
+            "<script>\nlocal safe = html.escape(props.bio)\nlocal raw = props.bio\n</script>\n\
+            {@html safe}{@html sanitize(raw)}\n\
+            {#each props.posts as post}{@local body = post.body}{@html body}{/each}";
+        let diagnostics = check(text, &["html.escape", "sanitize"]);
+        assert_eq!(diagnostics.len(), 1);
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        let messages: Vec<_> = related.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Value comes from props here",
+                "Flows into 'post' here",
+                "Flows into 'body' here",
+            ]
+        );
+    }
+}
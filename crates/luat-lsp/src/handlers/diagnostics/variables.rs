@@ -0,0 +1,215 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Variables used in the markup that aren't defined anywhere, and locals
+//! that are never used
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag};
+
+use super::rules::{self, UNDEFINED_VARIABLE, UNUSED_IMPORT, UNUSED_VARIABLE};
+use crate::document::Document;
+use crate::lua::globals::{is_known_global, TEMPLATE_GLOBALS};
+use crate::lua::SymbolKind;
+
+/// Report names in expressions and block conditions that resolve to no
+/// script local, template local, script-defined global or known global
+pub fn check_undefined(doc: &Document) -> Vec<Diagnostic> {
+    let model = doc.lua();
+    let version = doc.lua_version();
+    let mut diagnostics = Vec::new();
+
+    for read in &model.global_reads {
+        // Scripts may use globals the runtime provides; only the markup is checked
+        if model.in_script(read.span.start)
+            || is_known_global(&read.name, version)
+            || model.global_writes.iter().any(|w| w.name == read.name)
+        {
+            continue;
+        }
+
+        let mut candidates: Vec<&str> = model
+            .visible_at(read.span.start)
+            .into_iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        candidates.extend(TEMPLATE_GLOBALS);
+        let message = match closest(&read.name, &candidates) {
+            Some(suggestion) => format!(
+                "Undefined variable '{}' - did you mean '{}'?",
+                read.name, suggestion
+            ),
+            None => format!("Undefined variable '{}'", read.name),
+        };
+
+        diagnostics.push(Diagnostic {
+            range: doc.span_to_range(read.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(UNDEFINED_VARIABLE),
+            message,
+            source: Some("luat".to_string()),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// Report imports, script locals, `{@local}` values and `{#each}` index
+/// variables that are never used. Names starting with `_` are exempt, as
+/// are parameters and `{#each}` items, which often exist only to fill a slot.
+pub fn check_unused(doc: &Document) -> Vec<Diagnostic> {
+    let model = doc.lua();
+    let mut diagnostics = Vec::new();
+
+    for symbol in &model.symbols {
+        if !symbol.references.is_empty() || symbol.name.starts_with('_') {
+            continue;
+        }
+        let in_script = model.in_script(symbol.span.start);
+        let code = if symbol.kind == SymbolKind::Import {
+            UNUSED_IMPORT
+        } else {
+            UNUSED_VARIABLE
+        };
+        let message = match symbol.kind {
+            SymbolKind::Parameter | SymbolKind::EachItem => continue,
+            SymbolKind::Import => format!("'{}' is imported but never used", symbol.name),
+            SymbolKind::EachIndex => format!("Index variable '{}' is never used", symbol.name),
+            SymbolKind::Local | SymbolKind::Function if !in_script => {
+                format!("{{@local}} value '{}' is never used", symbol.name)
+            }
+            SymbolKind::Function => format!("Function '{}' is never used", symbol.name),
+            SymbolKind::Local => format!("'{}' is declared but never used", symbol.name),
+        };
+
+        diagnostics.push(Diagnostic {
+            range: doc.span_to_range(symbol.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: rules::code(code),
+            message,
+            source: Some("luat".to_string()),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+/// The candidate closest to `name`, if it's a plausible typo
+pub(super) fn closest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = (name.len() / 3).clamp(1, 2);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, counting a swap of adjacent characters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_document;
+    use crate::handlers::diagnostics::test_support::messages;
+
+    #[test]
+    fn test_template_scopes() {
+        let text = r#"<script module>
+local function format(x) return x end
+</script>
+<script>
+local user = props.user
+function helper() end
+</script>
+<h1>{format(user.name)} {helper()} {tostring(props.count)}</h1>
+{#each user.items as item, i}
+  {@local label = item.title .. i}
+  <li title={label}>{label}</li>
+{:empty}
+  {item}
+{/each}
+{label}
+{usre.name}
+{@local late = 1}
+"#;
+        assert_eq!(
+            messages(check_undefined, text),
+            vec![
+                // Each bindings and `{@local}` inside the loop stay inside it
+                "Undefined variable 'item'",
+                "Undefined variable 'label'",
+                "Undefined variable 'usre' - did you mean 'user'?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused() {
+        let text = r#"<script>
+local Card = require("components/Card")
+local Badge = require("components/Badge")
+local unused, _ignored = 1, 2
+local function helper(arg) end
+for i, v in ipairs(props.items) do print(v) end
+</script>
+<Card />
+{#each props.items as item, index}<li />{/each}
+{@local label = "x"}
+"#;
+        let doc = test_document(text);
+        let diagnostics = check_unused(&doc);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "'Badge' is imported but never used",
+                "'unused' is declared but never used",
+                "Function 'helper' is never used",
+                "'i' is declared but never used",
+                "Index variable 'index' is never used",
+                "{@local} value 'label' is never used",
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.tags == Some(vec![DiagnosticTag::UNNECESSARY])));
+    }
+
+    #[test]
+    fn test_local_visible_after_declaration() {
+        assert_eq!(
+            messages(
+                check_undefined,
+                "<p>{title}</p>{@local title = 'x'}<p>{title}</p>"
+            ),
+            vec!["Undefined variable 'title'"]
+        );
+    }
+}
@@ -0,0 +1,30 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for templates and modules
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build"];
+
+/// Collect files under `dir` whose name `matches`, skipping hidden and
+/// build output directories
+pub fn find_files(dir: &Path, matches: &dyn Fn(&str) -> bool, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                find_files(&path, matches, files);
+            }
+        } else if matches(&name) {
+            files.push(path);
+        }
+    }
+}
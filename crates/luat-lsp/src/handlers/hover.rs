@@ -3,13 +3,21 @@
 
 use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
 
+use crate::css;
 use crate::document::Document;
+use crate::handlers::definition;
 use crate::regions::RegionType;
+use crate::scheduler::Cancellation;
+use crate::syntax::AttributeKind;
 
 /// Get hover information at a position
-pub fn get_hover(doc: &Document, position: Position) -> Option<Hover> {
+pub fn get_hover(doc: &Document, position: Position, cancellation: &Cancellation) -> Option<Hover> {
     // Check if we're in a special region
-    if let Some(region) = doc.region_at_position(position) {
+    let region = doc.region_at_position(position);
+    if cancellation.is_cancelled() {
+        return None;
+    }
+    if let Some(region) = region {
         match region.region_type {
             RegionType::ControlFlow => {
                 return get_control_flow_hover(region.content.as_deref()?);
@@ -17,9 +25,35 @@ pub fn get_hover(doc: &Document, position: Position) -> Option<Hover> {
             RegionType::Directive => {
                 return get_directive_hover(region.content.as_deref()?);
             }
+            RegionType::AttributeExpression
+                if region
+                    .attribute
+                    .as_ref()
+                    .is_some_and(|a| a.kind == AttributeKind::Spread) =>
+            {
+                return Some(markdown_hover(
+                    "**Spread Attributes**\n\nPasses every field of the table as an attribute or prop.\n\n```luat\n<Card {...props} />\n```",
+                ));
+            }
+            RegionType::Style => {
+                let offset = doc.position_to_offset(position)?;
+                let sheet = region.content.as_deref()?;
+                return get_css_hover(sheet, offset.checked_sub(region.start)?);
+            }
+            // Lua keyword hovers make no sense in JavaScript or JSON
+            RegionType::ClientScript | RegionType::ScriptData => return None,
+            RegionType::StartTag | RegionType::AttributeExpression => {
+                // Reads the component's file
+                if let Some(hover) = get_prop_hover(doc, position) {
+                    return Some(hover);
+                }
+            }
             _ => {}
         }
     }
+    if cancellation.is_cancelled() {
+        return None;
+    }
 
     // Check for word at position
     let (word, _range) = doc.word_at_position(position)?;
@@ -28,6 +62,56 @@ pub fn get_hover(doc: &Document, position: Position) -> Option<Hover> {
     get_keyword_hover(&word)
 }
 
+/// Hover for an attribute name on a component, i.e. a prop
+fn get_prop_hover(doc: &Document, position: Position) -> Option<Hover> {
+    let offset = doc.position_to_offset(position)?;
+    let element = doc
+        .tree()
+        .element_at_start_tag(offset)
+        .filter(|e| e.is_component())?;
+    let attribute = element.attributes.iter().find(|a| {
+        a.kind != AttributeKind::Spread && a.name_span.start <= offset && offset <= a.name_span.end
+    })?;
+
+    let mut hover_text = format!("**{}**\n\nProp of `<{}>`", attribute.name, element.name);
+    if attribute.kind == AttributeKind::Shorthand {
+        hover_text.push_str(&format!(", shorthand for `{0}={{{0}}}`", attribute.name));
+    }
+    let props = definition::component_props(&element.name, doc);
+    if !props.is_empty() && !props.contains(&attribute.name) {
+        hover_text.push_str(&format!(
+            "\n\n`{}` never reads `props.{}`",
+            element.name, attribute.name
+        ));
+    }
+
+    Some(markdown_hover(&hover_text))
+}
+
+/// Hover for a CSS property name
+fn get_css_hover(sheet: &str, offset: usize) -> Option<Hover> {
+    let (_, name) = css::property_at(sheet, offset)?;
+    let property = css::property(name)?;
+
+    let mut hover_text = format!("**{}**\n\n{}", property.name, property.description);
+    if !property.values.is_empty() {
+        let values: Vec<_> = property.values.iter().map(|v| format!("`{}`", v)).collect();
+        hover_text.push_str(&format!("\n\nValues: {}", values.join(" | ")));
+    }
+
+    Some(markdown_hover(&hover_text))
+}
+
+fn markdown_hover(value: &str) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: value.to_string(),
+        }),
+        range: None,
+    }
+}
+
 fn get_control_flow_hover(content: &str) -> Option<Hover> {
     let hover_text = if content.starts_with("{#if") {
         "**Conditional Block**\n\nRenders content only if the condition is truthy.\n\n```luat\n{#if condition}\n  <p>Shown when true</p>\n{:else}\n  <p>Shown when false</p>\n{/if}\n```"
@@ -1,8 +1,10 @@
 // Copyright 2026 Maravilla Labs
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+pub mod code_action;
 pub mod completion;
 pub mod definition;
 pub mod diagnostics;
+pub mod files;
 pub mod hover;
 pub mod symbols;
@@ -1,112 +1,78 @@
 // Copyright 2026 Maravilla Labs
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use regex::Regex;
-use std::sync::LazyLock;
-use tower_lsp::lsp_types::{DocumentSymbol, DocumentSymbolResponse, Position, Range, SymbolKind};
+use tower_lsp::lsp_types::{DocumentSymbol, DocumentSymbolResponse, SymbolKind};
 
 use crate::document::Document;
-
-static FUNCTION_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(?m)^[\t ]*(?:local\s+)?function\s+(\w+)").unwrap());
-
-static COMPONENT_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"local\s+(\w+)\s*=\s*require\s*\("#).unwrap());
+use crate::lua::SymbolKind as LuaSymbolKind;
+use crate::syntax::{is_component_name, Span};
 
 /// Get document symbols for outline view
 #[allow(deprecated)]
 pub fn get_document_symbols(doc: &Document) -> Option<DocumentSymbolResponse> {
     let mut symbols = Vec::new();
-    let text = doc.text();
-
-    // Find script regions and extract symbols from them
-    if let Some(regions) = doc.regions() {
-        for region in regions.scripts() {
-            if let Some(content) = &region.content {
-                // Find functions
-                for cap in FUNCTION_RE.captures_iter(content) {
-                    if let Some(name_match) = cap.get(1) {
-                        let name = name_match.as_str().to_string();
-                        let offset_in_content = name_match.start();
+    let model = doc.lua();
 
-                        // Calculate position relative to document
-                        let abs_offset = region.start + offset_in_content + "<script>".len();
-                        let pos = doc.offset_to_position(abs_offset);
-
-                        symbols.push(DocumentSymbol {
-                            name: name.clone(),
-                            detail: Some("function".to_string()),
-                            kind: SymbolKind::FUNCTION,
-                            range: Range {
-                                start: pos,
-                                end: Position {
-                                    line: pos.line,
-                                    character: pos.character + name.len() as u32,
-                                },
-                            },
-                            selection_range: Range {
-                                start: pos,
-                                end: Position {
-                                    line: pos.line,
-                                    character: pos.character + name.len() as u32,
-                                },
-                            },
-                            tags: None,
-                            deprecated: None,
-                            children: None,
-                        });
-                    }
-                }
-
-                // Find component imports
-                for cap in COMPONENT_RE.captures_iter(content) {
-                    if let Some(name_match) = cap.get(1) {
-                        let name = name_match.as_str().to_string();
-                        if name
-                            .chars()
-                            .next()
-                            .map(|c| c.is_uppercase())
-                            .unwrap_or(false)
-                        {
-                            let offset_in_content = name_match.start();
-                            let abs_offset = region.start + offset_in_content + "<script>".len();
-                            let pos = doc.offset_to_position(abs_offset);
+    let symbol = |name: &str, detail: &str, kind: SymbolKind, span: Span| {
+        let range = doc.span_to_range(span);
+        DocumentSymbol {
+            name: name.to_string(),
+            detail: Some(detail.to_string()),
+            kind,
+            range,
+            selection_range: range,
+            tags: None,
+            deprecated: None,
+            children: None,
+        }
+    };
 
-                            symbols.push(DocumentSymbol {
-                                name: name.clone(),
-                                detail: Some("component".to_string()),
-                                kind: SymbolKind::CLASS,
-                                range: Range {
-                                    start: pos,
-                                    end: Position {
-                                        line: pos.line,
-                                        character: pos.character + name.len() as u32,
-                                    },
-                                },
-                                selection_range: Range {
-                                    start: pos,
-                                    end: Position {
-                                        line: pos.line,
-                                        character: pos.character + name.len() as u32,
-                                    },
-                                },
-                                tags: None,
-                                deprecated: None,
-                                children: None,
-                            });
-                        }
-                    }
+    // Functions and imports declared in the Lua scripts
+    for local in &model.symbols {
+        match local.kind {
+            LuaSymbolKind::Function => {
+                symbols.push(symbol(
+                    &local.name,
+                    "function",
+                    SymbolKind::FUNCTION,
+                    local.span,
+                ));
+            }
+            LuaSymbolKind::Import if local.scope == 0 => {
+                if is_component_name(&local.name) {
+                    symbols.push(symbol(
+                        &local.name,
+                        "component",
+                        SymbolKind::CLASS,
+                        local.span,
+                    ));
+                } else {
+                    symbols.push(symbol(
+                        &local.name,
+                        "module",
+                        SymbolKind::MODULE,
+                        local.span,
+                    ));
                 }
             }
+            _ => {}
         }
     }
+    for name in &model.global_functions {
+        symbols.push(symbol(
+            &name.name,
+            "function",
+            SymbolKind::FUNCTION,
+            name.span,
+        ));
+    }
+    symbols.sort_by_key(|s| (s.range.start.line, s.range.start.character));
 
     // Find component usages in template
-    let component_re = Regex::new(r"<([A-Z][A-Za-z0-9_]*)").unwrap();
-    for cap in component_re.captures_iter(&text) {
-        if let (Some(full), Some(name_match)) = (cap.get(0), cap.get(1)) {
-            let name = name_match.as_str().to_string();
-            let pos = doc.offset_to_position(full.start());
+    for element in doc.tree().elements() {
+        if element.is_component() {
+            let name = element.name.clone();
+            let range = doc.span_to_range(Span::new(element.span.start, element.name_span.end));
 
             // Check if already added as import
             if !symbols
@@ -117,20 +83,8 @@ pub fn get_document_symbols(doc: &Document) -> Option<DocumentSymbolResponse> {
                     name: format!("<{}>", name),
                     detail: Some("component usage".to_string()),
                     kind: SymbolKind::OBJECT,
-                    range: Range {
-                        start: pos,
-                        end: Position {
-                            line: pos.line,
-                            character: pos.character + name.len() as u32 + 1,
-                        },
-                    },
-                    selection_range: Range {
-                        start: pos,
-                        end: Position {
-                            line: pos.line,
-                            character: pos.character + name.len() as u32 + 1,
-                        },
-                    },
+                    range,
+                    selection_range: range,
                     tags: None,
                     deprecated: None,
                     children: None,
@@ -0,0 +1,419 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Syntax tree for Lua code.
+//!
+//! Spans are byte offsets into the source the code was parsed from, which
+//! for script blocks and expressions is the whole template.
+
+use crate::syntax::Span;
+
+/// An identifier and where it appears
+#[derive(Debug, Clone, PartialEq)]
+pub struct Name {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Block {
+    pub stats: Vec<Stat>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stat {
+    pub kind: StatKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatKind {
+    /// `local a <const>, b = 1, 2`
+    Local {
+        names: Vec<Name>,
+        /// Lua 5.4 attributes (`const`, `close`), one per name
+        attribs: Vec<Option<Name>>,
+        exprs: Vec<Expr>,
+    },
+    /// `local function f() end`
+    LocalFunction {
+        name: Name,
+        body: FunctionBody,
+    },
+    /// `function a.b:c() end`
+    Function {
+        /// `a`, `b`, `c` in `a.b:c`
+        path: Vec<Name>,
+        is_method: bool,
+        body: FunctionBody,
+    },
+    /// `a, b.c = 1, 2`
+    Assign {
+        targets: Vec<Expr>,
+        exprs: Vec<Expr>,
+    },
+    /// A function call used as a statement
+    Call(Expr),
+    Do(Block),
+    While {
+        cond: Expr,
+        block: Block,
+    },
+    Repeat {
+        block: Block,
+        cond: Expr,
+    },
+    If {
+        /// `if` and `elseif` conditions with their blocks
+        clauses: Vec<(Expr, Block)>,
+        else_block: Option<Block>,
+    },
+    NumericFor {
+        var: Name,
+        start: Box<Expr>,
+        end: Box<Expr>,
+        step: Option<Box<Expr>>,
+        block: Block,
+    },
+    GenericFor {
+        names: Vec<Name>,
+        exprs: Vec<Expr>,
+        block: Block,
+    },
+    Return(Vec<Expr>),
+    Break,
+    Goto(Name),
+    Label(Name),
+    /// A lone `;`
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionBody {
+    pub params: Vec<Name>,
+    pub vararg: bool,
+    pub block: Block,
+    /// From the opening `(` through `end`
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Nil,
+    True,
+    False,
+    Number,
+    /// Any string literal; see [`string_value`] for its contents
+    String,
+    /// `...`
+    Vararg,
+    Function(Box<FunctionBody>),
+    Table(Vec<Field>),
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr>,
+    },
+    Paren(Box<Expr>),
+    Name(String),
+    /// `obj[key]`
+    Index {
+        obj: Box<Expr>,
+        key: Box<Expr>,
+    },
+    /// `obj.name`
+    Field {
+        obj: Box<Expr>,
+        name: Name,
+    },
+    /// `callee(args)`, `callee "str"`, `callee {table}`
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    /// `obj:method(args)`
+    MethodCall {
+        obj: Box<Expr>,
+        method: Name,
+        args: Vec<Expr>,
+    },
+    /// Placeholder for an expression that failed to parse
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    /// `name = value`
+    Named(Name, Expr),
+    /// `[key] = value`
+    Indexed(Expr, Expr),
+    /// `value`
+    Positional(Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    IntDiv,
+    Mod,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    Len,
+    BitNot,
+}
+
+impl Expr {
+    /// The name if this is a plain variable reference
+    pub fn as_name(&self) -> Option<&str> {
+        match &self.kind {
+            ExprKind::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// If this is a `require("path")` call, the path literal
+    pub fn as_require(&self) -> Option<&Expr> {
+        match &self.kind {
+            ExprKind::Call { callee, args } if callee.as_name() == Some("require") => {
+                args.first().filter(|arg| arg.kind == ExprKind::String)
+            }
+            ExprKind::Paren(inner) => inner.as_require(),
+            _ => None,
+        }
+    }
+}
+
+// ---- span shifting -------------------------------------------------------
+//
+// After an edit outside the Lua code, move every position at or after
+// `from` by `delta`
+
+impl Name {
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        self.span.shift(from, delta);
+    }
+}
+
+impl Block {
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        self.span.shift(from, delta);
+        for stat in &mut self.stats {
+            stat.shift(from, delta);
+        }
+    }
+}
+
+impl FunctionBody {
+    fn shift(&mut self, from: usize, delta: isize) {
+        self.span.shift(from, delta);
+        self.block.shift(from, delta);
+        for param in &mut self.params {
+            param.shift(from, delta);
+        }
+    }
+}
+
+impl Stat {
+    fn shift(&mut self, from: usize, delta: isize) {
+        if self.span.end < from {
+            return;
+        }
+        self.span.shift(from, delta);
+        let shift_all = |exprs: &mut Vec<Expr>| {
+            for expr in exprs {
+                expr.shift(from, delta);
+            }
+        };
+        match &mut self.kind {
+            StatKind::Local {
+                names,
+                attribs,
+                exprs,
+            } => {
+                for name in names.iter_mut().chain(attribs.iter_mut().flatten()) {
+                    name.shift(from, delta);
+                }
+                shift_all(exprs);
+            }
+            StatKind::LocalFunction { name, body } => {
+                name.shift(from, delta);
+                body.shift(from, delta);
+            }
+            StatKind::Function { path, body, .. } => {
+                for name in path {
+                    name.shift(from, delta);
+                }
+                body.shift(from, delta);
+            }
+            StatKind::Assign { targets, exprs } => {
+                shift_all(targets);
+                shift_all(exprs);
+            }
+            StatKind::Call(expr) => expr.shift(from, delta),
+            StatKind::Do(block) => block.shift(from, delta),
+            StatKind::While { cond, block } | StatKind::Repeat { block, cond } => {
+                cond.shift(from, delta);
+                block.shift(from, delta);
+            }
+            StatKind::If {
+                clauses,
+                else_block,
+            } => {
+                for (cond, block) in clauses {
+                    cond.shift(from, delta);
+                    block.shift(from, delta);
+                }
+                if let Some(block) = else_block {
+                    block.shift(from, delta);
+                }
+            }
+            StatKind::NumericFor {
+                var,
+                start,
+                end,
+                step,
+                block,
+            } => {
+                var.shift(from, delta);
+                start.shift(from, delta);
+                end.shift(from, delta);
+                if let Some(step) = step {
+                    step.shift(from, delta);
+                }
+                block.shift(from, delta);
+            }
+            StatKind::GenericFor {
+                names,
+                exprs,
+                block,
+            } => {
+                for name in names {
+                    name.shift(from, delta);
+                }
+                shift_all(exprs);
+                block.shift(from, delta);
+            }
+            StatKind::Return(exprs) => shift_all(exprs),
+            StatKind::Goto(name) | StatKind::Label(name) => name.shift(from, delta),
+            StatKind::Break | StatKind::Empty => {}
+        }
+    }
+}
+
+impl Expr {
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        if self.span.end < from {
+            return;
+        }
+        self.span.shift(from, delta);
+        match &mut self.kind {
+            ExprKind::Function(body) => body.shift(from, delta),
+            ExprKind::Table(fields) => {
+                for field in fields {
+                    match field {
+                        Field::Named(name, value) => {
+                            name.shift(from, delta);
+                            value.shift(from, delta);
+                        }
+                        Field::Indexed(key, value) => {
+                            key.shift(from, delta);
+                            value.shift(from, delta);
+                        }
+                        Field::Positional(value) => value.shift(from, delta),
+                    }
+                }
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                lhs.shift(from, delta);
+                rhs.shift(from, delta);
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Paren(expr) => expr.shift(from, delta),
+            ExprKind::Index { obj, key } => {
+                obj.shift(from, delta);
+                key.shift(from, delta);
+            }
+            ExprKind::Field { obj, name } => {
+                obj.shift(from, delta);
+                name.shift(from, delta);
+            }
+            ExprKind::Call { callee, args } => {
+                callee.shift(from, delta);
+                for arg in args {
+                    arg.shift(from, delta);
+                }
+            }
+            ExprKind::MethodCall { obj, method, args } => {
+                obj.shift(from, delta);
+                method.shift(from, delta);
+                for arg in args {
+                    arg.shift(from, delta);
+                }
+            }
+            ExprKind::Nil
+            | ExprKind::True
+            | ExprKind::False
+            | ExprKind::Number
+            | ExprKind::String
+            | ExprKind::Vararg
+            | ExprKind::Name(_)
+            | ExprKind::Error => {}
+        }
+    }
+}
+
+/// The contents of a string literal: the text between the quotes or long
+/// brackets. Escape sequences are left as written.
+pub fn string_value(src: &str, span: Span) -> &str {
+    let text = span.text(src);
+    if let Some(rest) = text.strip_prefix('[') {
+        let level = rest.bytes().take_while(|b| *b == b'=').count();
+        let open = level + 2;
+        let close = if text.ends_with(']') && text.len() >= open * 2 {
+            open
+        } else {
+            0
+        };
+        let inner = &text[open.min(text.len())..text.len() - close];
+        // A newline right after the opening bracket is skipped
+        return inner
+            .strip_prefix("\r\n")
+            .or_else(|| inner.strip_prefix('\n'))
+            .unwrap_or(inner);
+    }
+    let quote = &text[..1.min(text.len())];
+    let inner = &text[quote.len()..];
+    inner.strip_suffix(quote).unwrap_or(inner)
+}
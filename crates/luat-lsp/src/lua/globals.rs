@@ -0,0 +1,64 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Names that are defined without a declaration
+
+use super::parser::LuaVersion;
+
+/// Standard library globals of every supported Lua version
+const LUA_GLOBALS: &[&str] = &[
+    "_G",
+    "_VERSION",
+    "assert",
+    "collectgarbage",
+    "coroutine",
+    "debug",
+    "dofile",
+    "error",
+    "getmetatable",
+    "io",
+    "ipairs",
+    "load",
+    "loadfile",
+    "math",
+    "next",
+    "os",
+    "package",
+    "pairs",
+    "pcall",
+    "print",
+    "rawequal",
+    "rawget",
+    "rawset",
+    "require",
+    "select",
+    "setmetatable",
+    "string",
+    "table",
+    "tonumber",
+    "tostring",
+    "type",
+    "xpcall",
+];
+
+/// Globals removed after Lua 5.1
+const LUA51_GLOBALS: &[&str] = &["getfenv", "loadstring", "module", "setfenv", "unpack"];
+
+/// Extensions LuaJIT loads by default
+const LUAJIT_GLOBALS: &[&str] = &["bit", "jit"];
+
+/// Globals added after Lua 5.1
+const LUA54_GLOBALS: &[&str] = &["rawlen", "utf8", "warn"];
+
+/// Names every template can use
+pub const TEMPLATE_GLOBALS: &[&str] = &["props"];
+
+/// Whether a name is a standard global of the Lua version or a template global
+pub fn is_known_global(name: &str, version: LuaVersion) -> bool {
+    let versioned = match version {
+        LuaVersion::Lua51 => LUA51_GLOBALS.contains(&name),
+        LuaVersion::LuaJit => LUA51_GLOBALS.contains(&name) || LUAJIT_GLOBALS.contains(&name),
+        LuaVersion::Lua54 => LUA54_GLOBALS.contains(&name),
+    };
+    LUA_GLOBALS.contains(&name) || versioned || TEMPLATE_GLOBALS.contains(&name)
+}
@@ -0,0 +1,319 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Tokenizer for Lua source embedded in templates
+
+use crate::syntax::Span;
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Multi-character symbols, longest first so they win over their prefixes
+const SYMBOLS: &[&str] = &[
+    "...", "..", "==", "~=", "<=", ">=", "//", "::", "<<", ">>", "+", "-", "*", "/", "%", "^", "#",
+    "&", "~", "|", "<", ">", "=", "(", ")", "{", "}", "[", "]", ";", ":", ",", ".",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Name,
+    Keyword,
+    Number,
+    /// Quoted or long string
+    String,
+    /// Line or long comment
+    Comment,
+    /// Operator or punctuation
+    Symbol,
+    /// A character that cannot start any token
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte span relative to the lexed source
+    pub span: Span,
+}
+
+impl Token {
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        self.span.text(source)
+    }
+}
+
+/// A lexical error such as an unterminated string
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub span: Span,
+    pub message: String,
+}
+
+/// Splits Lua source into tokens, skipping whitespace
+pub struct Lexer<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    pub errors: Vec<LexError>,
+}
+
+impl<'a> Lexer<'a> {
+    #[cfg(test)]
+    pub fn new(src: &'a str) -> Self {
+        Self::at(src, 0)
+    }
+
+    /// Start lexing at a byte offset into `src`
+    pub fn at(src: &'a str, pos: usize) -> Self {
+        Self {
+            src,
+            bytes: src.as_bytes(),
+            pos,
+            errors: Vec::new(),
+        }
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn error(&mut self, start: usize, message: &str) {
+        self.errors.push(LexError {
+            span: Span::new(start, self.pos),
+            message: message.to_string(),
+        });
+    }
+
+    /// If a long bracket `[[`, `[=[`, ... starts at the current position,
+    /// return its level (number of `=`)
+    fn long_bracket_level(&self) -> Option<usize> {
+        if self.peek_at(0) != Some(b'[') {
+            return None;
+        }
+        let level = self.bytes[self.pos + 1..]
+            .iter()
+            .take_while(|b| **b == b'=')
+            .count();
+        (self.peek_at(level + 1) == Some(b'[')).then_some(level)
+    }
+
+    /// Consume a long bracket body; the current position is at the opening `[`
+    fn long_bracket(&mut self, level: usize, what: &str) {
+        let start = self.pos;
+        self.pos += level + 2;
+        let close = format!("]{}]", "=".repeat(level));
+        match self.src[self.pos..].find(&close) {
+            Some(i) => self.pos += i + close.len(),
+            None => {
+                self.pos = self.bytes.len();
+                self.error(start, &format!("Unfinished long {}", what));
+            }
+        }
+    }
+
+    fn quoted_string(&mut self, quote: u8) {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(b) = self.peek_at(0) {
+            match b {
+                b'\\' => {
+                    // `\z` skips following whitespace, including newlines
+                    if self.peek_at(1) == Some(b'z') {
+                        self.pos += 2;
+                        while self.peek_at(0).is_some_and(|b| b.is_ascii_whitespace()) {
+                            self.pos += 1;
+                        }
+                    } else {
+                        self.pos += 2;
+                    }
+                }
+                b'\n' => break,
+                _ if b == quote => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        self.pos = self.pos.min(self.bytes.len());
+        self.error(start, "Unfinished string");
+    }
+
+    fn number(&mut self) {
+        let start = self.pos;
+        let hex = self.peek_at(0) == Some(b'0') && matches!(self.peek_at(1), Some(b'x' | b'X'));
+        if hex {
+            self.pos += 2;
+        }
+        let exponent: &[u8] = if hex { b"pP" } else { b"eE" };
+        while let Some(b) = self.peek_at(0) {
+            if exponent.contains(&b) && matches!(self.peek_at(1), Some(b'+' | b'-')) {
+                self.pos += 2;
+            } else if b.is_ascii_alphanumeric() || b == b'.' || b == b'_' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        let text = &self.src[start..self.pos];
+        let valid = if hex {
+            text.len() > 2
+                && text[2..]
+                    .bytes()
+                    .all(|b| b.is_ascii_hexdigit() || matches!(b, b'.' | b'p' | b'P' | b'+' | b'-'))
+        } else {
+            text.parse::<f64>().is_ok()
+        };
+        if !valid {
+            self.error(start, "Malformed number");
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        while self.peek_at(0).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        let b = self.peek_at(0)?;
+
+        let kind = if b == b'-' && self.peek_at(1) == Some(b'-') {
+            self.pos += 2;
+            match self.long_bracket_level() {
+                Some(level) => self.long_bracket(level, "comment"),
+                None => {
+                    self.pos = self.src[self.pos..]
+                        .find('\n')
+                        .map(|i| self.pos + i)
+                        .unwrap_or(self.bytes.len());
+                }
+            }
+            TokenKind::Comment
+        } else if b == b'"' || b == b'\'' {
+            self.quoted_string(b);
+            TokenKind::String
+        } else if let Some(level) = self.long_bracket_level() {
+            self.long_bracket(level, "string");
+            TokenKind::String
+        } else if b.is_ascii_digit()
+            || (b == b'.' && self.peek_at(1).is_some_and(|b| b.is_ascii_digit()))
+        {
+            self.number();
+            TokenKind::Number
+        } else if b.is_ascii_alphabetic() || b == b'_' {
+            while self
+                .peek_at(0)
+                .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_')
+            {
+                self.pos += 1;
+            }
+            if KEYWORDS.contains(&&self.src[start..self.pos]) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Name
+            }
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| self.src[start..].starts_with(**s)) {
+            self.pos += symbol.len();
+            TokenKind::Symbol
+        } else {
+            self.pos += self.src[start..].chars().next().map_or(1, char::len_utf8);
+            self.error(start, "Unexpected character");
+            TokenKind::Unknown
+        };
+
+        Some(Token {
+            kind,
+            span: Span::new(start, self.pos),
+        })
+    }
+}
+
+/// Find the `}` matching the `{` at `open`, skipping over Lua strings,
+/// long strings, comments and nested table constructors.
+pub fn find_closing_brace(src: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for token in Lexer::at(src, open + 1) {
+        if token.kind != TokenKind::Symbol {
+            continue;
+        }
+        match token.text(src) {
+            "{" => depth += 1,
+            "}" if depth == 0 => return Some(token.span.start),
+            "}" => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str) -> Vec<(TokenKind, &str)> {
+        Lexer::new(src).map(|t| (t.kind, t.text(src))).collect()
+    }
+
+    #[test]
+    fn test_lex_tokens() {
+        assert_eq!(
+            kinds("local x = a.b .. 'hi' -- note\nreturn x ~= 0x1F"),
+            vec![
+                (TokenKind::Keyword, "local"),
+                (TokenKind::Name, "x"),
+                (TokenKind::Symbol, "="),
+                (TokenKind::Name, "a"),
+                (TokenKind::Symbol, "."),
+                (TokenKind::Name, "b"),
+                (TokenKind::Symbol, ".."),
+                (TokenKind::String, "'hi'"),
+                (TokenKind::Comment, "-- note"),
+                (TokenKind::Keyword, "return"),
+                (TokenKind::Name, "x"),
+                (TokenKind::Symbol, "~="),
+                (TokenKind::Number, "0x1F"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_long_brackets() {
+        let src = "[==[ a ]] b ]==] --[[ c\n ]] x";
+        assert_eq!(
+            kinds(src),
+            vec![
+                (TokenKind::String, "[==[ a ]] b ]==]"),
+                (TokenKind::Comment, "--[[ c\n ]]"),
+                (TokenKind::Name, "x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_errors() {
+        let mut lexer = Lexer::new("'open\n1e");
+        while lexer.next().is_some() {}
+        let messages: Vec<_> = lexer.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["Unfinished string", "Malformed number"]);
+    }
+
+    #[test]
+    fn test_find_closing_brace() {
+        let cases = [
+            ("{#each {1,2,3} as n}", 19),
+            ("{fmt({a=1})}", 11),
+            (r#"{"}"}"#, 4),
+            ("{[[}]] .. [=[}]=]}", 17),
+            ("{x -- }\n}", 8),
+        ];
+        for (src, expected) in cases {
+            assert_eq!(find_closing_brace(src, 0), Some(expected), "{}", src);
+        }
+        assert_eq!(find_closing_brace("{a {b}", 0), None);
+    }
+}
@@ -0,0 +1,16 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Lua language support for script blocks and template expressions
+
+pub mod ast;
+pub mod globals;
+pub mod lexer;
+pub mod parser;
+pub mod scope;
+pub mod virtual_document;
+
+pub use lexer::find_closing_brace;
+pub use parser::LuaVersion;
+pub use scope::{ScopeModel, SymbolKind};
+pub use virtual_document::VirtualDocument;
@@ -0,0 +1,1093 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Recursive descent parser for Lua 5.1 through 5.4.
+//!
+//! The parser accepts the Lua 5.4 grammar and reports constructs that the
+//! selected [`LuaVersion`] doesn't support. It recovers from errors by
+//! skipping to the next line or statement keyword, so a single mistake
+//! doesn't hide the rest of the script.
+
+use serde::Deserialize;
+
+use super::ast::*;
+use super::lexer::{Lexer, Token, TokenKind};
+use crate::syntax::{ParseError, Span};
+
+/// Lua dialect to validate against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum LuaVersion {
+    #[serde(rename = "5.1")]
+    Lua51,
+    /// LuaJIT 2: Lua 5.1 with `goto` and labels from 5.2
+    #[serde(rename = "luajit")]
+    LuaJit,
+    #[default]
+    #[serde(rename = "5.4")]
+    Lua54,
+}
+
+impl LuaVersion {
+    fn supports(self, syntax: Syntax) -> bool {
+        match self {
+            LuaVersion::Lua51 => false,
+            LuaVersion::LuaJit => syntax == Syntax::Goto,
+            LuaVersion::Lua54 => true,
+        }
+    }
+}
+
+/// Syntax added after Lua 5.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    /// `goto` and `::labels::`
+    Goto,
+    /// `//` and the bitwise operators
+    IntegerOperators,
+    /// `<const>` and `<close>`
+    Attributes,
+}
+
+impl Syntax {
+    /// The Lua version that introduced it
+    fn since(self) -> &'static str {
+        match self {
+            Syntax::Goto => "5.2",
+            Syntax::IntegerOperators => "5.3",
+            Syntax::Attributes => "5.4",
+        }
+    }
+}
+
+/// A parsed block of Lua code
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Chunk {
+    pub block: Block,
+    /// Lexical and syntax errors, in source order
+    pub errors: Vec<ParseError>,
+}
+
+/// Parse the Lua code in `span` of `src`, e.g. the contents of a script block
+pub fn parse_chunk(src: &str, span: Span, version: LuaVersion) -> Chunk {
+    let mut parser = Parser::new(src, span, version);
+    let mut block = parser.block(&[]);
+    block.span = span;
+    if let Some(token) = parser.peek() {
+        // Only reachable through a stray block terminator such as `end`
+        let message = format!("Unexpected '{}'", token.text(src));
+        parser.error(token.span, message);
+    }
+    parser.finish(block)
+}
+
+/// Parse a single expression filling `span`, e.g. the inside of `{...}`.
+/// Statements and anything after the expression are errors.
+pub fn parse_expression(src: &str, span: Span, version: LuaVersion) -> (Expr, Vec<ParseError>) {
+    let (mut exprs, errors) = parse_expressions(src, span, version, false);
+    (exprs.remove(0), errors)
+}
+
+/// Parse a comma-separated list of expressions filling `span`, e.g. the
+/// values of `{@local a, b = x, y}`
+pub fn parse_expression_list(
+    src: &str,
+    span: Span,
+    version: LuaVersion,
+) -> (Vec<Expr>, Vec<ParseError>) {
+    parse_expressions(src, span, version, true)
+}
+
+fn parse_expressions(
+    src: &str,
+    span: Span,
+    version: LuaVersion,
+    list: bool,
+) -> (Vec<Expr>, Vec<ParseError>) {
+    let mut parser = Parser::new(src, span, version);
+
+    if let Some(token) = parser.peek() {
+        let text = token.text(src);
+        if token.kind == TokenKind::Keyword
+            && STATEMENT_KEYWORDS.contains(&text)
+            && text != "function"
+            || token.kind == TokenKind::Symbol && text == "::"
+        {
+            let message = format!(
+                "Expected an expression, found a '{}' statement - statements aren't allowed here",
+                text
+            );
+            parser.error(Span::new(token.span.start, span.end), message);
+            let chunk = parser.finish(Block::default());
+            return (
+                vec![Expr {
+                    kind: ExprKind::Error,
+                    span,
+                }],
+                chunk.errors,
+            );
+        }
+    }
+
+    let exprs = if list {
+        parser.expr_list()
+    } else {
+        vec![parser.expr()]
+    };
+    if let Some(token) = parser.peek() {
+        let message = match token.text(src) {
+            "=" => {
+                "Unexpected '=' - assignments are statements and aren't allowed here".to_string()
+            }
+            "," if !list => "Unexpected ',' - only a single expression is allowed here".to_string(),
+            text => format!("Unexpected '{}' after expression", text),
+        };
+        parser.error(Span::new(token.span.start, span.end), message);
+    }
+    let chunk = parser.finish(Block::default());
+    (exprs, chunk.errors)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    /// End of the parsed range, used for errors at end of input
+    end: usize,
+    version: LuaVersion,
+    errors: Vec<ParseError>,
+}
+
+/// Keywords that start a statement, used to resynchronize after errors
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "local", "function", "if", "for", "while", "repeat", "return", "do", "break", "goto",
+];
+
+/// Keywords that end a block
+const BLOCK_END: &[&str] = &["end", "else", "elseif", "until"];
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str, span: Span, version: LuaVersion) -> Self {
+        let mut lexer = Lexer::at(&src[..span.end], span.start);
+        let tokens = lexer
+            .by_ref()
+            // Unknown characters are already reported by the lexer
+            .filter(|token| !matches!(token.kind, TokenKind::Comment | TokenKind::Unknown))
+            .collect();
+        let errors = lexer
+            .errors
+            .into_iter()
+            .map(|e| ParseError {
+                span: e.span,
+                message: e.message,
+            })
+            .collect();
+        Self {
+            src,
+            tokens,
+            pos: 0,
+            end: span.end,
+            version,
+            errors,
+        }
+    }
+
+    fn finish(mut self, block: Block) -> Chunk {
+        self.errors.sort_by_key(|e| e.span.start);
+        Chunk {
+            block,
+            errors: self.errors,
+        }
+    }
+
+    // ---- token helpers ---------------------------------------------------
+
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<Token> {
+        self.tokens.get(self.pos + n).copied()
+    }
+
+    /// Whether the current token is the given keyword or symbol
+    fn check(&self, text: &str) -> bool {
+        self.peek().is_some_and(|t| {
+            matches!(t.kind, TokenKind::Keyword | TokenKind::Symbol) && t.text(self.src) == text
+        })
+    }
+
+    fn check_any(&self, texts: &[&str]) -> bool {
+        texts.iter().any(|text| self.check(text))
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.check(text);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.peek();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Span of the current token, or an empty span at the end of input
+    fn current_span(&self) -> Span {
+        self.peek()
+            .map_or(Span::new(self.end, self.end), |t| t.span)
+    }
+
+    /// End of the last consumed token
+    fn last_end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(self.current_span().start, |t| t.span.end)
+    }
+
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        let message = message.into();
+        // Several failures at the same place are one error for the user
+        if self
+            .errors
+            .last()
+            .is_some_and(|e| e.span.start == span.start)
+        {
+            return;
+        }
+        self.errors.push(ParseError { span, message });
+    }
+
+    fn error_here(&mut self, expected: &str) {
+        let span = self.current_span();
+        let message = match self.peek() {
+            Some(token) => format!("Expected {} near '{}'", expected, token.text(self.src)),
+            None => format!("Expected {} at end of input", expected),
+        };
+        self.error(span, message);
+    }
+
+    fn expect(&mut self, text: &str) -> bool {
+        if self.eat(text) {
+            true
+        } else {
+            self.error_here(&format!("'{}'", text));
+            false
+        }
+    }
+
+    /// Expect the keyword closing a construct opened at `open`
+    fn expect_close(&mut self, text: &str, open: &str, open_span: Span) {
+        if !self.eat(text) {
+            let line = self.src[..open_span.start].matches('\n').count() + 1;
+            self.error_here(&format!("'{}' to close '{}' at line {}", text, open, line));
+        }
+    }
+
+    fn name(&mut self) -> Option<Name> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Name => {
+                self.pos += 1;
+                Some(Name {
+                    name: token.text(self.src).to_string(),
+                    span: token.span,
+                })
+            }
+            _ => {
+                self.error_here("a name");
+                None
+            }
+        }
+    }
+
+    /// Report `what`, a singular noun such as `Label`, unless the version
+    /// supports the syntax
+    fn require_version(&mut self, span: Span, what: &str, syntax: Syntax) {
+        match self.version {
+            version if version.supports(syntax) => {}
+            LuaVersion::LuaJit => self.error(span, format!("{} is not supported by LuaJIT", what)),
+            _ => self.error(
+                span,
+                format!("{} is not supported before Lua {}", what, syntax.since()),
+            ),
+        }
+    }
+
+    /// Skip ahead to something that can start a statement
+    fn recover(&mut self) {
+        let Some(first) = self.bump() else {
+            return;
+        };
+        let mut prev_end = first.span.end;
+        while let Some(token) = self.peek() {
+            let new_line = self.src[prev_end..token.span.start].contains('\n');
+            if new_line || self.check_any(STATEMENT_KEYWORDS) || self.check_any(BLOCK_END) {
+                break;
+            }
+            prev_end = token.span.end;
+            self.pos += 1;
+        }
+    }
+
+    // ---- statements ------------------------------------------------------
+
+    /// Parse statements up to one of the terminators. The block's span runs
+    /// from the end of the keyword opening it to the start of the terminator.
+    fn block(&mut self, terminators: &[&str]) -> Block {
+        let start = self.last_end();
+        let mut stats = Vec::new();
+
+        while self.peek().is_some() && !self.check_any(terminators) {
+            if terminators.is_empty() && self.check_any(BLOCK_END) {
+                // A stray `end` at top level
+                let token = self.bump().unwrap();
+                let message = format!("Unexpected '{}'", token.text(self.src));
+                self.error(token.span, message);
+                continue;
+            }
+            if !terminators.is_empty() && self.check_any(BLOCK_END) {
+                // Terminator of an enclosing construct; let it report
+                break;
+            }
+
+            let before = self.pos;
+            let errors = self.errors.len();
+            match self.statement() {
+                Some(stat) => {
+                    let is_return = matches!(stat.kind, StatKind::Return(_));
+                    stats.push(stat);
+                    if is_return && self.peek().is_some() && !self.check_any(BLOCK_END) {
+                        self.error_here("end of block after 'return'");
+                        self.recover();
+                    }
+                }
+                None => {
+                    if self.pos == before || self.errors.len() > errors {
+                        self.recover();
+                    }
+                }
+            }
+        }
+
+        Block {
+            stats,
+            span: Span::new(start, self.current_span().start.max(start)),
+        }
+    }
+
+    fn statement(&mut self) -> Option<Stat> {
+        let start = self.current_span().start;
+        let token = self.peek()?;
+        let text = token.text(self.src);
+
+        let kind = match (token.kind, text) {
+            (TokenKind::Symbol, ";") => {
+                self.pos += 1;
+                StatKind::Empty
+            }
+            (TokenKind::Symbol, "::") => {
+                self.pos += 1;
+                self.require_version(token.span, "Label", Syntax::Goto);
+                let name = self.name()?;
+                self.expect("::");
+                StatKind::Label(name)
+            }
+            (TokenKind::Keyword, "if") => self.if_statement(token.span),
+            (TokenKind::Keyword, "while") => {
+                self.pos += 1;
+                let cond = self.expr();
+                self.expect("do");
+                let block = self.block(&["end"]);
+                self.expect_close("end", "while", token.span);
+                StatKind::While { cond, block }
+            }
+            (TokenKind::Keyword, "do") => {
+                self.pos += 1;
+                let block = self.block(&["end"]);
+                self.expect_close("end", "do", token.span);
+                StatKind::Do(block)
+            }
+            (TokenKind::Keyword, "for") => self.for_statement(token.span)?,
+            (TokenKind::Keyword, "repeat") => {
+                self.pos += 1;
+                let block = self.block(&["until"]);
+                self.expect_close("until", "repeat", token.span);
+                let cond = self.expr();
+                StatKind::Repeat { block, cond }
+            }
+            (TokenKind::Keyword, "function") => {
+                self.pos += 1;
+                let mut path = vec![self.name()?];
+                let mut is_method = false;
+                while self.check(".") || self.check(":") {
+                    is_method = self.check(":");
+                    self.pos += 1;
+                    path.push(self.name()?);
+                    if is_method {
+                        break;
+                    }
+                }
+                let body = self.function_body(token.span);
+                StatKind::Function {
+                    path,
+                    is_method,
+                    body,
+                }
+            }
+            (TokenKind::Keyword, "local") => {
+                self.pos += 1;
+                if self.check("function") {
+                    let function = self.bump().unwrap();
+                    let name = self.name()?;
+                    let body = self.function_body(function.span);
+                    StatKind::LocalFunction { name, body }
+                } else {
+                    self.local_statement()?
+                }
+            }
+            (TokenKind::Keyword, "return") => {
+                self.pos += 1;
+                let exprs = if self.peek().is_none() || self.check(";") || self.check_any(BLOCK_END)
+                {
+                    Vec::new()
+                } else {
+                    self.expr_list()
+                };
+                self.eat(";");
+                StatKind::Return(exprs)
+            }
+            (TokenKind::Keyword, "break") => {
+                self.pos += 1;
+                StatKind::Break
+            }
+            (TokenKind::Keyword, "goto") => {
+                self.pos += 1;
+                self.require_version(token.span, "'goto'", Syntax::Goto);
+                StatKind::Goto(self.name()?)
+            }
+            _ => self.expression_statement()?,
+        };
+
+        Some(Stat {
+            kind,
+            span: Span::new(start, self.last_end().max(start)),
+        })
+    }
+
+    fn if_statement(&mut self, open: Span) -> StatKind {
+        self.pos += 1;
+        let mut clauses = Vec::new();
+        let cond = self.expr();
+        self.expect("then");
+        let block = self.block(&["elseif", "else", "end"]);
+        clauses.push((cond, block));
+
+        let mut else_block = None;
+        loop {
+            if self.eat("elseif") {
+                let cond = self.expr();
+                self.expect("then");
+                let block = self.block(&["elseif", "else", "end"]);
+                clauses.push((cond, block));
+            } else if self.eat("else") {
+                else_block = Some(self.block(&["end"]));
+                self.expect_close("end", "if", open);
+                break;
+            } else {
+                self.expect_close("end", "if", open);
+                break;
+            }
+        }
+        StatKind::If {
+            clauses,
+            else_block,
+        }
+    }
+
+    fn for_statement(&mut self, open: Span) -> Option<StatKind> {
+        self.pos += 1;
+        let first = self.name()?;
+
+        if self.eat("=") {
+            let start = Box::new(self.expr());
+            self.expect(",");
+            let end = Box::new(self.expr());
+            let step = self.eat(",").then(|| Box::new(self.expr()));
+            self.expect("do");
+            let block = self.block(&["end"]);
+            self.expect_close("end", "for", open);
+            return Some(StatKind::NumericFor {
+                var: first,
+                start,
+                end,
+                step,
+                block,
+            });
+        }
+
+        let mut names = vec![first];
+        while self.eat(",") {
+            names.push(self.name()?);
+        }
+        if !self.expect("in") {
+            return None;
+        }
+        let exprs = self.expr_list();
+        self.expect("do");
+        let block = self.block(&["end"]);
+        self.expect_close("end", "for", open);
+        Some(StatKind::GenericFor {
+            names,
+            exprs,
+            block,
+        })
+    }
+
+    fn local_statement(&mut self) -> Option<StatKind> {
+        let mut names = Vec::new();
+        let mut attribs = Vec::new();
+        loop {
+            names.push(self.name()?);
+            let attrib = if self.check("<") {
+                let open = self.bump().unwrap();
+                self.require_version(open.span, "Variable attribute", Syntax::Attributes);
+                let attrib = self.name();
+                if let Some(attrib) = &attrib {
+                    if attrib.name != "const" && attrib.name != "close" {
+                        let message = format!("Unknown attribute '{}'", attrib.name);
+                        self.error(attrib.span, message);
+                    }
+                }
+                self.expect(">");
+                attrib
+            } else {
+                None
+            };
+            attribs.push(attrib);
+            if !self.eat(",") {
+                break;
+            }
+        }
+
+        let exprs = if self.eat("=") {
+            self.expr_list()
+        } else {
+            Vec::new()
+        };
+        Some(StatKind::Local {
+            names,
+            attribs,
+            exprs,
+        })
+    }
+
+    /// An assignment or a function call
+    fn expression_statement(&mut self) -> Option<StatKind> {
+        let first = self.suffixed_expr()?;
+
+        if self.check("=") || self.check(",") {
+            let mut targets = vec![first];
+            while self.eat(",") {
+                targets.push(self.suffixed_expr()?);
+            }
+            for target in &targets {
+                if !matches!(
+                    target.kind,
+                    ExprKind::Name(_) | ExprKind::Index { .. } | ExprKind::Field { .. }
+                ) {
+                    self.error(target.span, "Cannot assign to this expression");
+                }
+            }
+            self.expect("=");
+            let exprs = self.expr_list();
+            return Some(StatKind::Assign { targets, exprs });
+        }
+
+        if matches!(
+            first.kind,
+            ExprKind::Call { .. } | ExprKind::MethodCall { .. }
+        ) {
+            Some(StatKind::Call(first))
+        } else {
+            self.error(
+                first.span,
+                "Syntax error: expected an assignment or a function call",
+            );
+            None
+        }
+    }
+
+    fn function_body(&mut self, open: Span) -> FunctionBody {
+        let start = self.current_span().start;
+        let mut params = Vec::new();
+        let mut vararg = false;
+
+        if self.expect("(") {
+            if !self.check(")") {
+                loop {
+                    if self.eat("...") {
+                        vararg = true;
+                        break;
+                    }
+                    match self.name() {
+                        Some(name) => params.push(name),
+                        None => break,
+                    }
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+            }
+            self.expect(")");
+        }
+
+        let block = self.block(&["end"]);
+        self.expect_close("end", "function", open);
+        FunctionBody {
+            params,
+            vararg,
+            block,
+            span: Span::new(start, self.last_end().max(start)),
+        }
+    }
+
+    // ---- expressions -----------------------------------------------------
+
+    fn expr_list(&mut self) -> Vec<Expr> {
+        let mut exprs = vec![self.expr()];
+        while self.eat(",") {
+            exprs.push(self.expr());
+        }
+        exprs
+    }
+
+    fn expr(&mut self) -> Expr {
+        self.sub_expr(0)
+    }
+
+    /// Parse an expression whose binary operators bind tighter than `limit`
+    fn sub_expr(&mut self, limit: u8) -> Expr {
+        let start = self.current_span().start;
+        let mut lhs = match self.unary_op() {
+            Some(op) => {
+                let operand = self.sub_expr(UNARY_PRIORITY);
+                Expr {
+                    span: Span::new(start, operand.span.end),
+                    kind: ExprKind::Unary {
+                        op,
+                        expr: Box::new(operand),
+                    },
+                }
+            }
+            None => self.simple_expr(),
+        };
+
+        while let Some((op, left, right)) = self.binary_op() {
+            // Don't pile more errors onto an operand that failed to parse
+            if left <= limit || lhs.kind == ExprKind::Error {
+                break;
+            }
+            let token = self.bump().unwrap();
+            if matches!(
+                op,
+                BinaryOp::IntDiv
+                    | BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor
+                    | BinaryOp::Shl
+                    | BinaryOp::Shr
+            ) {
+                let what = format!("'{}'", token.text(self.src));
+                self.require_version(token.span, &what, Syntax::IntegerOperators);
+            }
+            let rhs = self.sub_expr(right);
+            lhs = Expr {
+                span: Span::new(lhs.span.start, rhs.span.end),
+                kind: ExprKind::Binary {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+            };
+        }
+        lhs
+    }
+
+    fn unary_op(&mut self) -> Option<UnaryOp> {
+        let op = match self.peek()?.text(self.src) {
+            "not" if self.check("not") => UnaryOp::Not,
+            "-" => UnaryOp::Neg,
+            "#" => UnaryOp::Len,
+            "~" if self.check("~") => UnaryOp::BitNot,
+            _ => return None,
+        };
+        let token = self.bump().unwrap();
+        if op == UnaryOp::BitNot {
+            self.require_version(token.span, "'~'", Syntax::IntegerOperators);
+        }
+        Some(op)
+    }
+
+    /// The binary operator at the current token with its left and right priority
+    fn binary_op(&self) -> Option<(BinaryOp, u8, u8)> {
+        let token = self.peek()?;
+        if !matches!(token.kind, TokenKind::Symbol | TokenKind::Keyword) {
+            return None;
+        }
+        Some(match token.text(self.src) {
+            "or" => (BinaryOp::Or, 1, 1),
+            "and" => (BinaryOp::And, 2, 2),
+            "<" => (BinaryOp::Lt, 3, 3),
+            ">" => (BinaryOp::Gt, 3, 3),
+            "<=" => (BinaryOp::Le, 3, 3),
+            ">=" => (BinaryOp::Ge, 3, 3),
+            "~=" => (BinaryOp::Ne, 3, 3),
+            "==" => (BinaryOp::Eq, 3, 3),
+            "|" => (BinaryOp::BitOr, 4, 4),
+            "~" => (BinaryOp::BitXor, 5, 5),
+            "&" => (BinaryOp::BitAnd, 6, 6),
+            "<<" => (BinaryOp::Shl, 7, 7),
+            ">>" => (BinaryOp::Shr, 7, 7),
+            // Right associative
+            ".." => (BinaryOp::Concat, 9, 8),
+            "+" => (BinaryOp::Add, 10, 10),
+            "-" => (BinaryOp::Sub, 10, 10),
+            "*" => (BinaryOp::Mul, 11, 11),
+            "/" => (BinaryOp::Div, 11, 11),
+            "//" => (BinaryOp::IntDiv, 11, 11),
+            "%" => (BinaryOp::Mod, 11, 11),
+            // Right associative, binds tighter than unary operators
+            "^" => (BinaryOp::Pow, 14, 13),
+            _ => return None,
+        })
+    }
+
+    fn simple_expr(&mut self) -> Expr {
+        let Some(token) = self.peek() else {
+            self.error_here("an expression");
+            return self.error_expr();
+        };
+
+        let kind = match (token.kind, token.text(self.src)) {
+            (TokenKind::Number, _) => ExprKind::Number,
+            (TokenKind::String, _) => ExprKind::String,
+            (TokenKind::Keyword, "nil") => ExprKind::Nil,
+            (TokenKind::Keyword, "true") => ExprKind::True,
+            (TokenKind::Keyword, "false") => ExprKind::False,
+            (TokenKind::Symbol, "...") => ExprKind::Vararg,
+            (TokenKind::Symbol, "{") => return self.table(),
+            (TokenKind::Keyword, "function") => {
+                self.pos += 1;
+                let body = self.function_body(token.span);
+                return Expr {
+                    span: Span::new(token.span.start, body.span.end),
+                    kind: ExprKind::Function(Box::new(body)),
+                };
+            }
+            _ => return self.suffixed_expr().unwrap_or_else(|| self.error_expr()),
+        };
+        self.pos += 1;
+        Expr {
+            kind,
+            span: token.span,
+        }
+    }
+
+    fn error_expr(&self) -> Expr {
+        let span = self.current_span();
+        Expr {
+            kind: ExprKind::Error,
+            span: Span::new(span.start, span.start),
+        }
+    }
+
+    fn primary_expr(&mut self) -> Option<Expr> {
+        let token = self.peek();
+        match token {
+            Some(token) if token.kind == TokenKind::Name => {
+                self.pos += 1;
+                Some(Expr {
+                    kind: ExprKind::Name(token.text(self.src).to_string()),
+                    span: token.span,
+                })
+            }
+            Some(token) if self.check("(") => {
+                self.pos += 1;
+                let inner = self.expr();
+                self.expect(")");
+                Some(Expr {
+                    kind: ExprKind::Paren(Box::new(inner)),
+                    span: Span::new(token.span.start, self.last_end()),
+                })
+            }
+            _ => {
+                self.error_here("an expression");
+                None
+            }
+        }
+    }
+
+    fn suffixed_expr(&mut self) -> Option<Expr> {
+        let mut expr = self.primary_expr()?;
+        let start = expr.span.start;
+
+        loop {
+            let kind = if self.eat(".") {
+                let name = self.name()?;
+                ExprKind::Field {
+                    obj: Box::new(expr),
+                    name,
+                }
+            } else if self.eat("[") {
+                let key = self.expr();
+                self.expect("]");
+                ExprKind::Index {
+                    obj: Box::new(expr),
+                    key: Box::new(key),
+                }
+            } else if self.eat(":") {
+                let method = self.name()?;
+                let args = self.call_args()?;
+                ExprKind::MethodCall {
+                    obj: Box::new(expr),
+                    method,
+                    args,
+                }
+            } else if self.check("(") || self.check("{") || self.at_string() {
+                let args = self.call_args()?;
+                ExprKind::Call {
+                    callee: Box::new(expr),
+                    args,
+                }
+            } else {
+                return Some(expr);
+            };
+            expr = Expr {
+                kind,
+                span: Span::new(start, self.last_end()),
+            };
+        }
+    }
+
+    fn at_string(&self) -> bool {
+        self.peek().is_some_and(|t| t.kind == TokenKind::String)
+    }
+
+    fn call_args(&mut self) -> Option<Vec<Expr>> {
+        if self.at_string() {
+            let token = self.bump().unwrap();
+            return Some(vec![Expr {
+                kind: ExprKind::String,
+                span: token.span,
+            }]);
+        }
+        if self.check("{") {
+            return Some(vec![self.table()]);
+        }
+        if !self.expect("(") {
+            return None;
+        }
+        let args = if self.check(")") {
+            Vec::new()
+        } else {
+            self.expr_list()
+        };
+        self.expect(")");
+        Some(args)
+    }
+
+    fn table(&mut self) -> Expr {
+        let open = self.bump().unwrap();
+        let mut fields = Vec::new();
+
+        while !self.check("}") && self.peek().is_some() {
+            let field = if self.check("[") {
+                self.pos += 1;
+                let key = self.expr();
+                self.expect("]");
+                self.expect("=");
+                Field::Indexed(key, self.expr())
+            } else if self.peek().is_some_and(|t| t.kind == TokenKind::Name)
+                && self
+                    .peek_at(1)
+                    .is_some_and(|t| t.kind == TokenKind::Symbol && t.text(self.src) == "=")
+            {
+                let name = self.name().unwrap();
+                self.pos += 1;
+                Field::Named(name, self.expr())
+            } else {
+                let before = self.pos;
+                let value = self.expr();
+                if self.pos == before {
+                    // Not an expression; give up on the table
+                    break;
+                }
+                Field::Positional(value)
+            };
+            fields.push(field);
+
+            if !self.eat(",") && !self.eat(";") {
+                break;
+            }
+        }
+
+        if !self.eat("}") {
+            let line = self.src[..open.span.start].matches('\n').count() + 1;
+            self.error_here(&format!("'}}' to close '{{' at line {}", line));
+        }
+        Expr {
+            kind: ExprKind::Table(fields),
+            span: Span::new(open.span.start, self.last_end()),
+        }
+    }
+}
+
+/// Priority of unary operators; only `^` binds tighter
+const UNARY_PRIORITY: u8 = 12;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> Chunk {
+        parse_chunk(src, Span::new(0, src.len()), LuaVersion::Lua54)
+    }
+
+    fn messages(chunk: &Chunk) -> Vec<&str> {
+        chunk.errors.iter().map(|e| e.message.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let src = r#"
+local Card = require("components/Card")
+local count <const> = 0
+
+local function format(value, ...)
+  return tostring(value) .. "!"
+end
+
+function M.helpers:run(a)
+  for i = 1, 10, 2 do print(i) end
+  for k, v in pairs(a) do
+    if k == 1 then break elseif not v then goto skip else a[k] = -v ^ 2 end
+  end
+  ::skip::
+  repeat count = count + 1 until count > 3
+  while true do end
+  do local t = { 1, x = 2, ["y"] = 3; f = function() end } end
+end
+
+return { format = format }
+"#;
+        let chunk = parse(src);
+        assert_eq!(messages(&chunk), Vec::<&str>::new());
+        assert_eq!(chunk.block.stats.len(), 5);
+
+        let StatKind::Local { names, exprs, .. } = &chunk.block.stats[0].kind else {
+            panic!("expected local");
+        };
+        assert_eq!(names[0].name, "Card");
+        let path = exprs[0].as_require().unwrap();
+        assert_eq!(string_value(src, path.span), "components/Card");
+    }
+
+    #[test]
+    fn test_operator_precedence() {
+        let src = "x = 1 + 2 * 3 .. 'a' .. 'b'";
+        let chunk = parse(src);
+        let StatKind::Assign { exprs, .. } = &chunk.block.stats[0].kind else {
+            panic!("expected assignment");
+        };
+        // `..` binds loosest and is right associative
+        let ExprKind::Binary { op, lhs, rhs } = &exprs[0].kind else {
+            panic!("expected binary expression");
+        };
+        assert_eq!(*op, BinaryOp::Concat);
+        assert_eq!(lhs.span.text(src), "1 + 2 * 3");
+        assert_eq!(rhs.span.text(src), "'a' .. 'b'");
+    }
+
+    #[test]
+    fn test_parse_errors_recover() {
+        let src = "local x = \nif x then\n  y = = 2\nend\nlocal function f(\nprint('ok')";
+        let chunk = parse(src);
+        assert_eq!(
+            messages(&chunk),
+            vec![
+                "Expected an expression near 'if'",
+                "Expected an expression near '='",
+                "Expected ')' near '('",
+                "Expected 'end' to close 'function' at line 5 at end of input",
+            ]
+        );
+        // Statements after errors are still parsed
+        assert!(chunk
+            .block
+            .stats
+            .iter()
+            .any(|s| matches!(s.kind, StatKind::If { .. })));
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        let parse = |src: &str| parse_expression(src, Span::new(0, src.len()), LuaVersion::Lua54);
+
+        let (expr, errors) = parse("user.name .. '!'");
+        assert!(errors.is_empty());
+        assert!(matches!(expr.kind, ExprKind::Binary { .. }));
+
+        let messages =
+            |src: &str| -> Vec<String> { parse(src).1.into_iter().map(|e| e.message).collect() };
+        assert_eq!(
+            messages("user.name .."),
+            vec!["Expected an expression at end of input"]
+        );
+        assert_eq!(
+            messages("local x = 1"),
+            vec!["Expected an expression, found a 'local' statement - statements aren't allowed here"]
+        );
+        assert_eq!(
+            messages("x = 1"),
+            vec!["Unexpected '=' - assignments are statements and aren't allowed here"]
+        );
+        assert!(messages("function() return 1 end").is_empty());
+
+        let src = "1, f(2)";
+        let (exprs, errors) =
+            parse_expression_list(src, Span::new(0, src.len()), LuaVersion::Lua54);
+        assert_eq!(exprs.len(), 2);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_version_specific_syntax() {
+        let src = "local a <close> = x // 2\ngoto done\n::done::";
+        assert!(parse(src).errors.is_empty());
+
+        let chunk = parse_chunk(src, Span::new(0, src.len()), LuaVersion::Lua51);
+        assert_eq!(
+            messages(&chunk),
+            vec![
+                "Variable attribute is not supported before Lua 5.4",
+                "'//' is not supported before Lua 5.3",
+                "'goto' is not supported before Lua 5.2",
+                "Label is not supported before Lua 5.2",
+            ]
+        );
+
+        let chunk = parse_chunk(src, Span::new(0, src.len()), LuaVersion::LuaJit);
+        assert_eq!(
+            messages(&chunk),
+            vec![
+                "Variable attribute is not supported by LuaJIT",
+                "'//' is not supported by LuaJIT",
+            ]
+        );
+    }
+}
@@ -0,0 +1,1000 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Scopes and bindings of the Lua code in a template's scripts.
+//!
+//! Module scripts are analyzed first, then instance scripts, mirroring the
+//! order they run in. Top-level locals of all scripts share the root scope,
+//! which also covers the template markup, so instance script locals are
+//! visible to template expressions.
+
+use std::collections::HashMap;
+
+use super::ast::*;
+use super::lexer::{Lexer, TokenKind};
+use super::parser::{parse_chunk, parse_expression, parse_expression_list, Chunk, LuaVersion};
+use super::virtual_document::split_each;
+use crate::syntax::{Block as TemplateBlock, Edit, Node, ParseError, Span, SyntaxTree, Tag};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// `local x = ...`, or a loop variable
+    Local,
+    /// `local function f()` or `local f = function() end`
+    Function,
+    Parameter,
+    /// `local X = require("path")`
+    Import,
+    /// `item` in `{#each items as item, index}`
+    EachItem,
+    /// `index` in `{#each items as item, index}`
+    EachIndex,
+}
+
+/// A local variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The name where it's declared
+    pub span: Span,
+    /// The declaring statement, or the name for parameters and loop variables
+    pub declaration: Span,
+    pub scope: usize,
+    /// Offset from which the name refers to this symbol
+    pub visible_from: usize,
+    /// Reads and writes after the declaration
+    pub references: Vec<Span>,
+    /// For imports, the path string literal
+    pub require: Option<Span>,
+    /// Whether a nested function uses it as an upvalue
+    pub captured: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The whole template
+    Root,
+    Function,
+    Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub span: Span,
+    pub parent: Option<usize>,
+    /// The innermost function (or root) scope containing this scope
+    pub function: usize,
+    /// For function scopes, the enclosing locals the function uses
+    pub upvalues: Vec<usize>,
+}
+
+/// A `require("path")` call anywhere in the scripts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Require {
+    pub path: String,
+    /// The path string literal
+    pub span: Span,
+}
+
+/// A field of the table a module script returns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub name: String,
+    pub span: Span,
+    /// Whether the value is a function
+    pub is_function: bool,
+}
+
+/// A `{@local a, b = x, y}` directive
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateLocal {
+    /// The names being declared
+    pub names: Vec<Span>,
+    pub values: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScopeModel {
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    /// Reads of names that aren't declared as locals
+    pub global_reads: Vec<Name>,
+    /// Assignments to names that aren't declared as locals
+    pub global_writes: Vec<Name>,
+    /// `function name()` declarations of globals
+    pub global_functions: Vec<Name>,
+    pub requires: Vec<Require>,
+    /// Component tags whose name isn't a local, like `<Card>` without an import
+    pub unbound_components: Vec<Name>,
+    /// Fields of the table returned from a module script
+    pub exports: Vec<Export>,
+    /// Syntax errors in the scripts
+    pub errors: Vec<ParseError>,
+    /// Parsed scripts in the order they were analyzed
+    pub scripts: Vec<Block>,
+    /// Markup expressions that parsed without errors, by span
+    pub expressions: HashMap<Span, Expr>,
+    /// `{@local}` directives whose values parsed without errors, by the
+    /// directive's expression
+    pub template_locals: HashMap<Span, TemplateLocal>,
+    /// Script contents in the order they were analyzed
+    chunks: Vec<Span>,
+}
+
+impl ScopeModel {
+    /// Analyze the Lua scripts of a template, then the Lua fragments of its
+    /// markup with the scopes its blocks introduce
+    pub fn for_template(tree: &SyntaxTree, text: &str, version: LuaVersion) -> Self {
+        Self::analyze(tree, text, version, |span| parse_chunk(text, span, version))
+    }
+
+    /// Analyze a template again after `edit`, reusing the parsed scripts of
+    /// `previous` that the edit didn't touch. Only the edited script and the
+    /// markup fragments are parsed again.
+    pub fn reanalyze(
+        previous: &ScopeModel,
+        tree: &SyntaxTree,
+        text: &str,
+        version: LuaVersion,
+        edit: Edit,
+    ) -> Self {
+        Self::analyze(tree, text, version, |span| {
+            previous
+                .parsed_chunk(span, edit)
+                .unwrap_or_else(|| parse_chunk(text, span, version))
+        })
+    }
+
+    fn analyze(
+        tree: &SyntaxTree,
+        text: &str,
+        version: LuaVersion,
+        mut parse: impl FnMut(Span) -> Chunk,
+    ) -> Self {
+        let scripts = tree.lua_scripts();
+        let (modules, instances): (Vec<_>, Vec<_>) =
+            scripts.into_iter().partition(|script| script.is_module());
+
+        let mut builder = Builder::new(text, version);
+        for script in modules.iter().chain(&instances) {
+            let chunk = parse(script.content);
+            builder.model.errors.extend(chunk.errors);
+            builder.chunk(script.content, &chunk.block);
+            builder.model.scripts.push(chunk.block);
+        }
+        builder.template_nodes(&tree.nodes);
+
+        let mut model = builder.model;
+        model.errors.sort_by_key(|e| e.span.start);
+        model
+    }
+
+    /// Analyze parsed chunks, each with the span of source it came from
+    #[cfg(test)]
+    pub fn build(src: &str, chunks: &[(Span, &Block)]) -> Self {
+        let mut builder = Builder::new(src, LuaVersion::default());
+        for (span, block) in chunks {
+            builder.chunk(*span, block);
+            builder.model.scripts.push((*block).clone());
+        }
+        builder.model
+    }
+
+    /// The script chunk in `span` as parsed before `edit`, if the edit left
+    /// it alone
+    fn parsed_chunk(&self, span: Span, edit: Edit) -> Option<Chunk> {
+        let (i, &old) = self.chunks.iter().enumerate().find(|(_, chunk)| {
+            if chunk.end < edit.start {
+                **chunk == span
+            } else if chunk.start > edit.old_end {
+                let mut shifted = **chunk;
+                shifted.shift(edit.old_end, edit.delta());
+                shifted == span
+            } else {
+                false
+            }
+        })?;
+        // Only parsing reports errors inside scripts
+        let mut chunk = Chunk {
+            block: self.scripts[i].clone(),
+            errors: self
+                .errors
+                .iter()
+                .filter(|e| e.span.start >= old.start && e.span.start <= old.end)
+                .cloned()
+                .collect(),
+        };
+        if old != span {
+            chunk.block.shift(edit.old_end, edit.delta());
+            for error in &mut chunk.errors {
+                error.span.shift(edit.old_end, edit.delta());
+            }
+        }
+        Some(chunk)
+    }
+
+    /// Move every position after an edit that ended at `from` by `delta`.
+    /// The edit left the Lua code alone, so it only inserted or removed
+    /// markup next to it.
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        // The root scope covers everything. A block's scope is its body, so
+        // text inserted where the body starts is inside it.
+        for scope in &mut self.scopes[1..] {
+            let start = scope.span.start;
+            scope.span.shift(from, delta);
+            if start == from {
+                scope.span.start = start;
+            }
+        }
+        for symbol in &mut self.symbols {
+            shift_code(&mut symbol.span, from, delta);
+            shift_code(&mut symbol.declaration, from, delta);
+            // Symbols declared by a tag ending at `from` are visible from there
+            if symbol.visible_from > from {
+                symbol.visible_from = symbol.visible_from.saturating_add_signed(delta);
+            }
+            for reference in &mut symbol.references {
+                shift_code(reference, from, delta);
+            }
+            if let Some(require) = &mut symbol.require {
+                require.shift(from, delta);
+            }
+        }
+        for name in self
+            .global_reads
+            .iter_mut()
+            .chain(&mut self.global_writes)
+            .chain(&mut self.global_functions)
+            .chain(&mut self.unbound_components)
+        {
+            shift_code(&mut name.span, from, delta);
+        }
+        for require in &mut self.requires {
+            require.span.shift(from, delta);
+        }
+        for export in &mut self.exports {
+            export.span.shift(from, delta);
+        }
+        for error in &mut self.errors {
+            error.span.shift(from, delta);
+        }
+        for block in &mut self.scripts {
+            block.shift(from, delta);
+        }
+        self.expressions = std::mem::take(&mut self.expressions)
+            .into_iter()
+            .map(|(mut span, mut expr)| {
+                span.shift(from, delta);
+                expr.shift(from, delta);
+                (span, expr)
+            })
+            .collect();
+        self.template_locals = std::mem::take(&mut self.template_locals)
+            .into_iter()
+            .map(|(mut span, mut local)| {
+                span.shift(from, delta);
+                for name in &mut local.names {
+                    name.shift(from, delta);
+                }
+                for value in &mut local.values {
+                    value.shift(from, delta);
+                }
+                (span, local)
+            })
+            .collect();
+        for chunk in &mut self.chunks {
+            chunk.shift(from, delta);
+        }
+    }
+
+    /// Whether the offset is inside one of the scripts rather than the markup
+    pub fn in_script(&self, offset: usize) -> bool {
+        self.chunks.iter().any(|span| span.contains(offset))
+    }
+
+    /// The innermost scope containing the offset
+    pub fn scope_at(&self, offset: usize) -> usize {
+        // Scopes are created in source order, so the last match is the deepest
+        self.scopes
+            .iter()
+            .rposition(|scope| scope.span.start <= offset && offset <= scope.span.end)
+            .unwrap_or(0)
+    }
+
+    /// Locals visible at the offset, innermost first. Shadowed locals are
+    /// left out.
+    pub fn visible_at(&self, offset: usize) -> Vec<&Symbol> {
+        let mut chain = Vec::new();
+        let mut scope = Some(self.scope_at(offset));
+        while let Some(index) = scope {
+            chain.push(index);
+            scope = self.scopes[index].parent;
+        }
+        let chunk = self.chunks.iter().position(|span| span.contains(offset));
+
+        let mut visible: Vec<&Symbol> = Vec::new();
+        for symbol in self.symbols.iter().rev() {
+            if !chain.contains(&symbol.scope) || visible.iter().any(|s| s.name == symbol.name) {
+                continue;
+            }
+            let declared_in = self
+                .chunks
+                .iter()
+                .position(|span| span.contains(symbol.span.start));
+            let in_scope = match chunk {
+                Some(chunk) if Some(chunk) == declared_in => offset >= symbol.visible_from,
+                Some(chunk) => declared_in.is_some_and(|declared| declared < chunk),
+                // Markup sees every script, and template locals after their tag
+                None => declared_in.is_some() || offset >= symbol.visible_from,
+            };
+            if in_scope {
+                visible.push(symbol);
+            }
+        }
+        visible
+    }
+
+    /// The local declared or referenced at the offset
+    pub fn symbol_at(&self, offset: usize) -> Option<&Symbol> {
+        let touches = |span: &Span| span.start <= offset && offset <= span.end;
+        self.symbols
+            .iter()
+            .find(|symbol| touches(&symbol.span) || symbol.references.iter().any(touches))
+    }
+
+    /// The require call whose path literal contains the offset
+    pub fn require_at(&self, offset: usize) -> Option<&Require> {
+        self.requires
+            .iter()
+            .find(|require| require.span.start <= offset && offset <= require.span.end)
+    }
+
+    /// The top-level import binding a name, e.g. a component
+    pub fn import(&self, name: &str) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|s| s.kind == SymbolKind::Import && s.scope == 0 && s.name == name)
+    }
+
+    /// The require path of an import
+    pub fn require_path(&self, symbol: &Symbol) -> Option<&str> {
+        let span = symbol.require?;
+        self.requires
+            .iter()
+            .find(|require| require.span == span)
+            .map(|require| require.path.as_str())
+    }
+}
+
+/// Shift a span of code past an edit that ended at `from`. Text inserted
+/// right after the code isn't part of it.
+fn shift_code(span: &mut Span, from: usize, delta: isize) {
+    let end = span.end;
+    span.shift(from, delta);
+    if end == from && span.start < from {
+        span.end = end;
+    }
+}
+
+struct Builder<'a> {
+    src: &'a str,
+    version: LuaVersion,
+    model: ScopeModel,
+    /// Symbols in scope at the current point of the walk
+    active: Vec<usize>,
+    scope: usize,
+    /// Fields assigned to top-level locals, for `return M` exports
+    fields: HashMap<usize, Vec<Export>>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(src: &'a str, version: LuaVersion) -> Self {
+        let mut model = ScopeModel::default();
+        model.scopes.push(Scope {
+            kind: ScopeKind::Root,
+            span: Span::new(0, usize::MAX),
+            parent: None,
+            function: 0,
+            upvalues: Vec::new(),
+        });
+        Self {
+            src,
+            version,
+            model,
+            active: Vec::new(),
+            scope: 0,
+            fields: HashMap::new(),
+        }
+    }
+
+    fn chunk(&mut self, span: Span, block: &Block) {
+        self.model.chunks.push(span);
+        self.stats(&block.stats);
+        self.exports(block);
+    }
+
+    fn enter(&mut self, kind: ScopeKind, span: Span) -> (usize, usize) {
+        let index = self.model.scopes.len();
+        let function = match kind {
+            ScopeKind::Block => self.model.scopes[self.scope].function,
+            _ => index,
+        };
+        self.model.scopes.push(Scope {
+            kind,
+            span,
+            parent: Some(self.scope),
+            function,
+            upvalues: Vec::new(),
+        });
+        let saved = (self.scope, self.active.len());
+        self.scope = index;
+        saved
+    }
+
+    fn exit(&mut self, (scope, active): (usize, usize)) {
+        self.scope = scope;
+        self.active.truncate(active);
+    }
+
+    fn declare(&mut self, name: &Name, kind: SymbolKind, declaration: Span, visible_from: usize) {
+        let index = self.model.symbols.len();
+        self.model.symbols.push(Symbol {
+            name: name.name.clone(),
+            kind,
+            span: name.span,
+            declaration,
+            scope: self.scope,
+            visible_from,
+            references: Vec::new(),
+            require: None,
+            captured: false,
+        });
+        self.active.push(index);
+    }
+
+    /// Record a use of a name, returning the local it refers to
+    fn reference(&mut self, name: &str, span: Span) -> Option<usize> {
+        let index = self
+            .active
+            .iter()
+            .rev()
+            .copied()
+            .find(|&i| self.model.symbols[i].name == name)?;
+
+        self.model.symbols[index].references.push(span);
+        let function = self.model.scopes[self.scope].function;
+        let declared_in = self.model.scopes[self.model.symbols[index].scope].function;
+        if function != declared_in {
+            self.model.symbols[index].captured = true;
+            let upvalues = &mut self.model.scopes[function].upvalues;
+            if !upvalues.contains(&index) {
+                upvalues.push(index);
+            }
+        }
+        Some(index)
+    }
+
+    fn block(&mut self, block: &Block) {
+        let saved = self.enter(ScopeKind::Block, block.span);
+        self.stats(&block.stats);
+        self.exit(saved);
+    }
+
+    fn stats(&mut self, stats: &[Stat]) {
+        for stat in stats {
+            self.stat(stat);
+        }
+    }
+
+    fn stat(&mut self, stat: &Stat) {
+        match &stat.kind {
+            StatKind::Local { names, exprs, .. } => {
+                self.exprs(exprs);
+                for (i, name) in names.iter().enumerate() {
+                    let value = exprs.get(i);
+                    let require = value.and_then(Expr::as_require);
+                    let kind = match value.map(|e| &e.kind) {
+                        _ if require.is_some() => SymbolKind::Import,
+                        Some(ExprKind::Function(_)) => SymbolKind::Function,
+                        _ => SymbolKind::Local,
+                    };
+                    self.declare(name, kind, stat.span, stat.span.end);
+                    let symbol = self.model.symbols.last_mut().unwrap();
+                    symbol.require = require.map(|path| path.span);
+                }
+            }
+            StatKind::LocalFunction { name, body } => {
+                // Visible inside its own body, so it can recurse
+                self.declare(name, SymbolKind::Function, stat.span, name.span.end);
+                self.function(body, None);
+            }
+            StatKind::Function {
+                path,
+                is_method,
+                body,
+            } => {
+                let root = &path[0];
+                match self.reference(&root.name, root.span) {
+                    Some(symbol) if path.len() == 2 && self.scope == 0 => {
+                        self.fields.entry(symbol).or_default().push(Export {
+                            name: path[1].name.clone(),
+                            span: path[1].span,
+                            is_function: true,
+                        });
+                    }
+                    Some(_) => {}
+                    None if path.len() == 1 => {
+                        self.model.global_writes.push(root.clone());
+                        self.model.global_functions.push(root.clone());
+                    }
+                    None => self.model.global_reads.push(root.clone()),
+                }
+                let method = is_method.then(|| path.last().unwrap());
+                self.function(body, method);
+            }
+            StatKind::Assign { targets, exprs } => {
+                self.exprs(exprs);
+                for (i, target) in targets.iter().enumerate() {
+                    self.assign(target, exprs.get(i));
+                }
+            }
+            StatKind::Call(expr) => self.expr(expr),
+            StatKind::Do(block) => self.block(block),
+            StatKind::While { cond, block } => {
+                self.expr(cond);
+                self.block(block);
+            }
+            StatKind::Repeat { block, cond } => {
+                // The condition can see the body's locals
+                let saved =
+                    self.enter(ScopeKind::Block, Span::new(block.span.start, cond.span.end));
+                self.stats(&block.stats);
+                self.expr(cond);
+                self.exit(saved);
+            }
+            StatKind::If {
+                clauses,
+                else_block,
+            } => {
+                for (cond, block) in clauses {
+                    self.expr(cond);
+                    self.block(block);
+                }
+                if let Some(block) = else_block {
+                    self.block(block);
+                }
+            }
+            StatKind::NumericFor {
+                var,
+                start,
+                end,
+                step,
+                block,
+            } => {
+                self.expr(start);
+                self.expr(end);
+                if let Some(step) = step {
+                    self.expr(step);
+                }
+                let saved = self.enter(ScopeKind::Block, block.span);
+                self.declare(var, SymbolKind::Local, var.span, block.span.start);
+                self.stats(&block.stats);
+                self.exit(saved);
+            }
+            StatKind::GenericFor {
+                names,
+                exprs,
+                block,
+            } => {
+                self.exprs(exprs);
+                let saved = self.enter(ScopeKind::Block, block.span);
+                for name in names {
+                    self.declare(name, SymbolKind::Local, name.span, block.span.start);
+                }
+                self.stats(&block.stats);
+                self.exit(saved);
+            }
+            StatKind::Return(exprs) => self.exprs(exprs),
+            StatKind::Break | StatKind::Goto(_) | StatKind::Label(_) | StatKind::Empty => {}
+        }
+    }
+
+    fn assign(&mut self, target: &Expr, value: Option<&Expr>) {
+        match &target.kind {
+            ExprKind::Name(name) => {
+                if self.reference(name, target.span).is_none() {
+                    self.model.global_writes.push(Name {
+                        name: name.clone(),
+                        span: target.span,
+                    });
+                }
+            }
+            ExprKind::Field { obj, name } => {
+                self.expr(obj);
+                // `M.name = ...` on a top-level table, for `return M`
+                let local = obj
+                    .as_name()
+                    .and_then(|n| {
+                        self.active
+                            .iter()
+                            .rev()
+                            .find(|&&i| self.model.symbols[i].name == n)
+                    })
+                    .copied();
+                if let Some(symbol) = local.filter(|_| self.scope == 0) {
+                    self.fields.entry(symbol).or_default().push(Export {
+                        name: name.name.clone(),
+                        span: name.span,
+                        is_function: matches!(value.map(|v| &v.kind), Some(ExprKind::Function(_))),
+                    });
+                }
+            }
+            _ => self.expr(target),
+        }
+    }
+
+    fn function(&mut self, body: &FunctionBody, method: Option<&Name>) {
+        let saved = self.enter(ScopeKind::Function, body.span);
+        if let Some(method) = method {
+            // The implicit `self` parameter of `function T:method()`
+            let name = Name {
+                name: "self".to_string(),
+                span: method.span,
+            };
+            self.declare(&name, SymbolKind::Parameter, method.span, body.span.start);
+        }
+        for param in &body.params {
+            self.declare(param, SymbolKind::Parameter, param.span, body.span.start);
+        }
+        self.stats(&body.block.stats);
+        self.exit(saved);
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Name(name) => {
+                if self.reference(name, expr.span).is_none() {
+                    self.model.global_reads.push(Name {
+                        name: name.clone(),
+                        span: expr.span,
+                    });
+                }
+            }
+            ExprKind::Function(body) => self.function(body, None),
+            ExprKind::Table(fields) => {
+                for field in fields {
+                    match field {
+                        Field::Named(_, value) | Field::Positional(value) => self.expr(value),
+                        Field::Indexed(key, value) => {
+                            self.expr(key);
+                            self.expr(value);
+                        }
+                    }
+                }
+            }
+            ExprKind::Binary { lhs, rhs, .. } => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            ExprKind::Unary { expr, .. } | ExprKind::Paren(expr) => self.expr(expr),
+            ExprKind::Index { obj, key } => {
+                self.expr(obj);
+                self.expr(key);
+            }
+            ExprKind::Field { obj, .. } => self.expr(obj),
+            ExprKind::Call { callee, args } => {
+                if let Some(path) = expr.as_require() {
+                    self.model.requires.push(Require {
+                        path: string_value(self.src, path.span).to_string(),
+                        span: path.span,
+                    });
+                }
+                self.expr(callee);
+                self.exprs(args);
+            }
+            ExprKind::MethodCall { obj, args, .. } => {
+                self.expr(obj);
+                self.exprs(args);
+            }
+            ExprKind::Nil
+            | ExprKind::True
+            | ExprKind::False
+            | ExprKind::Number
+            | ExprKind::String
+            | ExprKind::Vararg
+            | ExprKind::Error => {}
+        }
+    }
+
+    // ---- template markup -------------------------------------------------
+
+    fn template_nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.template_node(node);
+        }
+    }
+
+    fn template_node(&mut self, node: &Node) {
+        match node {
+            Node::Expression(expr) if expr.closed => self.fragment(expr.content),
+            Node::Element(element) => {
+                for attribute in &element.attributes {
+                    let Some(value) = &attribute.value else {
+                        continue;
+                    };
+                    for expr in value.expressions() {
+                        if expr.closed {
+                            self.fragment(expr.content);
+                        }
+                    }
+                }
+                if element.is_component() {
+                    // `<Card>` and `<UI.Button>` use the import they're named after
+                    let name = element.name.split('.').next().unwrap_or_default();
+                    let span = Span::new(
+                        element.name_span.start,
+                        element.name_span.start + name.len(),
+                    );
+                    if self.reference(name, span).is_none() {
+                        self.model.unbound_components.push(Name {
+                            name: name.to_string(),
+                            span,
+                        });
+                    }
+                }
+                self.template_nodes(&element.children);
+            }
+            Node::Block(block) => self.template_block(block),
+            Node::Directive(tag) if tag.closed => self.directive(tag),
+            _ => {}
+        }
+    }
+
+    /// Each branch of a block is its own scope. `{#each}` bindings are only
+    /// visible in the loop body, not in `{:empty}`.
+    fn template_block(&mut self, block: &TemplateBlock) {
+        for (i, branch) in block.branches.iter().enumerate() {
+            let tag = &branch.tag;
+            let end = block
+                .branches
+                .get(i + 1)
+                .map(|next| next.tag.span.start)
+                .or(block.close.as_ref().map(|close| close.span.start))
+                .unwrap_or(block.span.end);
+
+            let mut bindings = Vec::new();
+            match (tag.keyword.as_str(), tag.expression) {
+                ("if" | "else if", Some(cond)) if tag.closed => self.fragment(cond),
+                ("each", Some(each)) if tag.closed && i == 0 => {
+                    if let Some((iterable, names)) = split_each(self.src, each) {
+                        self.fragment(iterable);
+                        bindings = names;
+                    }
+                }
+                _ => {}
+            }
+
+            let saved = self.enter(ScopeKind::Block, Span::new(tag.span.end, end));
+            for (i, span) in bindings.into_iter().enumerate() {
+                let name = Name {
+                    name: span.text(self.src).to_string(),
+                    span,
+                };
+                let kind = if i == 0 {
+                    SymbolKind::EachItem
+                } else {
+                    SymbolKind::EachIndex
+                };
+                self.declare(&name, kind, tag.span, tag.span.end);
+            }
+            self.template_nodes(&branch.children);
+            self.exit(saved);
+        }
+    }
+
+    fn directive(&mut self, tag: &Tag) {
+        let Some(expression) = tag.expression else {
+            return;
+        };
+        match tag.keyword.as_str() {
+            "html" | "render" => self.fragment(expression),
+            "local" => {
+                // `{@local a, b = x, y}`; a missing `=` is a grammar error
+                let tokens: Vec<_> = Lexer::at(&self.src[..expression.end], expression.start)
+                    .filter(|t| t.kind != TokenKind::Comment)
+                    .collect();
+                let Some(assign) = tokens
+                    .iter()
+                    .position(|t| t.kind == TokenKind::Symbol && t.text(self.src) == "=")
+                else {
+                    return;
+                };
+                let values = Span::new(tokens[assign].span.end, expression.end);
+                let mut local = None;
+                if !values.text(self.src).trim().is_empty() {
+                    let (exprs, errors) = parse_expression_list(self.src, values, self.version);
+                    self.exprs(&exprs);
+                    if errors.is_empty() {
+                        local = Some(TemplateLocal {
+                            names: Vec::new(),
+                            values: exprs,
+                        });
+                    }
+                    self.model.errors.extend(errors);
+                }
+                for token in &tokens[..assign] {
+                    if token.kind == TokenKind::Name {
+                        if let Some(local) = &mut local {
+                            local.names.push(token.span);
+                        }
+                        let name = Name {
+                            name: token.text(self.src).to_string(),
+                            span: token.span,
+                        };
+                        let kind = match tokens.get(assign + 1) {
+                            Some(t) if t.text(self.src) == "function" => SymbolKind::Function,
+                            _ => SymbolKind::Local,
+                        };
+                        self.declare(&name, kind, tag.span, tag.span.end);
+                    }
+                }
+                if let Some(local) = local {
+                    self.model.template_locals.insert(expression, local);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A Lua expression in the markup
+    fn fragment(&mut self, span: Span) {
+        // Empty expressions are reported on their own
+        if span.text(self.src).trim().is_empty() {
+            return;
+        }
+        let (expr, errors) = parse_expression(self.src, span, self.version);
+        self.expr(&expr);
+        if errors.is_empty() {
+            self.model.expressions.insert(span, expr);
+        }
+        self.model.errors.extend(errors);
+    }
+
+    /// Record the fields of a chunk's returned table
+    fn exports(&mut self, block: &Block) {
+        let Some(Stat {
+            kind: StatKind::Return(exprs),
+            ..
+        }) = block.stats.last()
+        else {
+            return;
+        };
+        match exprs.first().map(|e| &e.kind) {
+            Some(ExprKind::Table(fields)) => {
+                for field in fields {
+                    if let Field::Named(name, value) = field {
+                        let is_function = match &value.kind {
+                            ExprKind::Function(_) => true,
+                            ExprKind::Name(n) => self.active.iter().any(|&i| {
+                                let symbol = &self.model.symbols[i];
+                                symbol.name == *n && symbol.kind == SymbolKind::Function
+                            }),
+                            _ => false,
+                        };
+                        self.model.exports.push(Export {
+                            name: name.name.clone(),
+                            span: name.span,
+                            is_function,
+                        });
+                    }
+                }
+            }
+            Some(ExprKind::Name(name)) => {
+                let symbol = self
+                    .active
+                    .iter()
+                    .rev()
+                    .find(|&&i| self.model.symbols[i].name == *name);
+                if let Some(fields) = symbol.and_then(|i| self.fields.get(i)) {
+                    self.model.exports.extend(fields.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(src: &str) -> ScopeModel {
+        let chunk = parse_chunk(src, Span::new(0, src.len()), LuaVersion::Lua54);
+        assert!(chunk.errors.is_empty(), "{:?}", chunk.errors);
+        ScopeModel::build(src, &[(Span::new(0, src.len()), &chunk.block)])
+    }
+
+    fn names(symbols: Vec<&Symbol>) -> Vec<&str> {
+        symbols.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_scopes_and_shadowing() {
+        let src = "local a = 1\nlocal function f(x)\n  local a = x\n  return a\nend\nprint(a, g)";
+        let model = model(src);
+
+        let inner = src.find("return a").unwrap();
+        assert_eq!(names(model.visible_at(inner)), vec!["a", "x", "f"]);
+        // The inner `a` shadows the outer one
+        let symbol = model.symbol_at(inner + "return ".len()).unwrap();
+        assert_eq!(symbol.span.start, src.find("local a = x").unwrap() + 6);
+
+        let end = src.len();
+        assert_eq!(names(model.visible_at(end)), vec!["f", "a"]);
+        assert_eq!(model.symbols[0].references.len(), 1);
+        let globals: Vec<_> = model.global_reads.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(globals, vec!["print", "g"]);
+
+        // A local isn't visible in its own initializer
+        let src = "local x = x";
+        let model = self::model(src);
+        assert!(model.symbols[0].references.is_empty());
+        assert_eq!(model.global_reads[0].name, "x");
+    }
+
+    #[test]
+    fn test_upvalues() {
+        let src = "local count = 0\nlocal function inc()\n  count = count + 1\nend";
+        let model = model(src);
+        assert!(model.symbols[0].captured);
+        let function = model
+            .scopes
+            .iter()
+            .find(|s| s.kind == ScopeKind::Function)
+            .unwrap();
+        assert_eq!(function.upvalues, vec![0]);
+    }
+
+    #[test]
+    fn test_requires_and_exports() {
+        let src = r#"local Card = require("components/Card")
+local M = {}
+function M.greet(name) return "hi " .. name end
+M.version = 1
+return M"#;
+        let model = model(src);
+
+        let card = model.import("Card").unwrap();
+        assert_eq!(model.require_path(card), Some("components/Card"));
+        let offset = src.find("components").unwrap();
+        assert_eq!(model.require_at(offset).unwrap().path, "components/Card");
+
+        let exports: Vec<_> = model
+            .exports
+            .iter()
+            .map(|e| (e.name.as_str(), e.is_function))
+            .collect();
+        assert_eq!(exports, vec![("greet", true), ("version", false)]);
+    }
+
+    #[test]
+    fn test_template_scripts() {
+        let src = "<script>\nlocal title = helper()\n</script>\n<h1>{title}</h1>\n<script module>\nlocal function helper() end\n</script>";
+        let tree = crate::syntax::parse(src);
+        let model = ScopeModel::for_template(&tree, src, LuaVersion::Lua54);
+
+        // Module script locals are visible to the instance script before it
+        let helper = model.symbols.iter().find(|s| s.name == "helper").unwrap();
+        assert_eq!(helper.references.len(), 1);
+        let markup = src.find("{title}").unwrap();
+        assert_eq!(names(model.visible_at(markup)), vec!["title", "helper"]);
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Virtual Lua documents generated from templates.
+//!
+//! All Lua in a template is stitched into one chunk that Lua tooling can
+//! understand:
+//!
+//! ```lua
+//! -- <script module> contents
+//! local function __luat_template(props)
+//!   -- <script> contents
+//!   local _ = expr                          -- {expr}, attribute values, {@html expr}
+//!   if cond then ... elseif c then ... else ... end   -- {#if}
+//!   for i, item in ipairs(items) do ... end         -- {#each items as item, i}
+//!   local name = value                      -- {@local name = value}
+//! end
+//! ```
+//!
+//! Control flow blocks become nested Lua blocks, so `{#each}` loop variables
+//! and `{@local}` declarations are scoped exactly like they are at runtime.
+//! Every piece of template source copied into the chunk is recorded in a
+//! [`SourceMap`], so offsets can be mapped in both directions.
+
+use super::lexer::{Lexer, TokenKind};
+use crate::syntax::{AttributeValue, Block, Node, ScriptKind, Span, SyntaxTree, Tag};
+
+/// A template span copied verbatim to `virtual_start` in the virtual document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    pub template: Span,
+    pub virtual_start: usize,
+}
+
+impl Mapping {
+    fn virtual_span(&self) -> Span {
+        Span::new(
+            self.virtual_start,
+            self.virtual_start + self.template.end - self.template.start,
+        )
+    }
+}
+
+/// Bidirectional offset map between a template and its virtual Lua document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    /// Sorted by virtual offset. Template offsets are not necessarily in
+    /// order, since instance scripts are moved after module scripts.
+    mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// Map a template offset into the virtual document. Offsets in template
+    /// markup that has no Lua counterpart map to `None`.
+    pub fn to_virtual(&self, offset: usize) -> Option<usize> {
+        self.mappings
+            .iter()
+            .find(|m| m.template.start <= offset && offset <= m.template.end)
+            .map(|m| m.virtual_start + offset - m.template.start)
+    }
+
+    /// Map a virtual document offset back to the template. Offsets in
+    /// generated glue code map to `None`.
+    pub fn to_template(&self, offset: usize) -> Option<usize> {
+        self.mappings
+            .iter()
+            .find(|m| {
+                let span = m.virtual_span();
+                span.start <= offset && offset <= span.end
+            })
+            .map(|m| m.template.start + offset - m.virtual_start)
+    }
+
+    /// Map a virtual span back to the template, if both ends are mapped
+    pub fn span_to_template(&self, span: Span) -> Option<Span> {
+        Some(Span::new(
+            self.to_template(span.start)?,
+            self.to_template(span.end)?,
+        ))
+    }
+}
+
+/// A template's Lua code as a single chunk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VirtualDocument {
+    pub text: String,
+    pub source_map: SourceMap,
+}
+
+impl VirtualDocument {
+    /// Generate the virtual document for a parsed template
+    pub fn build(tree: &SyntaxTree, text: &str) -> Self {
+        let mut builder = Builder {
+            src: text,
+            out: VirtualDocument::default(),
+            indent: 0,
+        };
+
+        let scripts = tree.lua_scripts();
+        for script in scripts.iter().filter(|s| s.kind() == ScriptKind::LuaModule) {
+            builder.mapped(script.content);
+            builder.newline();
+        }
+
+        builder.line("local function __luat_template(props)");
+        for script in scripts.iter().filter(|s| s.kind() == ScriptKind::Lua) {
+            builder.mapped(script.content);
+            builder.newline();
+        }
+        builder.indent += 1;
+        builder.nodes(&tree.nodes);
+        builder.indent -= 1;
+        builder.line("end");
+
+        builder.out
+    }
+}
+
+struct Builder<'a> {
+    src: &'a str,
+    out: VirtualDocument,
+    indent: usize,
+}
+
+impl Builder<'_> {
+    fn push(&mut self, code: &str) {
+        if self.out.text.ends_with('\n') || self.out.text.is_empty() {
+            for _ in 0..self.indent {
+                self.out.text.push_str("  ");
+            }
+        }
+        self.out.text.push_str(code);
+    }
+
+    fn newline(&mut self) {
+        self.out.text.push('\n');
+    }
+
+    fn line(&mut self, code: &str) {
+        self.push(code);
+        self.newline();
+    }
+
+    /// Copy template source into the document, recording the mapping
+    fn mapped(&mut self, span: Span) {
+        self.push("");
+        self.out.source_map.mappings.push(Mapping {
+            template: span,
+            virtual_start: self.out.text.len(),
+        });
+        self.out.text.push_str(span.text(self.src));
+    }
+
+    /// `local _ = <expression>`
+    fn expression(&mut self, content: Span) {
+        if content.text(self.src).trim().is_empty() {
+            return;
+        }
+        self.push("local _ = ");
+        self.mapped(content);
+        self.newline();
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Expression(expr) => self.expression(expr.content),
+                Node::Element(element) => {
+                    for attribute in &element.attributes {
+                        for expr in attribute.value.iter().flat_map(AttributeValue::expressions) {
+                            self.expression(expr.content);
+                        }
+                    }
+                    self.nodes(&element.children);
+                }
+                Node::Block(block) => self.block(block),
+                Node::Directive(tag) => self.directive(tag),
+                Node::Text(_)
+                | Node::Comment(_)
+                | Node::Script(_)
+                | Node::StrayTag(_)
+                | Node::StrayEndTag(_) => {}
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        match block.keyword.as_str() {
+            "if" => {
+                for (i, branch) in block.branches.iter().enumerate() {
+                    let condition = branch.tag.expression;
+                    match (i, branch.tag.keyword.as_str(), condition) {
+                        (0, _, Some(condition)) => self.clause("if ", condition, " then"),
+                        (0, _, None) => self.line("if true then"),
+                        (_, "else if", Some(condition)) => {
+                            self.clause("elseif ", condition, " then")
+                        }
+                        _ => self.line("else"),
+                    }
+                    self.indent += 1;
+                    self.nodes(&branch.children);
+                    self.indent -= 1;
+                }
+                self.line("end");
+            }
+            "each" => {
+                let open = &block.branches[0];
+                self.each_header(&open.tag);
+                self.indent += 1;
+                self.nodes(&open.children);
+                self.indent -= 1;
+                self.line("end");
+
+                // `{:empty}` and anything else runs outside the loop
+                for branch in &block.branches[1..] {
+                    self.line("do");
+                    self.indent += 1;
+                    self.nodes(&branch.children);
+                    self.indent -= 1;
+                    self.line("end");
+                }
+            }
+            _ => {
+                // Unknown block: keep its contents scoped
+                self.line("do");
+                self.indent += 1;
+                for branch in &block.branches {
+                    self.nodes(&branch.children);
+                }
+                self.indent -= 1;
+                self.line("end");
+            }
+        }
+    }
+
+    fn clause(&mut self, before: &str, span: Span, after: &str) {
+        self.push(before);
+        self.mapped(span);
+        self.push(after);
+        self.newline();
+    }
+
+    /// `for index, item in ipairs(iterable) do`
+    fn each_header(&mut self, tag: &Tag) {
+        let Some((iterable, bindings)) = tag.expression.and_then(|e| split_each(self.src, e))
+        else {
+            // Still evaluate what's there so tooling sees it
+            match tag.expression {
+                Some(span) => self.clause("for _ in ipairs(", span, ") do"),
+                None => self.line("do"),
+            }
+            return;
+        };
+
+        self.push("for ");
+        match bindings.get(1) {
+            Some(index) => self.mapped(*index),
+            None => self.push("_"),
+        }
+        self.push(", ");
+        match bindings.first() {
+            Some(item) => self.mapped(*item),
+            None => self.push("_"),
+        }
+        self.push(" in ipairs(");
+        self.mapped(iterable);
+        self.push(") do");
+        self.newline();
+    }
+
+    fn directive(&mut self, tag: &Tag) {
+        let Some(expression) = tag.expression else {
+            return;
+        };
+        match tag.keyword.as_str() {
+            "local" => {
+                self.push("local ");
+                self.mapped(expression);
+                self.newline();
+            }
+            "html" | "render" => self.expression(expression),
+            _ => {}
+        }
+    }
+}
+
+/// Split an `{#each}` expression into the iterable and the names bound by
+/// `as`, e.g. `items as item, i` into `items` and `[item, i]`
+pub fn split_each(src: &str, expression: Span) -> Option<(Span, Vec<Span>)> {
+    let end = expression.end;
+    let mut depth = 0usize;
+    let mut as_token = None;
+
+    for token in Lexer::at(&src[..end], expression.start) {
+        match (token.kind, token.text(src)) {
+            (TokenKind::Symbol, "(" | "[" | "{") => depth += 1,
+            (TokenKind::Symbol, ")" | "]" | "}") => depth = depth.saturating_sub(1),
+            (TokenKind::Name, "as") if depth == 0 => as_token = Some(token.span),
+            _ => {}
+        }
+    }
+
+    let as_span = as_token?;
+    let iterable = Span::new(expression.start, as_span.start);
+    let iterable = Span::new(
+        iterable.start,
+        iterable.start + iterable.text(src).trim_end().len(),
+    );
+
+    let bindings = Lexer::at(&src[..end], as_span.end)
+        .filter(|token| token.kind == TokenKind::Name)
+        .map(|token| token.span)
+        .collect();
+
+    Some((iterable, bindings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse;
+
+    fn build(text: &str) -> VirtualDocument {
+        VirtualDocument::build(&parse(text), text)
+    }
+
+    #[test]
+    fn test_virtual_document_structure() {
+        let text = r#"<script module>
+local Card = require("Card")
+</script>
+<script>
+local items = props.items
+</script>
+{#each items as item, i}
+  {@local label = item.name}
+  <Card title={label} />
+{:empty}
+  <p>{#if props.loading}Loading{:else}None{/if}</p>
+{/each}"#;
+
+        let doc = build(text);
+        assert_eq!(
+            doc.text,
+            r#"
+local Card = require("Card")
+
+local function __luat_template(props)
+
+local items = props.items
+
+  for i, item in ipairs(items) do
+    local label = item.name
+    local _ = label
+  end
+  do
+    if props.loading then
+    else
+    end
+  end
+end
+"#
+        );
+    }
+
+    #[test]
+    fn test_source_map_round_trip() {
+        let text = "<p class=\"a {cls}\">{#each list as row}{row.title}{/each}</p>";
+        let doc = build(text);
+
+        for name in ["cls", "list", "row.title"] {
+            let offset = text.find(name).unwrap();
+            let virtual_offset = doc.source_map.to_virtual(offset).unwrap();
+            assert_eq!(&doc.text[virtual_offset..virtual_offset + name.len()], name);
+            assert_eq!(doc.source_map.to_template(virtual_offset), Some(offset));
+        }
+
+        // Markup and generated glue are unmapped
+        assert_eq!(doc.source_map.to_virtual(text.find("class").unwrap()), None);
+        let glue = doc.text.find("ipairs").unwrap();
+        assert_eq!(doc.source_map.to_template(glue), None);
+    }
+
+    #[test]
+    fn test_split_each() {
+        let src = "pairs_of(t, {as = 1}) as key, value";
+        let (iterable, bindings) = split_each(src, Span::new(0, src.len())).unwrap();
+        assert_eq!(iterable.text(src), "pairs_of(t, {as = 1})");
+        let names: Vec<_> = bindings.iter().map(|s| s.text(src)).collect();
+        assert_eq!(names, vec!["key", "value"]);
+
+        assert_eq!(split_each("items", Span::new(0, 5)), None);
+    }
+}
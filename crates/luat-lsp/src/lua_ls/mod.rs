@@ -0,0 +1,13 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Optional proxy to a locally installed lua-language-server.
+//!
+//! Each open template is mirrored to the child server as its virtual Lua
+//! document. Requests in Lua regions are forwarded with positions mapped
+//! into the virtual document, and results are mapped back.
+
+mod proxy;
+mod rpc;
+
+pub use proxy::LuaLanguageServer;
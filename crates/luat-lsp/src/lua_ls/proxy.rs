@@ -0,0 +1,662 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tower_lsp::lsp_types::*;
+
+use super::rpc::{Notification, RpcClient};
+use crate::lua::VirtualDocument;
+use crate::syntax::Span;
+
+/// Environment variable naming the lua-language-server executable.
+/// Set it to an empty string or `off` to disable the proxy.
+const EXECUTABLE_ENV: &str = "LUAT_LUA_LANGUAGE_SERVER";
+const EXECUTABLE_NAME: &str = "lua-language-server";
+
+/// Give up on requests the child server doesn't answer in time, including
+/// the handshake and shutdown
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maps a span in a template to a range in the client's position encoding
+pub type SpanToRange<'a> = &'a (dyn Fn(&Url, Span) -> Option<Range> + Sync);
+
+/// A virtual Lua document as last sent to the child server
+struct VirtualFile {
+    uri: Url,
+    version: i32,
+    /// Version of the template the document was built from
+    template_version: Option<i32>,
+    document: VirtualDocument,
+}
+
+pub struct LuaLanguageServer {
+    rpc: RpcClient,
+    /// Keyed by template URI
+    files: Mutex<HashMap<Url, VirtualFile>>,
+    /// Killed when the proxy is dropped
+    _child: Option<Child>,
+}
+
+impl LuaLanguageServer {
+    /// Find the lua-language-server executable from the environment or `PATH`
+    pub fn find_executable() -> Option<PathBuf> {
+        if let Some(configured) = std::env::var_os(EXECUTABLE_ENV) {
+            if configured.is_empty() || configured == "off" {
+                return None;
+            }
+            return Some(PathBuf::from(configured));
+        }
+
+        let names: &[&str] = if cfg!(windows) {
+            &["lua-language-server.exe"]
+        } else {
+            &[EXECUTABLE_NAME]
+        };
+        std::env::split_paths(&std::env::var_os("PATH")?)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Spawn the server and complete the `initialize` handshake
+    pub async fn spawn(
+        program: &Path,
+        root: Option<Url>,
+    ) -> io::Result<(Self, mpsc::UnboundedReceiver<Notification>)> {
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (mut server, notifications) = Self::connect(stdout, stdin, root).await?;
+        server._child = Some(child);
+        Ok((server, notifications))
+    }
+
+    /// Connect to a server over arbitrary streams and complete the
+    /// `initialize` handshake
+    pub async fn connect<R, W>(
+        reader: R,
+        writer: W,
+        root: Option<Url>,
+    ) -> io::Result<(Self, mpsc::UnboundedReceiver<Notification>)>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let (rpc, notifications) = RpcClient::new(reader, writer);
+
+        #[allow(deprecated)] // root_uri is still what lua-language-server reads
+        let params = InitializeParams {
+            process_id: Some(std::process::id()),
+            root_uri: root.clone(),
+            workspace_folders: root.map(|uri| {
+                vec![WorkspaceFolder {
+                    name: uri.path().to_string(),
+                    uri,
+                }]
+            }),
+            capabilities: ClientCapabilities::default(),
+            ..Default::default()
+        };
+        // A server that never answers is no server at all
+        let initialize = rpc.request("initialize", serde_json::to_value(params)?);
+        tokio::time::timeout(REQUEST_TIMEOUT, initialize)
+            .await
+            .map_err(|_| {
+                io::Error::new(io::ErrorKind::TimedOut, "initialize was not answered")
+            })??;
+        rpc.notify("initialized", json!({})).await?;
+
+        let server = Self {
+            rpc,
+            files: Mutex::default(),
+            _child: None,
+        };
+        Ok((server, notifications))
+    }
+
+    /// Send the virtual document built from version `template_version` of a
+    /// template
+    pub async fn update(
+        &self,
+        template: &Url,
+        template_version: Option<i32>,
+        document: VirtualDocument,
+    ) {
+        let (method, params) = {
+            let mut files = self.files.lock().unwrap();
+            match files.get_mut(template) {
+                Some(file) => {
+                    file.version += 1;
+                    file.template_version = template_version;
+                    file.document = document;
+                    let params = DidChangeTextDocumentParams {
+                        text_document: VersionedTextDocumentIdentifier::new(
+                            file.uri.clone(),
+                            file.version,
+                        ),
+                        content_changes: vec![TextDocumentContentChangeEvent {
+                            range: None,
+                            range_length: None,
+                            text: file.document.text.clone(),
+                        }],
+                    };
+                    ("textDocument/didChange", serde_json::to_value(params))
+                }
+                None => {
+                    let uri = virtual_uri(template);
+                    let params = DidOpenTextDocumentParams {
+                        text_document: TextDocumentItem::new(
+                            uri.clone(),
+                            "lua".to_string(),
+                            0,
+                            document.text.clone(),
+                        ),
+                    };
+                    files.insert(
+                        template.clone(),
+                        VirtualFile {
+                            uri,
+                            version: 0,
+                            template_version,
+                            document,
+                        },
+                    );
+                    ("textDocument/didOpen", serde_json::to_value(params))
+                }
+            }
+        };
+
+        if let Ok(params) = params {
+            self.notify(method, params).await;
+        }
+    }
+
+    /// Stop mirroring a template
+    pub async fn close(&self, template: &Url) {
+        let Some(file) = self.files.lock().unwrap().remove(template) else {
+            return;
+        };
+        let params = DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(file.uri),
+        };
+        if let Ok(params) = serde_json::to_value(params) {
+            self.notify("textDocument/didClose", params).await;
+        }
+    }
+
+    pub async fn shutdown(&self) {
+        let shutdown = self.rpc.request("shutdown", Value::Null);
+        let _ = tokio::time::timeout(REQUEST_TIMEOUT, shutdown).await;
+        self.notify("exit", Value::Null).await;
+    }
+
+    /// Map diagnostics published for a virtual document back to its
+    /// template, returning the template version they belong to.
+    /// Diagnostics in generated code are dropped.
+    pub fn map_diagnostics(
+        &self,
+        params: PublishDiagnosticsParams,
+        to_range: SpanToRange,
+    ) -> Option<(Url, Option<i32>, Vec<Diagnostic>)> {
+        let (template, version) = {
+            let files = self.files.lock().unwrap();
+            let (template, file) = files.iter().find(|(_, file)| file.uri == params.uri)?;
+            // A report for an older virtual document would land at the
+            // wrong positions
+            if params
+                .version
+                .is_some_and(|version| version != file.version)
+            {
+                return None;
+            }
+            (template.clone(), file.template_version)
+        };
+        let diagnostics = params
+            .diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                diagnostic.range = self.map_range(&params.uri, diagnostic.range, to_range)?.1;
+                diagnostic.source = Some("luat (lua-language-server)".to_string());
+                diagnostic.related_information = diagnostic.related_information.map(|info| {
+                    info.into_iter()
+                        .filter_map(|mut related| {
+                            related.location = self.map_location(related.location, to_range)?;
+                            Some(related)
+                        })
+                        .collect()
+                });
+                Some(diagnostic)
+            })
+            .collect();
+        Some((template, version, diagnostics))
+    }
+
+    pub async fn completion(
+        &self,
+        template: &Url,
+        offset: usize,
+        to_range: SpanToRange<'_>,
+    ) -> Option<Vec<CompletionItem>> {
+        let position = self.position_params(template, offset)?;
+        let response: CompletionResponse = self
+            .request(
+                "textDocument/completion",
+                json!({
+                    "textDocument": position.text_document,
+                    "position": position.position,
+                }),
+            )
+            .await?;
+
+        let items = match response {
+            CompletionResponse::Array(items) => items,
+            CompletionResponse::List(list) => list.items,
+        };
+        let virtual_uri = position.text_document.uri;
+        Some(
+            items
+                .into_iter()
+                .map(|item| self.map_completion(&virtual_uri, item, to_range))
+                .collect(),
+        )
+    }
+
+    pub async fn hover(
+        &self,
+        template: &Url,
+        offset: usize,
+        to_range: SpanToRange<'_>,
+    ) -> Option<Hover> {
+        let position = self.position_params(template, offset)?;
+        let mut hover: Hover = self
+            .request("textDocument/hover", serde_json::to_value(&position).ok()?)
+            .await?;
+        hover.range = hover
+            .range
+            .and_then(|range| self.map_range(&position.text_document.uri, range, to_range))
+            .map(|(_, range)| range);
+        Some(hover)
+    }
+
+    pub async fn definition(
+        &self,
+        template: &Url,
+        offset: usize,
+        to_range: SpanToRange<'_>,
+    ) -> Option<GotoDefinitionResponse> {
+        let position = self.position_params(template, offset)?;
+        let response: GotoDefinitionResponse = self
+            .request(
+                "textDocument/definition",
+                serde_json::to_value(&position).ok()?,
+            )
+            .await?;
+
+        let locations: Vec<Location> = match response {
+            GotoDefinitionResponse::Scalar(location) => vec![location],
+            GotoDefinitionResponse::Array(locations) => locations,
+            GotoDefinitionResponse::Link(links) => links
+                .into_iter()
+                .map(|link| Location::new(link.target_uri, link.target_selection_range))
+                .collect(),
+        };
+        let locations: Vec<_> = locations
+            .into_iter()
+            .filter_map(|location| self.map_location(location, to_range))
+            .collect();
+        (!locations.is_empty()).then_some(GotoDefinitionResponse::Array(locations))
+    }
+
+    pub async fn signature_help(&self, template: &Url, offset: usize) -> Option<SignatureHelp> {
+        let position = self.position_params(template, offset)?;
+        self.request(
+            "textDocument/signatureHelp",
+            serde_json::to_value(&position).ok()?,
+        )
+        .await
+    }
+
+    // ---- helpers ---------------------------------------------------------
+
+    async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Option<T> {
+        let result = tokio::time::timeout(REQUEST_TIMEOUT, self.rpc.request(method, params)).await;
+        match result {
+            Ok(Ok(value)) => serde_json::from_value::<Option<T>>(value).ok()?,
+            Ok(Err(err)) => {
+                tracing::debug!("lua-language-server {} failed: {}", method, err);
+                None
+            }
+            Err(_) => {
+                tracing::debug!("lua-language-server {} timed out", method);
+                None
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) {
+        if let Err(err) = self.rpc.notify(method, params).await {
+            tracing::debug!("lua-language-server {} failed: {}", method, err);
+        }
+    }
+
+    /// The virtual document position for a template offset, if the offset
+    /// is inside Lua code
+    fn position_params(&self, template: &Url, offset: usize) -> Option<TextDocumentPositionParams> {
+        let files = self.files.lock().unwrap();
+        let file = files.get(template)?;
+        let virtual_offset = file.document.source_map.to_virtual(offset)?;
+        Some(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(file.uri.clone()),
+            position: offset_to_position(&file.document.text, virtual_offset),
+        })
+    }
+
+    fn template_for(&self, virtual_uri: &Url) -> Option<Url> {
+        let files = self.files.lock().unwrap();
+        files
+            .iter()
+            .find(|(_, file)| &file.uri == virtual_uri)
+            .map(|(template, _)| template.clone())
+    }
+
+    /// Map a range in a virtual document to its template
+    fn map_range(&self, uri: &Url, range: Range, to_range: SpanToRange) -> Option<(Url, Range)> {
+        let (template, span) = {
+            let files = self.files.lock().unwrap();
+            let (template, file) = files.iter().find(|(_, file)| &file.uri == uri)?;
+            let text = &file.document.text;
+            let span = Span::new(
+                position_to_offset(text, range.start)?,
+                position_to_offset(text, range.end)?,
+            );
+            (
+                template.clone(),
+                file.document.source_map.span_to_template(span)?,
+            )
+        };
+        let range = to_range(&template, span)?;
+        Some((template, range))
+    }
+
+    /// Map a location into a virtual document to its template; locations in
+    /// real files are kept as they are
+    fn map_location(&self, location: Location, to_range: SpanToRange) -> Option<Location> {
+        if self.template_for(&location.uri).is_none() {
+            return Some(location);
+        }
+        let (uri, range) = self.map_range(&location.uri, location.range, to_range)?;
+        Some(Location::new(uri, range))
+    }
+
+    fn map_completion(
+        &self,
+        uri: &Url,
+        mut item: CompletionItem,
+        to_range: SpanToRange,
+    ) -> CompletionItem {
+        // Edits that can't be mapped fall back to plain insert text
+        if let Some(edit) = item.text_edit.take() {
+            let (range, new_text) = match &edit {
+                CompletionTextEdit::Edit(edit) => (edit.range, edit.new_text.clone()),
+                CompletionTextEdit::InsertAndReplace(edit) => (edit.replace, edit.new_text.clone()),
+            };
+            match self.map_range(uri, range, to_range) {
+                Some((_, range)) => {
+                    item.text_edit = Some(CompletionTextEdit::Edit(TextEdit::new(range, new_text)))
+                }
+                None => item.insert_text = Some(new_text),
+            }
+        }
+        item.additional_text_edits = item.additional_text_edits.map(|edits| {
+            edits
+                .into_iter()
+                .filter_map(|mut edit| {
+                    edit.range = self.map_range(uri, edit.range, to_range)?.1;
+                    Some(edit)
+                })
+                .collect()
+        });
+        item
+    }
+}
+
+/// `file:///app/Card.luat` is mirrored as `file:///app/Card.luat.lua`
+fn virtual_uri(template: &Url) -> Url {
+    let mut uri = template.clone();
+    let path = format!("{}.lua", uri.path());
+    uri.set_path(&path);
+    uri
+}
+
+/// UTF-16 position of a byte offset in the virtual document, which is the
+/// encoding lua-language-server uses by default
+fn offset_to_position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].encode_utf16().count() as u32,
+    )
+}
+
+fn position_to_offset(text: &str, position: Position) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => text.match_indices('\n').nth(line as usize - 1)?.0 + 1,
+    };
+    let line_end = text[line_start..]
+        .find('\n')
+        .map_or(text.len(), |i| line_start + i);
+
+    let mut units = 0;
+    for (i, c) in text[line_start..line_end].char_indices() {
+        if units >= position.character as usize {
+            return Some(line_start + i);
+        }
+        units += c.len_utf16();
+    }
+    Some(line_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rpc::{read_message, write_message};
+    use super::*;
+    use crate::syntax::parse;
+    use tokio::io::{duplex, BufReader, DuplexStream};
+
+    const TEMPLATE: &str = "<h1>{props.title}</h1>";
+
+    fn template_uri() -> Url {
+        Url::parse("file:///app/Page.luat").unwrap()
+    }
+
+    /// Map template spans to UTF-8 ranges on the template's single line
+    fn to_range(_: &Url, span: Span) -> Option<Range> {
+        Some(Range::new(
+            Position::new(0, span.start as u32),
+            Position::new(0, span.end as u32),
+        ))
+    }
+
+    /// Scripted stand-in for lua-language-server
+    struct FakeServer {
+        reader: BufReader<DuplexStream>,
+        writer: DuplexStream,
+    }
+
+    impl FakeServer {
+        async fn expect(&mut self, method: &str) -> Value {
+            let message = read_message(&mut self.reader).await.unwrap().unwrap();
+            assert_eq!(message["method"], method, "{}", message);
+            message
+        }
+
+        async fn reply(&mut self, request: &Value, result: Value) {
+            let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+            write_message(&mut self.writer, &response).await.unwrap();
+        }
+    }
+
+    async fn start() -> (LuaLanguageServer, FakeServer, VirtualDocument) {
+        let (to_server, from_client) = duplex(64 * 1024);
+        let (to_client, from_server) = duplex(64 * 1024);
+
+        let mut fake = FakeServer {
+            reader: BufReader::new(from_client),
+            writer: to_client,
+        };
+        let handshake = tokio::spawn(async move {
+            let initialize = fake.expect("initialize").await;
+            fake.reply(&initialize, json!({ "capabilities": {} })).await;
+            fake.expect("initialized").await;
+            fake
+        });
+
+        let (proxy, _notifications) = LuaLanguageServer::connect(from_server, to_server, None)
+            .await
+            .unwrap();
+        let mut fake = handshake.await.unwrap();
+
+        let document = VirtualDocument::build(&parse(TEMPLATE), TEMPLATE);
+        proxy
+            .update(&template_uri(), Some(1), document.clone())
+            .await;
+        let open = fake.expect("textDocument/didOpen").await;
+        assert_eq!(
+            open["params"]["textDocument"]["uri"],
+            "file:///app/Page.luat.lua"
+        );
+        assert_eq!(open["params"]["textDocument"]["text"], document.text);
+
+        (proxy, fake, document)
+    }
+
+    #[tokio::test]
+    async fn test_completion_positions_are_mapped_both_ways() {
+        let (proxy, mut fake, document) = start().await;
+        let title = TEMPLATE.find("title").unwrap();
+        let virtual_title = document.source_map.to_virtual(title).unwrap();
+        let expected = offset_to_position(&document.text, virtual_title);
+
+        let server = tokio::spawn(async move {
+            let request = fake.expect("textDocument/completion").await;
+            assert_eq!(request["params"]["position"], json!(expected));
+            let edit_range = Range::new(
+                expected,
+                Position::new(expected.line, expected.character + 5),
+            );
+            fake.reply(
+                &request,
+                json!([{ "label": "title", "textEdit": { "range": edit_range, "newText": "title" } }]),
+            )
+            .await;
+        });
+
+        let items = proxy
+            .completion(&template_uri(), title, &to_range)
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("expected a mapped text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(
+                Position::new(0, title as u32),
+                Position::new(0, title as u32 + 5)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_in_generated_code_are_dropped() {
+        let (proxy, _fake, document) = start().await;
+        let props = TEMPLATE.find("props").unwrap();
+        let virtual_props = document.source_map.to_virtual(props).unwrap();
+        let start = offset_to_position(&document.text, virtual_props);
+
+        let diagnostic = |range| Diagnostic {
+            range,
+            message: "undefined field".to_string(),
+            ..Default::default()
+        };
+        let params = PublishDiagnosticsParams {
+            uri: Url::parse("file:///app/Page.luat.lua").unwrap(),
+            diagnostics: vec![
+                diagnostic(Range::new(
+                    start,
+                    Position::new(start.line, start.character + 5),
+                )),
+                // `__luat_template` on the first line is generated code
+                diagnostic(Range::new(Position::new(0, 15), Position::new(0, 30))),
+            ],
+            version: None,
+        };
+
+        let (uri, version, diagnostics) = proxy.map_diagnostics(params, &to_range).unwrap();
+        assert_eq!(uri, template_uri());
+        assert_eq!(version, Some(1));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(
+                Position::new(0, props as u32),
+                Position::new(0, props as u32 + 5)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_of_older_virtual_documents_are_dropped() {
+        let (proxy, mut fake, document) = start().await;
+        proxy.update(&template_uri(), Some(2), document).await;
+        fake.expect("textDocument/didChange").await;
+
+        let params = |version| PublishDiagnosticsParams {
+            uri: Url::parse("file:///app/Page.luat.lua").unwrap(),
+            diagnostics: Vec::new(),
+            version,
+        };
+        assert!(proxy.map_diagnostics(params(Some(0)), &to_range).is_none());
+        let (_, version, _) = proxy.map_diagnostics(params(Some(1)), &to_range).unwrap();
+        assert_eq!(version, Some(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unanswered_handshake_times_out() {
+        let (to_server, _from_client) = duplex(64 * 1024);
+        let (_to_client, from_server) = duplex(64 * 1024);
+        let connected = LuaLanguageServer::connect(from_server, to_server, None).await;
+        assert_eq!(
+            connected.err().map(|err| err.kind()),
+            Some(io::ErrorKind::TimedOut)
+        );
+    }
+
+    #[test]
+    fn test_virtual_positions_round_trip() {
+        let text = "local a = 1\nlocal ü = '😀' .. x\n";
+        let x = text.find('x').unwrap();
+        let position = offset_to_position(text, x);
+        assert_eq!(position, Position::new(1, 18));
+        assert_eq!(position_to_offset(text, position), Some(x));
+    }
+}
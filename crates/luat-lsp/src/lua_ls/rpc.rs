@@ -0,0 +1,221 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Minimal JSON-RPC client speaking the LSP base protocol
+//! (`Content-Length` framed messages) over a pair of byte streams
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+
+type Writer = Arc<tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>>;
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<io::Result<Value>>>>>;
+
+/// A notification sent by the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub method: String,
+    pub params: Value,
+}
+
+pub struct RpcClient {
+    writer: Writer,
+    pending: Pending,
+    next_id: AtomicI64,
+}
+
+impl RpcClient {
+    /// Start a client over the server's output and input streams. Server
+    /// notifications are delivered on the returned channel.
+    pub fn new<R, W>(reader: R, writer: W) -> (Self, mpsc::UnboundedReceiver<Notification>)
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let writer: Writer = Arc::new(tokio::sync::Mutex::new(Box::new(writer)));
+        let pending: Pending = Arc::default();
+        let (notifications, receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(read_loop(
+            BufReader::new(reader),
+            writer.clone(),
+            pending.clone(),
+            notifications,
+        ));
+
+        let client = Self {
+            writer,
+            pending,
+            next_id: AtomicI64::new(1),
+        };
+        (client, receiver)
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> io::Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(err) = write_message(&mut *self.writer.lock().await, &message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(err);
+        }
+
+        // Dropped without an answer if the request is cancelled
+        let guard = CancelOnDrop {
+            id,
+            pending: &self.pending,
+            writer: &self.writer,
+            done: false,
+        };
+        let result = receiver
+            .await
+            .unwrap_or_else(|_| Err(io::Error::other("language server connection closed")));
+        guard.finish();
+        result
+    }
+
+    /// Send a notification
+    pub async fn notify(&self, method: &str, params: Value) -> io::Result<()> {
+        let message = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        write_message(&mut *self.writer.lock().await, &message).await
+    }
+}
+
+/// Sends `$/cancelRequest` when a request future is dropped before its
+/// response arrives, e.g. because our own client cancelled
+struct CancelOnDrop<'a> {
+    id: i64,
+    pending: &'a Pending,
+    writer: &'a Writer,
+    done: bool,
+}
+
+impl CancelOnDrop<'_> {
+    fn finish(mut self) {
+        self.done = true;
+    }
+}
+
+impl Drop for CancelOnDrop<'_> {
+    fn drop(&mut self) {
+        if self.done || self.pending.lock().unwrap().remove(&self.id).is_none() {
+            return;
+        }
+        let writer = self.writer.clone();
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": "$/cancelRequest",
+            "params": { "id": self.id },
+        });
+        tokio::spawn(async move {
+            let _ = write_message(&mut *writer.lock().await, &message).await;
+        });
+    }
+}
+
+async fn read_loop<R: AsyncRead + Unpin>(
+    mut reader: BufReader<R>,
+    writer: Writer,
+    pending: Pending,
+    notifications: mpsc::UnboundedSender<Notification>,
+) {
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(err) => {
+                tracing::warn!("Invalid message from Lua language server: {}", err);
+                break;
+            }
+        };
+
+        let id = message.get("id").cloned();
+        match message.get("method").and_then(Value::as_str) {
+            // A request from the server. We don't implement any client
+            // features, so answer with empty results to keep it going.
+            Some(method) if id.is_some() => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_array().map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                let _ = write_message(&mut *writer.lock().await, &response).await;
+            }
+            Some(method) => {
+                let _ = notifications.send(Notification {
+                    method: method.to_string(),
+                    params: message.get("params").cloned().unwrap_or(Value::Null),
+                });
+            }
+            None => {
+                let Some(id) = id.as_ref().and_then(Value::as_i64) else {
+                    continue;
+                };
+                let Some(sender) = pending.lock().unwrap().remove(&id) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(io::Error::other(error.to_string())),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+        }
+    }
+
+    // Fail everything still waiting
+    pending.lock().unwrap().clear();
+}
+
+/// Read one framed message, or `None` at the end of the stream
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = content_length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Write one framed message
+pub async fn write_message<W: AsyncWrite + Unpin + ?Sized>(
+    writer: &mut W,
+    message: &Value,
+) -> io::Result<()> {
+    let body = message.to_string();
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(body.as_bytes()).await?;
+    writer.flush().await
+}
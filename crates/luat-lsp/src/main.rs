@@ -4,15 +4,27 @@
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod config;
+mod css;
 mod document;
 mod handlers;
+mod lua;
+mod lua_ls;
 mod regions;
+mod scheduler;
 mod server;
+mod syntax;
 
 use server::LuatLanguageServer;
 
 #[tokio::main]
 async fn main() {
+    // Print the JSON Schema for luat.toml, e.g. to publish it with an editor extension
+    if std::env::args().nth(1).as_deref() == Some("--config-schema") {
+        println!("{:#}", config::schema());
+        return;
+    }
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(
@@ -1,8 +1,10 @@
 // Copyright 2026 Maravilla Labs
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use regex::Regex;
-use std::sync::LazyLock;
+use crate::syntax::{
+    Attribute, AttributeKind, AttributeValue, CommentKind, Element, Expression, Node, ScriptKind,
+    Span, SyntaxTree, Tag, ValuePart,
+};
 
 /// Type of region in a .luat document
 #[derive(Debug, Clone, PartialEq)]
@@ -14,8 +16,22 @@ pub enum RegionType {
     LuaScriptModule,
     /// Regular script: <script>
     LuaScript,
+    /// Client-side JavaScript: <script type="module">, <script src="...">
+    ClientScript,
+    /// Opaque script data: <script type="application/ld+json"> or an unknown type
+    ScriptData,
+    /// CSS inside <style>...</style>
+    Style,
     /// Lua expression in {expression}
     LuaExpression,
+    /// Start tag of an element or component, e.g. `<Card title={t} />`.
+    /// Attribute values inside it get their own nested regions.
+    StartTag,
+    /// Static or interpolated quoted attribute value, e.g. `class="btn {extra}"`
+    AttributeValue,
+    /// Lua expression in an attribute: `title={t}`, `{title}`, `{...props}`
+    /// or an interpolation inside a quoted value
+    AttributeExpression,
     /// Control flow condition: {#if condition}, {#each list as item}
     ControlFlow,
     /// Directive: {@html}, {@local}, {@render}
@@ -27,13 +43,28 @@ pub enum RegionType {
 }
 
 /// A region in the document with its type and span
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Region {
     pub region_type: RegionType,
     pub start: usize,
     pub end: usize,
-    /// For script/expression regions, the extracted content
+    /// For script/expression regions, the extracted content.
+    /// For start tags, the element name.
     pub content: Option<String>,
+    /// For attribute regions, the attribute the region belongs to
+    pub attribute: Option<AttributeInfo>,
+}
+
+/// The attribute an attribute region belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeInfo {
+    /// Name of the element or component carrying the attribute
+    pub element: String,
+    /// Attribute name, empty for spread attributes
+    pub name: String,
+    pub kind: AttributeKind,
+    /// Whether the element is a component, making the attribute a prop
+    pub is_prop: bool,
 }
 
 impl Region {
@@ -43,172 +74,52 @@ impl Region {
 }
 
 /// All regions in a document
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DocumentRegions {
     pub regions: Vec<Region>,
 }
 
-// Regex patterns for region detection
-static SCRIPT_MODULE_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r#"(?s)<script\s+(?:module|context\s*=\s*"module")\s*>(.*?)</script>"#).unwrap()
-});
-
-static SCRIPT_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"(?s)<script\s*>(.*?)</script>"#).unwrap());
-
-static CONTROL_FLOW_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"\{[#:/!][^}]*\}"#).unwrap());
-
-static DIRECTIVE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\{@[^}]*\}"#).unwrap());
-
-// Note: regex crate doesn't support lookahead, so we match all braces
-// and filter out control flow/directives/comments in post-processing
-static MUSTACHE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\{[^}]+\}"#).unwrap());
-
-static LUAT_COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(?s)\{/\*.*?\*/\}"#).unwrap());
-
-static LUAT_LINE_COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\{--.*?--\}"#).unwrap());
-
-static HTML_COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"<!--.*?-->"#).unwrap());
-
 impl DocumentRegions {
     /// Parse a document into regions
+    #[cfg(test)]
     pub fn parse(text: &str) -> Self {
-        let mut regions = Vec::new();
-
-        // Find module scripts
-        for cap in SCRIPT_MODULE_RE.captures_iter(text) {
-            if let Some(full) = cap.get(0) {
-                let content = cap.get(1).map(|m| m.as_str().to_string());
-                regions.push(Region {
-                    region_type: RegionType::LuaScriptModule,
-                    start: full.start(),
-                    end: full.end(),
-                    content,
-                });
-            }
-        }
-
-        // Find regular scripts (excluding module scripts)
-        for cap in SCRIPT_RE.captures_iter(text) {
-            if let Some(full) = cap.get(0) {
-                // Check if this overlaps with a module script
-                let is_module = regions.iter().any(|r| {
-                    r.region_type == RegionType::LuaScriptModule && r.start == full.start()
-                });
-
-                if !is_module {
-                    let content = cap.get(1).map(|m| m.as_str().to_string());
-                    regions.push(Region {
-                        region_type: RegionType::LuaScript,
-                        start: full.start(),
-                        end: full.end(),
-                        content,
-                    });
-                }
-            }
-        }
-
-        // Find luat block comments {/* */}
-        for mat in LUAT_COMMENT_RE.find_iter(text) {
-            regions.push(Region {
-                region_type: RegionType::LuatComment,
-                start: mat.start(),
-                end: mat.end(),
-                content: None,
-            });
-        }
-
-        // Find luat line comments {-- --}
-        for mat in LUAT_LINE_COMMENT_RE.find_iter(text) {
-            if !Self::is_inside_region(&regions, mat.start()) {
-                regions.push(Region {
-                    region_type: RegionType::LuatComment,
-                    start: mat.start(),
-                    end: mat.end(),
-                    content: None,
-                });
-            }
-        }
-
-        // Find HTML comments
-        for mat in HTML_COMMENT_RE.find_iter(text) {
-            regions.push(Region {
-                region_type: RegionType::HtmlComment,
-                start: mat.start(),
-                end: mat.end(),
-                content: None,
-            });
-        }
-
-        // Find control flow blocks (not inside scripts or comments)
-        for mat in CONTROL_FLOW_RE.find_iter(text) {
-            if !Self::is_inside_region(&regions, mat.start()) {
-                regions.push(Region {
-                    region_type: RegionType::ControlFlow,
-                    start: mat.start(),
-                    end: mat.end(),
-                    content: Some(mat.as_str().to_string()),
-                });
-            }
-        }
-
-        // Find directives
-        for mat in DIRECTIVE_RE.find_iter(text) {
-            if !Self::is_inside_region(&regions, mat.start()) {
-                regions.push(Region {
-                    region_type: RegionType::Directive,
-                    start: mat.start(),
-                    end: mat.end(),
-                    content: Some(mat.as_str().to_string()),
-                });
-            }
-        }
-
-        // Find mustache expressions (not inside scripts, comments, control flow, or directives)
-        for mat in MUSTACHE_RE.find_iter(text) {
-            if !Self::is_inside_region(&regions, mat.start()) {
-                let expr = mat.as_str();
-
-                // Skip if it starts with special characters (control flow, directive, comment)
-                if let Some(first_content_char) = expr.chars().nth(1) {
-                    if matches!(first_content_char, '#' | ':' | '/' | '@' | '!') {
-                        continue;
-                    }
-                }
-
-                // Extract content between { and }
-                let content = expr
-                    .strip_prefix('{')
-                    .and_then(|s| s.strip_suffix('}'))
-                    .map(|s| s.to_string());
-
-                regions.push(Region {
-                    region_type: RegionType::LuaExpression,
-                    start: mat.start(),
-                    end: mat.end(),
-                    content,
-                });
-            }
-        }
-
-        // Sort by start position
-        regions.sort_by_key(|r| r.start);
+        Self::from_tree(&crate::syntax::parse(text), text)
+    }
 
+    /// Collect regions from an already parsed syntax tree
+    pub fn from_tree(tree: &SyntaxTree, text: &str) -> Self {
+        let mut regions = Vec::new();
+        collect_regions(&tree.nodes, text, &mut regions);
         Self { regions }
     }
 
-    /// Check if an offset is inside any existing region
-    fn is_inside_region(regions: &[Region], offset: usize) -> bool {
-        regions.iter().any(|r| r.contains(offset))
+    /// Update the regions for an incremental reparse that replaced the node
+    /// spanning `old`, shifting those after it
+    pub fn splice(&mut self, old: Span, new: &Node, text: &str) {
+        let delta = new.span().end as isize - old.end as isize;
+        // Regions are sorted by start, and the node's own lie inside it
+        let first = self.regions.partition_point(|r| r.start < old.start);
+        let last = self.regions.partition_point(|r| r.start < old.end);
+        for region in &mut self.regions[last..] {
+            region.start = region.start.saturating_add_signed(delta);
+            region.end = region.end.saturating_add_signed(delta);
+        }
+        let mut regions = Vec::new();
+        collect_regions(std::slice::from_ref(new), text, &mut regions);
+        self.regions.splice(first..last, regions);
     }
 
-    /// Get the region at a given offset
+    /// Get the innermost region at a given offset.
+    ///
+    /// Regions are stored in document order with nested regions (attribute
+    /// values inside a start tag) following their parent, so the last match
+    /// is the innermost one.
     pub fn region_at_offset(&self, offset: usize) -> Option<&Region> {
-        self.regions.iter().find(|r| r.contains(offset))
+        self.regions.iter().rev().find(|r| r.contains(offset))
     }
 
     /// Get all script regions
+    #[cfg(test)]
     pub fn scripts(&self) -> impl Iterator<Item = &Region> {
         self.regions.iter().filter(|r| {
             matches!(
@@ -218,51 +129,171 @@ impl DocumentRegions {
         })
     }
 
-    /// Get all expression regions
-    #[allow(dead_code)] // Useful for lua-language-server integration
+    /// Get all expression regions, including those in attributes
+    #[cfg(test)]
     pub fn expressions(&self) -> impl Iterator<Item = &Region> {
-        self.regions
-            .iter()
-            .filter(|r| r.region_type == RegionType::LuaExpression)
+        self.regions.iter().filter(|r| {
+            matches!(
+                r.region_type,
+                RegionType::LuaExpression | RegionType::AttributeExpression
+            )
+        })
     }
+}
 
-    /// Generate a virtual Lua document from all Lua regions
-    #[allow(dead_code)] // Useful for lua-language-server integration
-    pub fn virtual_lua_document(&self) -> String {
-        let mut doc = String::new();
-
-        // Add module scripts first
-        for region in self.regions.iter() {
-            if region.region_type == RegionType::LuaScriptModule {
-                if let Some(content) = &region.content {
-                    doc.push_str(content);
-                    doc.push_str("\n\n");
+/// Walk the tree in document order, emitting a region for every Lua or comment node
+fn collect_regions(nodes: &[Node], text: &str, regions: &mut Vec<Region>) {
+    for node in nodes {
+        match node {
+            Node::Script(script) => regions.push(Region {
+                region_type: match script.kind() {
+                    ScriptKind::LuaModule => RegionType::LuaScriptModule,
+                    ScriptKind::Lua => RegionType::LuaScript,
+                    ScriptKind::JavaScript => RegionType::ClientScript,
+                    ScriptKind::Json | ScriptKind::Other => RegionType::ScriptData,
+                },
+                start: script.span.start,
+                end: script.span.end,
+                content: Some(script.content.text(text).to_string()),
+                attribute: None,
+            }),
+            Node::Comment(comment) => regions.push(Region {
+                region_type: match comment.kind {
+                    CommentKind::Html => RegionType::HtmlComment,
+                    CommentKind::LuatBlock | CommentKind::LuatLine => RegionType::LuatComment,
+                },
+                start: comment.span.start,
+                end: comment.span.end,
+                content: None,
+                attribute: None,
+            }),
+            Node::Expression(expr) => regions.push(expression_region(expr, text)),
+            Node::Element(element) => {
+                collect_start_tag_regions(element, text, regions);
+                if element.name == "style" {
+                    regions.push(style_region(element, text));
+                } else {
+                    collect_regions(&element.children, text, regions);
                 }
             }
-        }
-
-        // Add regular scripts
-        for region in self.regions.iter() {
-            if region.region_type == RegionType::LuaScript {
-                if let Some(content) = &region.content {
-                    doc.push_str(content);
-                    doc.push_str("\n\n");
+            Node::Block(block) => {
+                for branch in &block.branches {
+                    regions.push(tag_region(&branch.tag, text));
+                    collect_regions(&branch.children, text, regions);
+                }
+                if let Some(close) = &block.close {
+                    regions.push(tag_region(close, text));
                 }
             }
+            Node::Directive(tag) | Node::StrayTag(tag) => regions.push(tag_region(tag, text)),
+            Node::Text(_) | Node::StrayEndTag(_) => {}
         }
+    }
+}
+
+fn expression_region(expr: &Expression, text: &str) -> Region {
+    Region {
+        region_type: RegionType::LuaExpression,
+        start: expr.span.start,
+        end: expr.span.end,
+        content: Some(expr.content.text(text).to_string()),
+        attribute: None,
+    }
+}
+
+/// The style sheet of a `<style>` element, up to its end tag
+fn style_region(element: &Element, text: &str) -> Region {
+    let content_end = element
+        .end_tag
+        .as_ref()
+        .map_or(element.span.end, |end| end.span.start);
+    Region {
+        region_type: RegionType::Style,
+        start: element.start_tag.end,
+        end: content_end,
+        content: Some(text[element.start_tag.end..content_end].to_string()),
+        attribute: None,
+    }
+}
 
-        // Wrap expressions in a function for analysis
-        doc.push_str("local function __luat_template(props)\n");
-        for region in self.regions.iter() {
-            if region.region_type == RegionType::LuaExpression {
-                if let Some(content) = &region.content {
-                    doc.push_str(&format!("  local _ = {}\n", content));
+/// Emit the start tag region followed by a region for each attribute value
+fn collect_start_tag_regions(element: &Element, text: &str, regions: &mut Vec<Region>) {
+    regions.push(Region {
+        region_type: RegionType::StartTag,
+        start: element.start_tag.start,
+        end: element.start_tag.end,
+        content: Some(element.name.clone()),
+        attribute: None,
+    });
+
+    for attribute in &element.attributes {
+        let info = AttributeInfo {
+            element: element.name.clone(),
+            name: attribute.name.clone(),
+            kind: attribute.kind,
+            is_prop: element.is_component(),
+        };
+        match &attribute.value {
+            Some(AttributeValue::Expression(expr)) => {
+                regions.push(attribute_expression_region(attribute, expr, text, &info));
+            }
+            Some(AttributeValue::Text { span, .. }) => regions.push(Region {
+                region_type: RegionType::AttributeValue,
+                start: span.start,
+                end: span.end,
+                content: Some(span.text(text).to_string()),
+                attribute: Some(info),
+            }),
+            Some(AttributeValue::Interpolated { span, parts }) => {
+                regions.push(Region {
+                    region_type: RegionType::AttributeValue,
+                    start: span.start,
+                    end: span.end,
+                    content: Some(span.text(text).to_string()),
+                    attribute: Some(info.clone()),
+                });
+                for part in parts {
+                    if let ValuePart::Expression(expr) = part {
+                        regions.push(attribute_expression_region(attribute, expr, text, &info));
+                    }
                 }
             }
+            None => {}
         }
-        doc.push_str("end\n");
+    }
+}
 
-        doc
+fn attribute_expression_region(
+    attribute: &Attribute,
+    expr: &Expression,
+    text: &str,
+    info: &AttributeInfo,
+) -> Region {
+    // Shorthand and spread attributes are nothing but their expression
+    let span = match attribute.kind {
+        AttributeKind::Normal => expr.span,
+        AttributeKind::Shorthand | AttributeKind::Spread => attribute.span,
+    };
+    Region {
+        region_type: RegionType::AttributeExpression,
+        start: span.start,
+        end: span.end,
+        content: Some(expr.content.text(text).to_string()),
+        attribute: Some(info.clone()),
+    }
+}
+
+fn tag_region(tag: &Tag, text: &str) -> Region {
+    Region {
+        region_type: if tag.sigil == '@' {
+            RegionType::Directive
+        } else {
+            RegionType::ControlFlow
+        },
+        start: tag.span.start,
+        end: tag.span.end,
+        content: Some(tag.span.text(text).to_string()),
+        attribute: None,
     }
 }
 
@@ -292,6 +323,50 @@ local count = 0
         assert_eq!(scripts[1].region_type, RegionType::LuaScript);
     }
 
+    #[test]
+    fn test_client_scripts_are_not_lua() {
+        let text = r#"<script type="module" src="/app.js"></script>
+<script>const x = {a: 1};</script>
+<script type="application/ld+json">{"@type": "Thing"}</script>
+<script src="/legacy.js"></script>
+<script lang="lua">local y = 1</script>
+"#;
+
+        let regions = DocumentRegions::parse(text);
+        let types: Vec<_> = regions.regions.iter().map(|r| &r.region_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                &RegionType::ClientScript,
+                &RegionType::LuaScript,
+                &RegionType::ScriptData,
+                &RegionType::ClientScript,
+                &RegionType::LuaScript,
+            ]
+        );
+        assert_eq!(regions.scripts().count(), 2);
+    }
+
+    #[test]
+    fn test_style_is_css_not_expressions() {
+        let text = "<style>\n.card {\n  color: red;\n}\n</style><p>{x}</p>";
+        let regions = DocumentRegions::parse(text);
+
+        let style = regions
+            .region_at_offset(text.find("color").unwrap())
+            .unwrap();
+        assert_eq!(style.region_type, RegionType::Style);
+        assert_eq!(
+            style.content.as_deref(),
+            Some("\n.card {\n  color: red;\n}\n")
+        );
+        let end_tag = regions.region_at_offset(text.find("</style>").unwrap() + 3);
+        assert!(end_tag.is_none());
+
+        let exprs: Vec<_> = regions.expressions().collect();
+        assert_eq!(exprs.len(), 1);
+    }
+
     #[test]
     fn test_parse_expressions() {
         let text = "<p>{name}</p><span>{count + 1}</span>";
@@ -303,6 +378,39 @@ local count = 0
         assert_eq!(exprs[1].content.as_deref(), Some("count + 1"));
     }
 
+    #[test]
+    fn test_parse_attribute_regions() {
+        let text = r#"<Card title={t} {count} {...rest} class="btn {extra}" />"#;
+        let regions = DocumentRegions::parse(text);
+
+        let title = regions.region_at_offset(text.find("t}").unwrap()).unwrap();
+        assert_eq!(title.region_type, RegionType::AttributeExpression);
+        assert_eq!(
+            title.attribute,
+            Some(AttributeInfo {
+                element: "Card".to_string(),
+                name: "title".to_string(),
+                kind: AttributeKind::Normal,
+                is_prop: true,
+            })
+        );
+
+        let exprs: Vec<_> = regions
+            .expressions()
+            .map(|r| r.content.as_deref().unwrap())
+            .collect();
+        assert_eq!(exprs, vec!["t", "count", "rest", "extra"]);
+
+        let class = regions.region_at_offset(text.find("btn").unwrap()).unwrap();
+        assert_eq!(class.region_type, RegionType::AttributeValue);
+
+        let tag = regions
+            .region_at_offset(text.find("class").unwrap())
+            .unwrap();
+        assert_eq!(tag.region_type, RegionType::StartTag);
+        assert_eq!(tag.content.as_deref(), Some("Card"));
+    }
+
     #[test]
     fn test_parse_control_flow() {
         let text = "{#if visible}<p>Hello</p>{/if}";
@@ -0,0 +1,172 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Background analysis.
+//!
+//! Analysis runs on tokio's blocking pool, at most one job per core at a
+//! time, on immutable document snapshots. Each document has at most one
+//! pending diagnostics task: scheduling another cancels it, so a burst of
+//! edits is checked once, after the last one. Work whose request was
+//! cancelled is told so through a [`Cancellation`] and stops early.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::AbortHandle;
+use tower_lsp::lsp_types::Url;
+
+/// How long a document must be left alone after an edit before it's checked
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Whether the result of some work is still wanted. Long analysis checks it
+/// between phases and stops early once it's set.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Cancels the work when its future is dropped
+struct CancelOnDrop(Cancellation);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0 .0.store(true, Ordering::Relaxed);
+    }
+}
+
+pub struct Scheduler {
+    /// One permit per concurrent job
+    workers: Arc<Semaphore>,
+    /// The latest diagnostics task of each document, until it finishes
+    pending: Arc<DashMap<Url, AbortHandle>>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        let workers = std::thread::available_parallelism().map_or(2, |n| n.get());
+        Self {
+            workers: Arc::new(Semaphore::new(workers)),
+            pending: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl Scheduler {
+    /// Run `work` on the worker pool. Dropping the returned future, e.g.
+    /// when the client cancels the request it belongs to, drops the work
+    /// if it hasn't started yet and cancels it otherwise.
+    pub async fn run<T, F>(&self, work: F) -> Option<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Cancellation) -> T + Send + 'static,
+    {
+        let cancellation = Cancellation::default();
+        let _cancel = CancelOnDrop(cancellation.clone());
+        let permit = self.workers.clone().acquire_owned().await.ok()?;
+        let job = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            work(&cancellation)
+        });
+        match job.await {
+            Ok(result) => Some(result),
+            Err(err) => {
+                tracing::error!("Analysis failed: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Start `task` for a document after `delay`, cancelling the one
+    /// scheduled before it
+    pub fn schedule<F>(&self, uri: Url, delay: Duration, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // Hold the entry while spawning so the task can't finish, and miss
+        // its own entry, before it's recorded
+        let pending = self.pending.clone();
+        let entry = self.pending.entry(uri.clone());
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            task.await;
+            let id = tokio::task::id();
+            pending.remove_if(&uri, |_, handle| handle.id() == id);
+        });
+        match entry {
+            Entry::Occupied(mut occupied) => {
+                occupied.insert(handle.abort_handle()).abort();
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(handle.abort_handle());
+            }
+        }
+    }
+
+    /// Cancel a document's pending task, e.g. when it's closed
+    pub fn cancel(&self, uri: &Url) {
+        if let Some((_, pending)) = self.pending.remove(uri) {
+            pending.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::test_uri;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_schedule_cancels_superseded_tasks() {
+        let scheduler = Scheduler::default();
+        let uri = test_uri();
+        let runs = Arc::new(AtomicUsize::new(0));
+        for _ in 0..3 {
+            let runs = runs.clone();
+            scheduler.schedule(uri.clone(), Duration::from_millis(20), async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        // Let the tasks start waiting, then move the paused clock past the delay
+        tokio::task::yield_now().await;
+        tokio::time::advance(Duration::from_millis(19)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        tokio::time::advance(Duration::from_millis(1)).await;
+        tokio::task::yield_now().await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(scheduler.pending.is_empty());
+
+        assert_eq!(scheduler.run(|_| 6 * 7).await, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_dropping_run_cancels_the_work() {
+        let scheduler = Scheduler::default();
+        let (started, wait_started) = std::sync::mpsc::channel();
+        let (stopped, wait_stopped) = std::sync::mpsc::channel();
+        let run = scheduler.run(move |cancellation| {
+            started.send(()).unwrap();
+            while !cancellation.is_cancelled() {
+                std::thread::yield_now();
+            }
+            stopped.send(()).unwrap();
+        });
+        tokio::select! {
+            _ = run => panic!("the work should wait for cancellation"),
+            _ = tokio::task::spawn_blocking(move || wait_started.recv()) => {}
+        }
+        // `run` was dropped by `select!`
+        let stopped =
+            tokio::task::spawn_blocking(move || wait_stopped.recv_timeout(Duration::from_secs(5)));
+        assert!(stopped.await.unwrap().is_ok());
+    }
+}
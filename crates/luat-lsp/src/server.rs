@@ -2,40 +2,410 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
-use crate::document::Document;
+use crate::config::{self, Settings};
+use crate::document::{Document, PositionEncoding};
 use crate::handlers;
+use crate::handlers::diagnostics::pull::{self, FileDiagnostics};
+use crate::lua::VirtualDocument;
+use crate::lua_ls::LuaLanguageServer;
+use crate::scheduler::{self, Scheduler};
+use crate::syntax::Span;
+
+/// Templates in a partial result of `workspace/diagnostic`
+const PARTIAL_RESULT_FILES: usize = 20;
+
+/// How the client gets diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DiagnosticDelivery {
+    /// We publish them whenever they may have changed
+    #[default]
+    Push,
+    /// The client asks for them, and we may ask it to ask again
+    Pull { refresh: bool },
+}
 
 pub struct LuatLanguageServer {
     client: Client,
-    documents: DashMap<Url, Document>,
+    /// Open documents. Edits replace a document rather than change it, so
+    /// a snapshot taken for background work stays as it was.
+    documents: Arc<DashMap<Url, Arc<Document>>>,
+    /// Position encoding agreed with the client during `initialize`
+    position_encoding: OnceLock<PositionEncoding>,
+    /// Workspace root from `initialize`
+    root_uri: OnceLock<Url>,
+    /// Whether the client lets us register file watchers
+    watch_files: OnceLock<bool>,
+    /// Whether the client answers `workspace/configuration`
+    pull_configuration: OnceLock<bool>,
+    /// Whether the client lets us register for configuration changes
+    watch_configuration: OnceLock<bool>,
+    /// Whether diagnostics are pushed or pulled, agreed during `initialize`
+    diagnostic_delivery: OnceLock<DiagnosticDelivery>,
+    /// Whether the client shows progress we start
+    work_done_progress: OnceLock<bool>,
+    /// lua-language-server executable found during `initialize`
+    lua_ls_path: OnceLock<PathBuf>,
+    /// Proxy to lua-language-server, when one is installed
+    lua_ls: Arc<OnceLock<LuaLanguageServer>>,
+    /// Latest diagnostics from lua-language-server, already mapped to templates
+    lua_diagnostics: Arc<LuaDiagnostics>,
+    /// Configuration of each workspace folder
+    settings: RwLock<Settings>,
+    /// Diagnostics of templates that aren't open, for `workspace/diagnostic`
+    file_diagnostics: Arc<FileDiagnostics>,
+    /// Runs analysis off the message loop
+    scheduler: Arc<Scheduler>,
 }
 
 impl LuatLanguageServer {
     pub fn new(client: Client) -> Self {
         Self {
             client,
-            documents: DashMap::new(),
+            documents: Arc::new(DashMap::new()),
+            position_encoding: OnceLock::new(),
+            root_uri: OnceLock::new(),
+            watch_files: OnceLock::new(),
+            pull_configuration: OnceLock::new(),
+            watch_configuration: OnceLock::new(),
+            diagnostic_delivery: OnceLock::new(),
+            work_done_progress: OnceLock::new(),
+            lua_ls_path: OnceLock::new(),
+            lua_ls: Arc::new(OnceLock::new()),
+            lua_diagnostics: Arc::new(DashMap::new()),
+            settings: RwLock::default(),
+            file_diagnostics: Arc::default(),
+            scheduler: Arc::default(),
+        }
+    }
+
+    fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding.get().copied().unwrap_or_default()
+    }
+
+    fn span_to_range(&self, uri: &Url, span: Span) -> Option<Range> {
+        span_to_range(&self.documents, uri, span)
+    }
+
+    fn diagnostic_delivery(&self) -> DiagnosticDelivery {
+        self.diagnostic_delivery.get().copied().unwrap_or_default()
+    }
+
+    /// The current state of an open document
+    fn snapshot(&self, uri: &Url) -> Option<Arc<Document>> {
+        snapshot(&self.documents, uri)
+    }
+
+    /// Diagnostics of an open document with its version, or of a template
+    /// on disk
+    async fn diagnostics_for(&self, uri: &Url) -> Option<(Vec<Diagnostic>, Option<i32>)> {
+        if let Some(doc) = self.snapshot(uri) {
+            let version = doc.version();
+            let mut diagnostics = self
+                .scheduler
+                .run(move |cancellation| {
+                    handlers::diagnostics::compute_diagnostics(&doc, cancellation)
+                })
+                .await?;
+            diagnostics.extend(lua_diagnostics_for(&self.lua_diagnostics, uri, version));
+            return Some((diagnostics, version));
+        }
+        let path = uri.to_file_path().ok()?;
+        let config = self.settings.read().unwrap().for_uri(uri);
+        let encoding = self.position_encoding();
+        let file_diagnostics = self.file_diagnostics.clone();
+        self.scheduler
+            .run(move |cancellation| file_diagnostics.get(&path, config, encoding, cancellation))
+            .await
+            .flatten()
+            .map(|diagnostics| (diagnostics, None))
+    }
+
+    /// Check a document in the background after `delay` and publish the
+    /// result
+    fn schedule_diagnostics(&self, uri: Url, delay: Duration) {
+        schedule_diagnostics(
+            &self.client,
+            &self.documents,
+            &self.lua_diagnostics,
+            &self.scheduler,
+            uri,
+            delay,
+        );
+    }
+
+    /// Let the client know the diagnostics of `uris` may have changed
+    async fn diagnostics_changed(&self, uris: Vec<Url>) {
+        diagnostics_changed(
+            &self.client,
+            &self.documents,
+            &self.lua_diagnostics,
+            &self.scheduler,
+            self.diagnostic_delivery(),
+            uris,
+        )
+        .await;
+    }
+
+    /// Start lua-language-server and forward its diagnostics to the client
+    async fn start_lua_ls(&self) {
+        let Some(path) = self.lua_ls_path.get() else {
+            return;
+        };
+        let root = self.root_uri.get().cloned();
+        let (server, mut notifications) = match LuaLanguageServer::spawn(path, root).await {
+            Ok(started) => started,
+            Err(err) => {
+                tracing::warn!("Could not start {}: {}", path.display(), err);
+                return;
+            }
+        };
+        tracing::info!("Using {} for Lua regions", path.display());
+        let _ = self.lua_ls.set(server);
+
+        let client = self.client.clone();
+        let documents = self.documents.clone();
+        let lua_ls = self.lua_ls.clone();
+        let lua_diagnostics = self.lua_diagnostics.clone();
+        let scheduler = self.scheduler.clone();
+        let delivery = self.diagnostic_delivery();
+        tokio::spawn(async move {
+            while let Some(notification) = notifications.recv().await {
+                if notification.method != "textDocument/publishDiagnostics" {
+                    continue;
+                }
+                let (Ok(params), Some(server)) = (
+                    serde_json::from_value::<PublishDiagnosticsParams>(notification.params),
+                    lua_ls.get(),
+                ) else {
+                    continue;
+                };
+                let mapped = server
+                    .map_diagnostics(params, &|uri, span| span_to_range(&documents, uri, span));
+                if let Some((uri, version, diagnostics)) = mapped {
+                    lua_diagnostics.insert(uri.clone(), (version, diagnostics));
+                    diagnostics_changed(
+                        &client,
+                        &documents,
+                        &lua_diagnostics,
+                        &scheduler,
+                        delivery,
+                        vec![uri],
+                    )
+                    .await;
+                }
+            }
+        });
+
+        // Mirror documents opened before the server was ready
+        let uris: Vec<Url> = self.documents.iter().map(|doc| doc.key().clone()).collect();
+        for uri in uris {
+            self.sync_lua_ls(&uri).await;
+        }
+    }
+
+    /// Ask the client to report modules being created or deleted, since
+    /// that changes which `require` paths resolve, changes to configuration
+    /// files, and changes to its settings
+    async fn register_capabilities(&self) {
+        let mut registrations = Vec::new();
+        if self.watch_files.get().copied().unwrap_or(false) {
+            let options = DidChangeWatchedFilesRegistrationOptions {
+                watchers: vec![
+                    FileSystemWatcher {
+                        glob_pattern: GlobPattern::String("**/*.{luat,lua}".to_string()),
+                        kind: Some(WatchKind::Create | WatchKind::Delete),
+                    },
+                    FileSystemWatcher {
+                        glob_pattern: GlobPattern::String(format!(
+                            "**/{{{}}}",
+                            config::CONFIG_FILES.join(",")
+                        )),
+                        kind: None,
+                    },
+                ],
+            };
+            registrations.push(Registration {
+                id: "luat-watch-files".to_string(),
+                method: "workspace/didChangeWatchedFiles".to_string(),
+                register_options: serde_json::to_value(options).ok(),
+            });
+        }
+        if self.watch_configuration.get().copied().unwrap_or(false) {
+            registrations.push(Registration {
+                id: "luat-watch-configuration".to_string(),
+                method: "workspace/didChangeConfiguration".to_string(),
+                register_options: None,
+            });
+        }
+        if registrations.is_empty() {
+            return;
+        }
+        if let Err(err) = self.client.register_capability(registrations).await {
+            tracing::warn!("Could not register for workspace changes: {}", err);
         }
     }
 
-    /// Get a document by URI (for external access)
-    #[allow(dead_code)] // Useful for future features like workspace-wide operations
-    pub fn get_document(&self, uri: &Url) -> Option<dashmap::mapref::one::Ref<'_, Url, Document>> {
-        self.documents.get(uri)
+    /// Pull the client's settings for every folder, reread configuration
+    /// files and re-check open documents with the result. `pushed` is used
+    /// for clients that can only send their settings along with
+    /// `workspace/didChangeConfiguration`.
+    async fn reload_configuration(&self, pushed: Option<serde_json::Value>) {
+        let folders = self.settings.read().unwrap().folders();
+        let mut client_settings: Vec<(Option<Url>, serde_json::Value)> = Vec::new();
+        if self.pull_configuration.get().copied().unwrap_or(false) {
+            let scopes: Vec<Option<Url>> = if folders.is_empty() {
+                vec![None]
+            } else {
+                folders.into_iter().map(Some).collect()
+            };
+            let items = scopes
+                .iter()
+                .map(|scope| ConfigurationItem {
+                    scope_uri: scope.clone(),
+                    section: Some("luat".to_string()),
+                })
+                .collect();
+            match self.client.configuration(items).await {
+                Ok(values) => client_settings.extend(scopes.into_iter().zip(values)),
+                Err(err) => tracing::warn!("Could not get client settings: {}", err),
+            }
+        } else if let Some(pushed) = pushed {
+            client_settings.push((None, pushed));
+        }
+
+        let problems = {
+            let mut settings = self.settings.write().unwrap();
+            for (folder, value) in client_settings {
+                settings.set_client_settings(folder.as_ref(), value);
+            }
+            settings.reload()
+        };
+        for problem in problems {
+            tracing::warn!("{}", problem);
+            self.client
+                .show_message(MessageType::WARNING, problem)
+                .await;
+        }
+
+        self.file_diagnostics.clear();
+        let uris: Vec<Url> = {
+            let settings = self.settings.read().unwrap();
+            self.documents
+                .iter_mut()
+                .map(|mut doc| {
+                    let config = settings.for_uri(doc.key());
+                    Arc::make_mut(doc.value_mut()).set_config(config);
+                    doc.key().clone()
+                })
+                .collect()
+        };
+        self.diagnostics_changed(uris).await;
+    }
+
+    /// Send the document's current virtual Lua document to lua-language-server
+    async fn sync_lua_ls(&self, uri: &Url) {
+        let Some(server) = self.lua_ls.get() else {
+            return;
+        };
+        if let Some(doc) = self.snapshot(uri) {
+            let virtual_document = VirtualDocument::build(doc.tree(), doc.text());
+            server.update(uri, doc.version(), virtual_document).await;
+        }
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for LuatLanguageServer {
-    async fn initialize(&self, _params: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::info!("Initializing luat-lsp");
 
+        let encoding = PositionEncoding::negotiate(
+            params
+                .capabilities
+                .general
+                .as_ref()
+                .and_then(|general| general.position_encodings.as_deref()),
+        );
+        let _ = self.position_encoding.set(encoding);
+        tracing::debug!("Using position encoding {:?}", encoding);
+
+        #[allow(deprecated)] // Fall back to root_uri for clients without workspace folders
+        let folders: Vec<Url> = match params.workspace_folders {
+            Some(folders) => folders.into_iter().map(|folder| folder.uri).collect(),
+            None => params.root_uri.into_iter().collect(),
+        };
+        if let Some(root) = folders.first() {
+            let _ = self.root_uri.set(root.clone());
+        }
+        // Configuration files are read once the client's settings are in,
+        // in `initialized`
+        let mut settings = Settings::new(params.initialization_options);
+        settings.set_folders(folders);
+        *self.settings.write().unwrap() = settings;
+
+        let workspace = params.capabilities.workspace.as_ref();
+        let pull_diagnostics = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|text_document| text_document.diagnostic.is_some());
+        let delivery = if pull_diagnostics {
+            DiagnosticDelivery::Pull {
+                refresh: workspace
+                    .and_then(|workspace| workspace.diagnostic.as_ref())
+                    .and_then(|diagnostic| diagnostic.refresh_support)
+                    .unwrap_or(false),
+            }
+        } else {
+            DiagnosticDelivery::Push
+        };
+        let _ = self.diagnostic_delivery.set(delivery);
+        let _ = self.work_done_progress.set(
+            params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|window| window.work_done_progress)
+                .unwrap_or(false),
+        );
+        let _ = self.pull_configuration.set(
+            workspace
+                .and_then(|workspace| workspace.configuration)
+                .unwrap_or(false),
+        );
+        let _ = self.watch_configuration.set(
+            workspace
+                .and_then(|workspace| workspace.did_change_configuration.as_ref())
+                .and_then(|config| config.dynamic_registration)
+                .unwrap_or(false),
+        );
+        let watch_files = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        let _ = self.watch_files.set(watch_files);
+        let lua_ls_path = LuaLanguageServer::find_executable();
+        let lua_ls_available = lua_ls_path.is_some();
+        if let Some(path) = lua_ls_path {
+            let _ = self.lua_ls_path.set(path);
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                position_encoding: Some(encoding.kind()),
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
@@ -60,7 +430,33 @@ impl LanguageServer for LuatLanguageServer {
                 }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
+                signature_help_provider: lua_ls_available.then(|| SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    ..Default::default()
+                }),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        ..Default::default()
+                    },
+                )),
+                diagnostic_provider: pull_diagnostics.then(|| {
+                    DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                        identifier: Some("luat".to_string()),
+                        // Templates depend on the modules they require
+                        inter_file_dependencies: true,
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    })
+                }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -76,10 +472,16 @@ impl LanguageServer for LuatLanguageServer {
         self.client
             .log_message(MessageType::INFO, "Luat language server initialized")
             .await;
+        self.register_capabilities().await;
+        self.reload_configuration(None).await;
+        self.start_lua_ls().await;
     }
 
     async fn shutdown(&self) -> Result<()> {
         tracing::info!("Shutting down luat-lsp");
+        if let Some(server) = self.lua_ls.get() {
+            server.shutdown().await;
+        }
         Ok(())
     }
 
@@ -89,69 +491,172 @@ impl LanguageServer for LuatLanguageServer {
 
         tracing::debug!("Document opened: {}", uri);
 
-        let document = Document::new(uri.clone(), text);
-        self.documents.insert(uri.clone(), document);
-
-        // Publish initial diagnostics
-        let diagnostics = if let Some(doc) = self.documents.get(&uri) {
-            handlers::diagnostics::compute_diagnostics(&doc)
-        } else {
-            vec![]
-        };
+        let mut document = Document::new(uri.clone(), text, self.position_encoding());
+        document.set_version(params.text_document.version);
+        document.set_config(self.settings.read().unwrap().for_uri(&uri));
+        self.documents.insert(uri.clone(), Arc::new(document));
+        self.sync_lua_ls(&uri).await;
 
-        self.client
-            .publish_diagnostics(uri, diagnostics, None)
-            .await;
+        // Publish initial diagnostics; clients that pull ask for them on open
+        if self.diagnostic_delivery() == DiagnosticDelivery::Push {
+            self.schedule_diagnostics(uri, Duration::ZERO);
+        }
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
 
-        if let Some(mut doc) = self.documents.get_mut(&uri) {
+        {
+            let Some(mut entry) = self.documents.get_mut(&uri) else {
+                return;
+            };
+            // Copies the document if a snapshot of it is still in use
+            let doc = Arc::make_mut(entry.value_mut());
             for change in params.content_changes {
                 doc.apply_change(&change);
             }
+            doc.set_version(params.text_document.version);
+        }
 
-            // Re-publish diagnostics
-            let diagnostics = handlers::diagnostics::compute_diagnostics(&doc);
-            self.client
-                .publish_diagnostics(uri, diagnostics, None)
-                .await;
+        // Re-publish diagnostics once typing pauses
+        if self.diagnostic_delivery() == DiagnosticDelivery::Push {
+            self.schedule_diagnostics(uri.clone(), scheduler::DEBOUNCE);
         }
+        self.sync_lua_ls(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
         tracing::debug!("Document closed: {}", uri);
         self.documents.remove(&uri);
+        self.lua_diagnostics.remove(&uri);
+        self.scheduler.cancel(&uri);
+        if let Some(server) = self.lua_ls.get() {
+            server.close(&uri).await;
+        }
 
         // Clear diagnostics
-        self.client.publish_diagnostics(uri, vec![], None).await;
+        if self.diagnostic_delivery() == DiagnosticDelivery::Push {
+            self.client.publish_diagnostics(uri, vec![], None).await;
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let config_changed = params.changes.iter().any(|change| {
+            change
+                .uri
+                .to_file_path()
+                .is_ok_and(|path| config::is_config_file(&path))
+        });
+        if config_changed {
+            // Re-checks every open document
+            self.reload_configuration(None).await;
+            return;
+        }
+
+        // Edits to a module's contents don't change whether it resolves
+        if params
+            .changes
+            .iter()
+            .all(|change| change.typ == FileChangeType::CHANGED)
+        {
+            return;
+        }
+
+        // Re-check require paths in every open document, and every closed
+        // one the next time the workspace is pulled
+        self.file_diagnostics.clear();
+        let uris: Vec<Url> = self.documents.iter().map(|doc| doc.key().clone()).collect();
+        self.diagnostics_changed(uris).await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // Clients that push their settings may send them with or without
+        // the section name
+        let pushed = match params.settings {
+            serde_json::Value::Object(mut settings) if settings.contains_key("luat") => {
+                settings.remove("luat")
+            }
+            serde_json::Value::Null => None,
+            settings => Some(settings),
+        };
+        self.reload_configuration(pushed).await;
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        {
+            let mut settings = self.settings.write().unwrap();
+            let mut folders = settings.folders();
+            folders.retain(|uri| !params.event.removed.iter().any(|f| f.uri == *uri));
+            folders.extend(params.event.added.into_iter().map(|folder| folder.uri));
+            settings.set_folders(folders);
+        }
+        self.reload_configuration(None).await;
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
 
-        if let Some(doc) = self.documents.get(uri) {
-            let completions = handlers::completion::get_completions(&doc, position);
-            Ok(Some(CompletionResponse::List(CompletionList {
-                is_incomplete: false,
-                items: completions,
-            })))
-        } else {
-            Ok(None)
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+        let offset = doc.position_to_offset(position);
+        // Dropped along with the request if the client cancels it
+        let Some(mut completions) = self
+            .scheduler
+            .run(move |cancellation| {
+                handlers::completion::get_completions(&doc, position, cancellation)
+            })
+            .await
+        else {
+            return Ok(None);
+        };
+
+        // In Lua code, lua-language-server's completions replace our basic
+        // keyword list; template-specific items are kept
+        if let (Some(server), Some(offset)) = (self.lua_ls.get(), offset) {
+            let to_range = |uri: &Url, span| self.span_to_range(uri, span);
+            if let Some(mut items) = server.completion(uri, offset, &to_range).await {
+                let template_items = completions
+                    .into_iter()
+                    .filter(|item| item.kind != Some(CompletionItemKind::KEYWORD))
+                    .filter(|item| !items.iter().any(|i| i.label == item.label))
+                    .collect::<Vec<_>>();
+                items.extend(template_items);
+                completions = items;
+            }
         }
+
+        Ok(Some(CompletionResponse::List(CompletionList {
+            is_incomplete: false,
+            items: completions,
+        })))
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
-        if let Some(doc) = self.documents.get(uri) {
-            Ok(handlers::hover::get_hover(&doc, position))
-        } else {
-            Ok(None)
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+        let offset = doc.position_to_offset(position);
+        let hover = self
+            .scheduler
+            .run(move |cancellation| handlers::hover::get_hover(&doc, position, cancellation))
+            .await
+            .flatten();
+        if hover.is_some() {
+            return Ok(hover);
+        }
+
+        match (self.lua_ls.get(), offset) {
+            (Some(server), Some(offset)) => {
+                let to_range = |uri: &Url, span| self.span_to_range(uri, span);
+                Ok(server.hover(uri, offset, &to_range).await)
+            }
+            _ => Ok(None),
         }
     }
 
@@ -162,10 +667,44 @@ impl LanguageServer for LuatLanguageServer {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
-        if let Some(doc) = self.documents.get(uri) {
-            Ok(handlers::definition::get_definition(&doc, position))
-        } else {
-            Ok(None)
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+        let offset = doc.position_to_offset(position);
+        // Resolving `require` paths looks for files on disk
+        let definition = self
+            .scheduler
+            .run(move |_| handlers::definition::get_definition(&doc, position))
+            .await
+            .flatten();
+        if definition.is_some() {
+            return Ok(definition);
+        }
+
+        match (self.lua_ls.get(), offset) {
+            (Some(server), Some(offset)) => {
+                let to_range = |uri: &Url, span| self.span_to_range(uri, span);
+                Ok(server.definition(uri, offset, &to_range).await)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+        let offset = self
+            .scheduler
+            .run(move |_| doc.position_to_offset(position))
+            .await
+            .flatten();
+        match (self.lua_ls.get(), offset) {
+            (Some(server), Some(offset)) => Ok(server.signature_help(uri, offset).await),
+            _ => Ok(None),
         }
     }
 
@@ -175,10 +714,338 @@ impl LanguageServer for LuatLanguageServer {
     ) -> Result<Option<DocumentSymbolResponse>> {
         let uri = &params.text_document.uri;
 
-        if let Some(doc) = self.documents.get(uri) {
-            Ok(handlers::symbols::get_document_symbols(&doc))
-        } else {
-            Ok(None)
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+        Ok(self
+            .scheduler
+            .run(move |_| handlers::symbols::get_document_symbols(&doc))
+            .await
+            .flatten())
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let Some(doc) = self.snapshot(uri) else {
+            return Ok(None);
+        };
+
+        // Search the workspace folder, or the document's directory outside of one
+        let root = self
+            .settings
+            .read()
+            .unwrap()
+            .root_for(uri)
+            .or_else(|| handlers::definition::document_dir(&doc));
+        // Finding components walks the workspace, so keep it off the message loop
+        let actions = self
+            .scheduler
+            .run(move |_| {
+                handlers::code_action::get_code_actions(
+                    &doc,
+                    params.range,
+                    &params.context.diagnostics,
+                    root.as_slice(),
+                )
+            })
+            .await
+            .unwrap_or_default();
+        Ok((!actions.is_empty()).then_some(actions))
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let uri = params.text_document.uri;
+        let (diagnostics, _) = self.diagnostics_for(&uri).await.unwrap_or_default();
+        let report = match pull::report(diagnostics, params.previous_result_id.as_deref()) {
+            DocumentDiagnosticReportKind::Full(full) => {
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: full,
+                })
+            }
+            DocumentDiagnosticReportKind::Unchanged(unchanged) => {
+                DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                    related_documents: None,
+                    unchanged_document_diagnostic_report: unchanged,
+                })
+            }
+        };
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        let previous: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|previous| (previous.uri, previous.value))
+            .collect();
+
+        // Every template in the workspace, then open documents outside it
+        let folders = self.settings.read().unwrap().folders();
+        let mut uris: Vec<Url> = self
+            .scheduler
+            .run(move |_| {
+                folders
+                    .iter()
+                    .filter_map(|folder| folder.to_file_path().ok())
+                    .flat_map(|root| pull::workspace_files(&root))
+                    .filter_map(|path| Url::from_file_path(path).ok())
+                    .collect()
+            })
+            .await
+            .unwrap_or_default();
+        let mut seen: HashSet<Url> = uris.iter().cloned().collect();
+        for doc in self.documents.iter() {
+            if seen.insert(doc.key().clone()) {
+                uris.push(doc.key().clone());
+            }
+        }
+
+        let progress = WorkDone::begin(
+            &self.client,
+            params.work_done_progress_params.work_done_token,
+            self.work_done_progress.get().copied().unwrap_or(false),
+            "Checking Luat templates",
+        )
+        .await;
+        let partial = params.partial_result_params.partial_result_token;
+        let mut items = Vec::new();
+        for (i, uri) in uris.iter().enumerate() {
+            if let Some((diagnostics, version)) = self.diagnostics_for(uri).await {
+                let version = version.map(i64::from);
+                let previous = previous.get(uri).map(String::as_str);
+                items.push(match pull::report(diagnostics, previous) {
+                    DocumentDiagnosticReportKind::Full(full) => {
+                        WorkspaceDocumentDiagnosticReport::Full(
+                            WorkspaceFullDocumentDiagnosticReport {
+                                uri: uri.clone(),
+                                version,
+                                full_document_diagnostic_report: full,
+                            },
+                        )
+                    }
+                    DocumentDiagnosticReportKind::Unchanged(unchanged) => {
+                        WorkspaceDocumentDiagnosticReport::Unchanged(
+                            WorkspaceUnchangedDocumentDiagnosticReport {
+                                uri: uri.clone(),
+                                version,
+                                unchanged_document_diagnostic_report: unchanged,
+                            },
+                        )
+                    }
+                });
+            }
+
+            let last = i + 1 == uris.len();
+            if let Some(token) = &partial {
+                if items.len() >= PARTIAL_RESULT_FILES || (last && !items.is_empty()) {
+                    self.client
+                        .send_notification::<PartialResults>(PartialResultsParams {
+                            token: token.clone(),
+                            value: WorkspaceDiagnosticReportPartialResult {
+                                items: std::mem::take(&mut items),
+                            },
+                        })
+                        .await;
+                }
+            }
+            progress.report(i + 1, uris.len()).await;
         }
+        progress.end().await;
+
+        // With partial results, everything has been sent already
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
+        ))
+    }
+}
+
+/// lua-language-server's diagnostics of each template, with the template
+/// version they were mapped against
+type LuaDiagnostics = DashMap<Url, (Option<i32>, Vec<Diagnostic>)>;
+
+/// lua-language-server's diagnostics of a template, if they were mapped
+/// against `version` of it. Older ones point at text that has since moved.
+fn lua_diagnostics_for(
+    lua_diagnostics: &LuaDiagnostics,
+    uri: &Url,
+    version: Option<i32>,
+) -> Vec<Diagnostic> {
+    match lua_diagnostics.get(uri) {
+        Some(entry) if entry.0 == version => entry.1.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn snapshot(documents: &DashMap<Url, Arc<Document>>, uri: &Url) -> Option<Arc<Document>> {
+    documents.get(uri).map(|doc| Arc::clone(doc.value()))
+}
+
+fn span_to_range(documents: &DashMap<Url, Arc<Document>>, uri: &Url, span: Span) -> Option<Range> {
+    documents.get(uri).map(|doc| doc.span_to_range(span))
+}
+
+/// Check a document on the worker pool after `delay` and publish our
+/// diagnostics along with lua-language-server's. Checking again, or closing
+/// the document, cancels it, and diagnostics of a document that changed in
+/// the meantime are dropped, so a client never sees stale ones.
+fn schedule_diagnostics(
+    client: &Client,
+    documents: &Arc<DashMap<Url, Arc<Document>>>,
+    lua_diagnostics: &Arc<LuaDiagnostics>,
+    scheduler: &Arc<Scheduler>,
+    uri: Url,
+    delay: Duration,
+) {
+    let client = client.clone();
+    let documents = documents.clone();
+    let lua_diagnostics = lua_diagnostics.clone();
+    let workers = scheduler.clone();
+    scheduler.schedule(uri.clone(), delay, async move {
+        let Some(doc) = snapshot(&documents, &uri) else {
+            return;
+        };
+        let checked = doc.clone();
+        let Some(mut diagnostics) = workers
+            .run(move |cancellation| {
+                handlers::diagnostics::compute_diagnostics(&checked, cancellation)
+            })
+            .await
+        else {
+            return;
+        };
+        let current = snapshot(&documents, &uri);
+        if !current.is_some_and(|current| Arc::ptr_eq(&current, &doc)) {
+            return;
+        }
+        diagnostics.extend(lua_diagnostics_for(&lua_diagnostics, &uri, doc.version()));
+        client
+            .publish_diagnostics(uri, diagnostics, doc.version())
+            .await;
+    });
+}
+
+/// Publish the diagnostics of `uris` again, or ask a client that pulls
+/// diagnostics to pull them again
+async fn diagnostics_changed(
+    client: &Client,
+    documents: &Arc<DashMap<Url, Arc<Document>>>,
+    lua_diagnostics: &Arc<LuaDiagnostics>,
+    scheduler: &Arc<Scheduler>,
+    delivery: DiagnosticDelivery,
+    uris: Vec<Url>,
+) {
+    match delivery {
+        DiagnosticDelivery::Push => {
+            for uri in uris {
+                schedule_diagnostics(
+                    client,
+                    documents,
+                    lua_diagnostics,
+                    scheduler,
+                    uri,
+                    Duration::ZERO,
+                );
+            }
+        }
+        DiagnosticDelivery::Pull { refresh: true } => {
+            if let Err(err) = client.workspace_diagnostic_refresh().await {
+                tracing::warn!("Could not refresh diagnostics: {}", err);
+            }
+        }
+        DiagnosticDelivery::Pull { refresh: false } => {}
+    }
+}
+
+/// `$/progress` carrying partial results, which lsp-types only models for
+/// work done progress
+enum PartialResults {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PartialResultsParams {
+    token: ProgressToken,
+    value: WorkspaceDiagnosticReportPartialResult,
+}
+
+impl notification::Notification for PartialResults {
+    type Params = PartialResultsParams;
+    const METHOD: &'static str = "$/progress";
+}
+
+/// Work done progress of a long request, reported through the token the
+/// client sent with it, or one we create if the client supports that
+struct WorkDone<'a> {
+    client: &'a Client,
+    token: Option<ProgressToken>,
+}
+
+impl<'a> WorkDone<'a> {
+    async fn begin(
+        client: &'a Client,
+        token: Option<ProgressToken>,
+        create: bool,
+        title: &str,
+    ) -> WorkDone<'a> {
+        static NEXT_TOKEN: AtomicU32 = AtomicU32::new(0);
+        let token = match token {
+            Some(token) => Some(token),
+            None if create => {
+                let token = ProgressToken::String(format!(
+                    "luat/{}",
+                    NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+                ));
+                let params = WorkDoneProgressCreateParams {
+                    token: token.clone(),
+                };
+                client
+                    .send_request::<request::WorkDoneProgressCreate>(params)
+                    .await
+                    .ok()
+                    .map(|_| token)
+            }
+            None => None,
+        };
+        let progress = WorkDone { client, token };
+        progress
+            .send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }))
+            .await;
+        progress
+    }
+
+    async fn report(&self, done: usize, total: usize) {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(format!("{}/{}", done, total)),
+            percentage: Some((done * 100 / total.max(1)) as u32),
+        }))
+        .await;
+    }
+
+    async fn end(self) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }))
+            .await;
+    }
+
+    async fn send(&self, value: WorkDoneProgress) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        self.client
+            .send_notification::<notification::Progress>(ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(value),
+            })
+            .await;
     }
 }
@@ -0,0 +1,391 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Incremental reparsing.
+//!
+//! After an edit we look for the innermost element, block or script that
+//! strictly contains the edited range, reparse just that node in the new
+//! text and shift the spans of everything after it. The reparse is only
+//! accepted when the new node ends exactly where the old one did (shifted by
+//! the edit), because the parser's decisions after that point then cannot
+//! differ from a full parse. Otherwise we try the next enclosing node, and
+//! finally fall back to parsing the whole document.
+
+use super::parser::{self, Open};
+use super::tree::*;
+
+/// A text edit in byte offsets: `start..old_end` in the old text was
+/// replaced by `start..new_end` in the new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub old_end: usize,
+    pub new_end: usize,
+}
+
+impl Edit {
+    pub fn delta(&self) -> isize {
+        self.new_end as isize - self.old_end as isize
+    }
+}
+
+/// The node an edit was confined to
+#[derive(Debug)]
+pub struct Replaced {
+    /// The node as it was before the edit
+    pub old: Node,
+    /// The node parsed from the new text
+    pub new: Node,
+}
+
+/// Update `tree` for an edit that turned its source into `text`, returning
+/// the node that was replaced, or `None` if the whole document was reparsed
+pub fn reparse(tree: &mut SyntaxTree, text: &str, edit: Edit) -> Option<Replaced> {
+    let replaced = try_reparse(tree, text, edit);
+    if replaced.is_none() {
+        *tree = parser::parse(text);
+    }
+    replaced
+}
+
+/// A node that could be reparsed on its own, identified by its path from the root
+struct Candidate {
+    /// (branch index, child index) pairs, where the branch is the one of the
+    /// indexed node that the path continues into (0 for elements)
+    path: Vec<(usize, usize)>,
+    ancestors: Vec<Open>,
+    span: Span,
+}
+
+fn try_reparse(tree: &mut SyntaxTree, text: &str, edit: Edit) -> Option<Replaced> {
+    let mut candidates = Vec::new();
+    collect_candidates(
+        &tree.nodes,
+        edit,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut candidates,
+    );
+
+    // Innermost first
+    for candidate in candidates.iter().rev() {
+        // Unclosed constructs before the node scan ahead through the rest of
+        // the document, so the edit could change how they end
+        if tree
+            .errors
+            .iter()
+            .any(|e| e.span.start < candidate.span.start)
+        {
+            return None;
+        }
+
+        let (Some(node), errors) =
+            parser::parse_node_at(text, candidate.span.start, candidate.ancestors.clone())
+        else {
+            continue;
+        };
+        let expected_end = candidate.span.end as isize + edit.delta();
+        if node.span().end as isize != expected_end {
+            continue;
+        }
+
+        // Take the old node out before shifting so it keeps the spans of the
+        // old text
+        let old = std::mem::replace(
+            node_at_path(&mut tree.nodes, &candidate.path),
+            Node::Text(candidate.span),
+        );
+        for node in &mut tree.nodes {
+            shift_node(node, edit.old_end, edit.delta());
+        }
+        *node_at_path(&mut tree.nodes, &candidate.path) = node.clone();
+
+        // Keep errors in document order: those before the node, the node's
+        // own, then those after it
+        let (before, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut tree.errors)
+            .into_iter()
+            .partition(|e| e.span.start < candidate.span.start);
+        let after = rest
+            .into_iter()
+            .filter(|e| e.span.start >= candidate.span.end);
+        tree.errors = before;
+        tree.errors.extend(errors);
+        tree.errors.extend(after.map(|mut e| {
+            e.span.shift(edit.old_end, edit.delta());
+            e
+        }));
+
+        return Some(Replaced { old, new: node });
+    }
+
+    None
+}
+
+/// Collect every element, block and script that strictly contains the
+/// edit, outermost first
+fn collect_candidates(
+    nodes: &[Node],
+    edit: Edit,
+    path: &mut Vec<(usize, usize)>,
+    ancestors: &mut Vec<Open>,
+    candidates: &mut Vec<Candidate>,
+) {
+    for (index, node) in nodes.iter().enumerate() {
+        let span = node.span();
+        if !(span.start < edit.start && edit.old_end < span.end) {
+            continue;
+        }
+
+        let (branch, children, open) = match node {
+            Node::Element(element) => (
+                0,
+                &element.children[..],
+                Some(Open::Element(element.name.clone())),
+            ),
+            Node::Block(block) => {
+                // Descend into the branch containing the edit
+                let branch = block
+                    .branches
+                    .iter()
+                    .rposition(|b| b.tag.span.start < edit.start)
+                    .unwrap_or(0);
+                (
+                    branch,
+                    &block.branches[branch].children[..],
+                    Some(Open::Block(block.keyword.clone())),
+                )
+            }
+            Node::Script(_) => (0, &[][..], None),
+            _ => return,
+        };
+
+        path.push((branch, index));
+        candidates.push(Candidate {
+            path: path.clone(),
+            ancestors: ancestors.clone(),
+            span,
+        });
+        if let Some(open) = open {
+            ancestors.push(open);
+            collect_candidates(children, edit, path, ancestors, candidates);
+            ancestors.pop();
+        }
+        path.pop();
+        return;
+    }
+}
+
+fn node_at_path<'a>(nodes: &'a mut [Node], path: &[(usize, usize)]) -> &'a mut Node {
+    let ((branch, index), rest) = path.split_first().expect("path is never empty");
+    let node = &mut nodes[*index];
+    if rest.is_empty() {
+        return node;
+    }
+    match node {
+        Node::Element(element) => node_at_path(&mut element.children, rest),
+        Node::Block(block) => node_at_path(&mut block.branches[*branch].children, rest),
+        _ => unreachable!("only elements and blocks have children"),
+    }
+}
+
+// ---- span shifting -------------------------------------------------------
+
+/// Move every position at or after `from` by `delta`
+fn shift_node(node: &mut Node, from: usize, delta: isize) {
+    if node.span().end < from {
+        return;
+    }
+    match node {
+        Node::Text(span) => span.shift(from, delta),
+        Node::Comment(comment) => comment.span.shift(from, delta),
+        Node::Expression(expr) => shift_expression(expr, from, delta),
+        Node::Element(element) => {
+            element.span.shift(from, delta);
+            element.name_span.shift(from, delta);
+            element.start_tag.shift(from, delta);
+            for attribute in &mut element.attributes {
+                shift_attribute(attribute, from, delta);
+            }
+            for child in &mut element.children {
+                shift_node(child, from, delta);
+            }
+            if let Some(end) = &mut element.end_tag {
+                shift_end_tag(end, from, delta);
+            }
+        }
+        Node::Script(script) => {
+            script.span.shift(from, delta);
+            script.start_tag.shift(from, delta);
+            script.content.shift(from, delta);
+            for attribute in &mut script.attributes {
+                shift_attribute(attribute, from, delta);
+            }
+            if let Some(end) = &mut script.end_tag {
+                shift_end_tag(end, from, delta);
+            }
+        }
+        Node::Block(block) => {
+            block.span.shift(from, delta);
+            for branch in &mut block.branches {
+                shift_tag(&mut branch.tag, from, delta);
+                for child in &mut branch.children {
+                    shift_node(child, from, delta);
+                }
+            }
+            if let Some(close) = &mut block.close {
+                shift_tag(close, from, delta);
+            }
+        }
+        Node::Directive(tag) | Node::StrayTag(tag) => shift_tag(tag, from, delta),
+        Node::StrayEndTag(end) => shift_end_tag(end, from, delta),
+    }
+}
+
+fn shift_expression(expr: &mut Expression, from: usize, delta: isize) {
+    expr.span.shift(from, delta);
+    expr.content.shift(from, delta);
+}
+
+fn shift_attribute(attribute: &mut Attribute, from: usize, delta: isize) {
+    attribute.span.shift(from, delta);
+    attribute.name_span.shift(from, delta);
+    match &mut attribute.value {
+        Some(AttributeValue::Text { span, .. }) => span.shift(from, delta),
+        Some(AttributeValue::Expression(expr)) => shift_expression(expr, from, delta),
+        Some(AttributeValue::Interpolated { span, parts }) => {
+            span.shift(from, delta);
+            for part in parts {
+                match part {
+                    ValuePart::Text(span) => span.shift(from, delta),
+                    ValuePart::Expression(expr) => shift_expression(expr, from, delta),
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+fn shift_end_tag(end: &mut EndTag, from: usize, delta: isize) {
+    end.span.shift(from, delta);
+    end.name_span.shift(from, delta);
+}
+
+fn shift_tag(tag: &mut Tag, from: usize, delta: isize) {
+    tag.span.shift(from, delta);
+    tag.keyword_span.shift(from, delta);
+    if let Some(expression) = &mut tag.expression {
+        expression.shift(from, delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<script module>
+local Card = require("components/Card")
+</script>
+
+<script>
+local items = props.items or {}
+</script>
+
+<main class="page">
+  <h1>{props.title}</h1>
+  {#if #items > 0}
+    <ul>
+      {#each items as item, i}
+        <li class="item {i == 1 and 'first' or ''}">{item.name}</li>
+      {/each}
+    </ul>
+  {:else}
+    <p>Nothing here</p>
+  {/if}
+  <Card title={props.title} />
+  {/* a comment */}
+</main>
+"#;
+
+    fn apply(text: &str, start: usize, end: usize, insert: &str) -> (String, Edit) {
+        let mut new_text = text.to_string();
+        new_text.replace_range(start..end, insert);
+        let edit = Edit {
+            start,
+            old_end: end,
+            new_end: start + insert.len(),
+        };
+        (new_text, edit)
+    }
+
+    #[test]
+    fn test_reparse_reuses_enclosing_node() {
+        let tree = parser::parse(SAMPLE);
+        let at = SAMPLE.find("Nothing").unwrap();
+        let (text, edit) = apply(SAMPLE, at, at + 7, "Still nothing");
+
+        let mut reparsed = tree.clone();
+        let replaced =
+            try_reparse(&mut reparsed, &text, edit).expect("edit should reparse locally");
+        assert_eq!(reparsed, parser::parse(&text));
+        assert!(replaced.old.span().text(SAMPLE).contains("Nothing"));
+        assert!(replaced.new.span().text(&text).contains("Still nothing"));
+    }
+
+    #[test]
+    fn test_reparse_matches_full_parse() {
+        let fragments = [
+            "",
+            "x",
+            "{",
+            "}",
+            "<",
+            ">",
+            "</li>",
+            "<p>",
+            "{/each}",
+            "{:else}",
+            "{#if a}",
+            "'",
+            "\"",
+            " ",
+            "\n",
+            "<!--",
+            "-->",
+            "</script>",
+            "{/if}",
+            "/>",
+            "[[",
+            "--",
+        ];
+
+        // Deterministic pseudo-random edits
+        let mut seed: u64 = 0x5eed;
+        let mut next = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound.max(1)
+        };
+
+        for _ in 0..500 {
+            let mut text = SAMPLE.to_string();
+            let mut tree = parser::parse(&text);
+            for _ in 0..5 {
+                let start = next(text.len() + 1);
+                let end = (start + next(8)).min(text.len());
+                let insert = fragments[next(fragments.len())];
+                let (new_text, edit) = apply(&text, start, end, insert);
+
+                reparse(&mut tree, &new_text, edit);
+                text = new_text;
+                assert_eq!(
+                    tree,
+                    parser::parse(&text),
+                    "after edit {:?} in\n{}",
+                    edit,
+                    text
+                );
+            }
+        }
+    }
+}
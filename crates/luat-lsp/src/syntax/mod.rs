@@ -0,0 +1,12 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Syntax tree and parser for .luat templates
+
+mod incremental;
+mod parser;
+mod tree;
+
+pub use incremental::{reparse, Edit, Replaced};
+pub use parser::parse;
+pub use tree::*;
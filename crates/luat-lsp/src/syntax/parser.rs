@@ -0,0 +1,901 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Recursive-descent parser for .luat templates.
+//!
+//! The parser never fails: malformed input is recorded in
+//! [`SyntaxTree::errors`] and parsing resumes at the next sensible point, so
+//! handlers always get a tree covering the whole document.
+
+use super::tree::*;
+use crate::lua;
+
+/// Elements whose content is not parsed as markup
+const RAW_TEXT_ELEMENTS: &[&str] = &["style"];
+
+/// Parse a document into a syntax tree
+pub fn parse(text: &str) -> SyntaxTree {
+    let mut parser = Parser::new(text);
+    let nodes = parser.parse_nodes();
+    SyntaxTree {
+        nodes,
+        errors: parser.errors,
+    }
+}
+
+/// Parse the single node starting at `start`, as if it were nested inside
+/// `ancestors` (outermost first). Used to reparse part of a document.
+pub(super) fn parse_node_at(
+    text: &str,
+    start: usize,
+    ancestors: Vec<Open>,
+) -> (Option<Node>, Vec<ParseError>) {
+    let mut parser = Parser::new(text);
+    parser.pos = start;
+    parser.open = ancestors;
+    let node = parser.parse_node();
+    (node, parser.errors)
+}
+
+/// Something the parser is currently inside of, used to decide which
+/// closing tags end the current node.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Open {
+    Element(String),
+    Block(String),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    open: Vec<Open>,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            bytes: src.as_bytes(),
+            pos: 0,
+            open: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn error(&mut self, span: Span, message: impl Into<String>) {
+        self.errors.push(ParseError {
+            span,
+            message: message.into(),
+        });
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Offset of the end of the line containing `offset` (before the newline)
+    fn line_end(&self, offset: usize) -> usize {
+        self.src[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(self.src.len())
+    }
+
+    /// Whether the byte at `i` starts a markup construct rather than text
+    fn starts_markup(&self, i: usize) -> bool {
+        let next = |n: usize| self.bytes.get(i + n).copied();
+        match self.bytes[i] {
+            b'{' => true,
+            b'<' => match next(1) {
+                Some(b) if b.is_ascii_alphabetic() => true,
+                Some(b'/') => next(2).is_some_and(|b| b.is_ascii_alphabetic()),
+                Some(b'!') => self.src[i..].starts_with("<!--"),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn is_open_element(&self, name: &str) -> bool {
+        self.open
+            .iter()
+            .any(|o| matches!(o, Open::Element(n) if n == name))
+    }
+
+    fn is_open_block(&self, keyword: &str) -> bool {
+        self.open
+            .iter()
+            .any(|o| matches!(o, Open::Block(k) if k == keyword))
+    }
+
+    fn in_block(&self) -> bool {
+        self.open.iter().any(|o| matches!(o, Open::Block(_)))
+    }
+
+    // ---- nodes -----------------------------------------------------------
+
+    /// Parse nodes until the end of input or a closer that belongs to an
+    /// enclosing element or block.
+    fn parse_nodes(&mut self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while let Some(node) = self.parse_node() {
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// Parse the next node, or return `None` at the end of input or at a
+    /// closer that belongs to an enclosing element or block.
+    fn parse_node(&mut self) -> Option<Node> {
+        if self.at_end() {
+            return None;
+        }
+        let rest = self.rest();
+
+        let node = if rest.starts_with("<!--") {
+            Node::Comment(self.parse_comment(CommentKind::Html, "<!--", "-->"))
+        } else if rest.starts_with("</") && self.starts_markup(self.pos) {
+            let name = self.peek_name(self.pos + 2);
+            if self.is_open_element(&name) {
+                return None;
+            }
+            Node::StrayEndTag(self.parse_end_tag())
+        } else if self.peek() == Some(b'<') && self.starts_markup(self.pos) {
+            // `<li>` ends an open `<li>` whose end tag was left out
+            if let Some(Open::Element(open)) = self.open.last() {
+                if closes_implicitly(open, &self.peek_name(self.pos + 1)) {
+                    return None;
+                }
+            }
+            self.parse_element()
+        } else if rest.starts_with("{/*") {
+            Node::Comment(self.parse_comment(CommentKind::LuatBlock, "{/*", "*/}"))
+        } else if rest.starts_with("{--") {
+            Node::Comment(self.parse_comment(CommentKind::LuatLine, "{--", "--}"))
+        } else if rest.starts_with("{#") {
+            Node::Block(self.parse_block())
+        } else if rest.starts_with("{:") {
+            if self.in_block() {
+                return None;
+            }
+            Node::StrayTag(self.parse_tag())
+        } else if rest.starts_with("{/") {
+            let keyword = self.peek_name(self.pos + 2);
+            if self.is_open_block(&keyword) {
+                return None;
+            }
+            Node::StrayTag(self.parse_tag())
+        } else if rest.starts_with("{@") {
+            Node::Directive(self.parse_tag())
+        } else if rest.starts_with('{') {
+            Node::Expression(self.parse_expression())
+        } else {
+            Node::Text(self.parse_text())
+        };
+
+        Some(node)
+    }
+
+    fn parse_text(&mut self) -> Span {
+        let start = self.pos;
+        self.pos += self.rest().chars().next().map_or(1, char::len_utf8);
+        while !self.at_end() {
+            match self.rest().find(['<', '{']) {
+                Some(i) => {
+                    self.pos += i;
+                    if self.starts_markup(self.pos) {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                None => self.pos = self.bytes.len(),
+            }
+        }
+        Span::new(start, self.pos)
+    }
+
+    fn parse_comment(&mut self, kind: CommentKind, open: &str, close: &str) -> Comment {
+        let start = self.pos;
+        let body = start + open.len();
+        match self.src[body..].find(close) {
+            Some(i) => {
+                self.pos = body + i + close.len();
+                Comment {
+                    span: Span::new(start, self.pos),
+                    kind,
+                    closed: true,
+                }
+            }
+            None => {
+                self.pos = self.bytes.len();
+                self.error(Span::new(start, body), "Unclosed comment");
+                Comment {
+                    span: Span::new(start, self.pos),
+                    kind,
+                    closed: false,
+                }
+            }
+        }
+    }
+
+    // ---- braces ----------------------------------------------------------
+
+    /// Find the `}` matching the `{` at `open`, treating the content as Lua
+    fn find_closing_brace(&self, open: usize) -> Option<usize> {
+        lua::find_closing_brace(self.src, open)
+    }
+
+    /// Consume a braced construct starting at the current `{`, returning the
+    /// content span and whether it was closed. Unclosed braces end at the end
+    /// of their line so the rest of the document still parses.
+    fn consume_braced(&mut self) -> (Span, bool) {
+        let start = self.pos;
+        match self.find_closing_brace(start) {
+            Some(close) => {
+                self.pos = close + 1;
+                (Span::new(start + 1, close), true)
+            }
+            None => {
+                let end = self.line_end(start);
+                self.pos = end;
+                self.error(Span::new(start, end), "Unclosed brace");
+                (Span::new(start + 1, end), false)
+            }
+        }
+    }
+
+    fn parse_expression(&mut self) -> Expression {
+        let start = self.pos;
+        let (content, closed) = self.consume_braced();
+        Expression {
+            span: Span::new(start, self.pos),
+            content,
+            closed,
+        }
+    }
+
+    /// Read an identifier-like name starting at `offset` without consuming it
+    fn peek_name(&self, offset: usize) -> String {
+        let len = self.bytes[offset..]
+            .iter()
+            .take_while(|b| is_name_byte(**b))
+            .count();
+        self.src[offset..offset + len].to_string()
+    }
+
+    /// Parse `{#kw ...}`, `{:kw ...}`, `{/kw}` or `{@kw ...}`
+    fn parse_tag(&mut self) -> Tag {
+        let start = self.pos;
+        let sigil = self.bytes[start + 1] as char;
+
+        let keyword_start = start + 2;
+        let mut keyword = self.peek_name(keyword_start);
+        let mut keyword_end = keyword_start + keyword.len();
+
+        // `{:else if cond}` is a single clause
+        if sigil == ':' && keyword == "else" {
+            let after = &self.src[keyword_end..];
+            let trimmed = after.trim_start();
+            if trimmed.starts_with("if")
+                && !trimmed[2..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+            {
+                keyword_end += after.len() - trimmed.len() + 2;
+                keyword = "else if".to_string();
+            }
+        }
+
+        let (content, closed) = self.consume_braced();
+        let body_start = keyword_end.min(content.end);
+        let expression = trimmed_span(self.src, Span::new(body_start, content.end));
+
+        Tag {
+            span: Span::new(start, self.pos),
+            sigil,
+            keyword,
+            keyword_span: Span::new(keyword_start, keyword_end.min(content.end)),
+            expression,
+            closed,
+        }
+    }
+
+    fn parse_block(&mut self) -> Block {
+        let start = self.pos;
+        let open = self.parse_tag();
+        let keyword = open.keyword.clone();
+
+        self.open.push(Open::Block(keyword.clone()));
+        let children = self.parse_nodes();
+        let mut branches = vec![Branch {
+            tag: open,
+            children,
+        }];
+        let mut close = None;
+
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("{:") {
+                let tag = self.parse_tag();
+                let children = self.parse_nodes();
+                branches.push(Branch { tag, children });
+            } else if rest.starts_with("{/") && self.peek_name(self.pos + 2) == keyword {
+                close = Some(self.parse_tag());
+                break;
+            } else {
+                break;
+            }
+        }
+        self.open.pop();
+
+        Block {
+            span: Span::new(start, self.pos),
+            keyword,
+            branches,
+            close,
+        }
+    }
+
+    // ---- elements --------------------------------------------------------
+
+    fn parse_element(&mut self) -> Node {
+        let start = self.pos;
+        self.pos += 1;
+        let name = self.peek_name(self.pos);
+        let name_span = Span::new(self.pos, self.pos + name.len());
+        self.pos = name_span.end;
+
+        let (attributes, self_closing) = self.parse_attributes(start);
+        let start_tag = Span::new(start, self.pos);
+
+        if name == "script" {
+            let (content, end_tag) = self.parse_raw_text(&name);
+            return Node::Script(Script {
+                span: Span::new(start, self.pos),
+                start_tag,
+                attributes,
+                content,
+                end_tag,
+            });
+        }
+
+        let mut children = Vec::new();
+        let mut end_tag = None;
+
+        if self_closing || VOID_ELEMENTS.contains(&name.as_str()) {
+            // No content
+        } else if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let (content, end) = self.parse_raw_text(&name);
+            if !content.is_empty() {
+                children.push(Node::Text(content));
+            }
+            end_tag = end;
+        } else {
+            self.open.push(Open::Element(name.clone()));
+            children = self.parse_nodes();
+            self.open.pop();
+
+            if self.rest().starts_with("</") && self.peek_name(self.pos + 2) == name {
+                end_tag = Some(self.parse_end_tag());
+            }
+        }
+
+        Node::Element(Element {
+            span: Span::new(start, self.pos),
+            name,
+            name_span,
+            start_tag,
+            attributes,
+            self_closing,
+            children,
+            end_tag,
+        })
+    }
+
+    /// Parse attributes up to and including the closing `>` or `/>`
+    fn parse_attributes(&mut self, tag_start: usize) -> (Vec<Attribute>, bool) {
+        let mut attributes = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some(b'<') => {
+                    self.error(
+                        Span::new(tag_start, self.pos),
+                        "Unterminated start tag - missing '>'",
+                    );
+                    return (attributes, false);
+                }
+                Some(b'>') => {
+                    self.pos += 1;
+                    return (attributes, false);
+                }
+                Some(b'/') if self.peek_at(1) == Some(b'>') => {
+                    self.pos += 2;
+                    return (attributes, true);
+                }
+                Some(b'/') => self.pos += 1,
+                Some(b'{') => attributes.push(self.parse_braced_attribute()),
+                Some(_) => attributes.push(self.parse_attribute()),
+            }
+        }
+    }
+
+    /// Parse `{name}` shorthand or `{...expr}` spread attributes
+    fn parse_braced_attribute(&mut self) -> Attribute {
+        let mut expr = self.parse_expression();
+        let inner = expr.content.text(self.src);
+        let trimmed = inner.trim_start();
+
+        let (kind, name_span) = if let Some(spread) = trimmed.strip_prefix("...") {
+            let start = expr.content.end - spread.len();
+            expr.content = trimmed_span(self.src, Span::new(start, expr.content.end))
+                .unwrap_or(Span::new(expr.content.end, expr.content.end));
+            (
+                AttributeKind::Spread,
+                Span::new(expr.span.start, expr.span.start),
+            )
+        } else {
+            let name_span = trimmed_span(self.src, expr.content)
+                .unwrap_or(Span::new(expr.content.start, expr.content.start));
+            (AttributeKind::Shorthand, name_span)
+        };
+
+        Attribute {
+            span: expr.span,
+            kind,
+            name: name_span.text(self.src).to_string(),
+            name_span,
+            value: Some(AttributeValue::Expression(expr)),
+        }
+    }
+
+    fn parse_attribute(&mut self) -> Attribute {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| {
+            !b.is_ascii_whitespace() && !matches!(b, b'=' | b'>' | b'/' | b'<' | b'{')
+        }) {
+            self.pos += 1;
+        }
+        let name_span = Span::new(start, self.pos);
+        let name = name_span.text(self.src).to_string();
+
+        let after_name = self.pos;
+        self.skip_whitespace();
+        if self.peek() != Some(b'=') {
+            self.pos = after_name;
+            return Attribute {
+                span: name_span,
+                kind: AttributeKind::Normal,
+                name,
+                name_span,
+                value: None,
+            };
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+
+        let value = match self.peek() {
+            Some(quote @ (b'"' | b'\'')) => Some(self.parse_quoted_value(quote)),
+            Some(b'{') => Some(AttributeValue::Expression(self.parse_expression())),
+            Some(b) if !b.is_ascii_whitespace() && b != b'>' => {
+                let value_start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|b| !b.is_ascii_whitespace() && b != b'>')
+                {
+                    self.pos += 1;
+                }
+                let span = Span::new(value_start, self.pos);
+                Some(AttributeValue::Text {
+                    span,
+                    value: span.text(self.src).to_string(),
+                })
+            }
+            _ => None,
+        };
+
+        Attribute {
+            span: Span::new(start, self.pos),
+            kind: AttributeKind::Normal,
+            name,
+            name_span,
+            value,
+        }
+    }
+
+    /// Parse a quoted value, splitting out any `{expression}` interpolations.
+    /// Values may span lines. One that runs into a `<` after a `>`, or into
+    /// the end of the document, is taken to be missing its closing quote and
+    /// ends at that `>` instead, so the tag still ends where it was meant to.
+    fn parse_quoted_value(&mut self, quote: u8) -> AttributeValue {
+        let start = self.pos;
+        self.pos += 1;
+        let mut parts = Vec::new();
+        let mut text_start = self.pos;
+        let mut first_gt = None;
+
+        loop {
+            match self.peek() {
+                Some(b) if b == quote => break,
+                // Quotes inside an interpolation don't end the value
+                Some(b'{') => match self.find_closing_brace(self.pos) {
+                    Some(close) => {
+                        if text_start < self.pos {
+                            parts.push(ValuePart::Text(Span::new(text_start, self.pos)));
+                        }
+                        parts.push(ValuePart::Expression(Expression {
+                            span: Span::new(self.pos, close + 1),
+                            content: Span::new(self.pos + 1, close),
+                            closed: true,
+                        }));
+                        self.pos = close + 1;
+                        text_start = self.pos;
+                    }
+                    None => self.pos += 1,
+                },
+                Some(b'>') => {
+                    first_gt.get_or_insert(self.pos);
+                    self.pos += 1;
+                }
+                Some(b'<') if first_gt.is_some() => {
+                    self.end_unterminated_value(start, first_gt, &mut parts, &mut text_start);
+                    break;
+                }
+                None => {
+                    self.end_unterminated_value(start, first_gt, &mut parts, &mut text_start);
+                    break;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+
+        let inner_end = self.pos;
+        if text_start < inner_end {
+            parts.push(ValuePart::Text(Span::new(text_start, inner_end)));
+        }
+        if self.peek() == Some(quote) {
+            self.pos += 1;
+        }
+
+        let span = Span::new(start, self.pos);
+        if parts.iter().any(|p| matches!(p, ValuePart::Expression(_))) {
+            AttributeValue::Interpolated { span, parts }
+        } else {
+            AttributeValue::Text {
+                span,
+                value: self.src[start + 1..inner_end].to_string(),
+            }
+        }
+    }
+
+    /// Report a quoted value without its closing quote and cut it off at
+    /// the first `>` in it, if any
+    fn end_unterminated_value(
+        &mut self,
+        start: usize,
+        first_gt: Option<usize>,
+        parts: &mut Vec<ValuePart>,
+        text_start: &mut usize,
+    ) {
+        if let Some(cut) = first_gt {
+            self.pos = cut;
+            // Interpolations are skipped whole, so none straddles the cut
+            parts.retain(|part| match part {
+                ValuePart::Text(span) => span.end <= cut,
+                ValuePart::Expression(expr) => expr.span.end <= cut,
+            });
+            *text_start = (*text_start).min(cut);
+        }
+        self.error(Span::new(start, self.pos), "Unterminated attribute value");
+    }
+
+    fn parse_end_tag(&mut self) -> EndTag {
+        let start = self.pos;
+        let name = self.peek_name(start + 2);
+        let name_span = Span::new(start + 2, start + 2 + name.len());
+        self.pos = name_span.end;
+
+        match self.rest().find(['>', '<']) {
+            Some(i) if self.bytes[self.pos + i] == b'>' => self.pos += i + 1,
+            found => {
+                self.pos += found.unwrap_or(self.rest().len());
+                self.error(
+                    Span::new(start, self.pos),
+                    "Unterminated end tag - missing '>'",
+                );
+            }
+        }
+
+        EndTag {
+            span: Span::new(start, self.pos),
+            name,
+            name_span,
+        }
+    }
+
+    /// Consume raw content up to `</name`, returning the content span and end tag
+    fn parse_raw_text(&mut self, name: &str) -> (Span, Option<EndTag>) {
+        let start = self.pos;
+        let needle = format!("</{name}");
+        let end = self.src[start..].find(&needle).map(|i| start + i);
+
+        match end {
+            Some(end) => {
+                self.pos = end;
+                (Span::new(start, end), Some(self.parse_end_tag()))
+            }
+            None => {
+                self.pos = self.bytes.len();
+                (Span::new(start, self.pos), None)
+            }
+        }
+    }
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.' | b':')
+}
+
+/// Shrink a span to exclude surrounding whitespace; `None` if nothing is left
+fn trimmed_span(src: &str, span: Span) -> Option<Span> {
+    let text = span.text(src);
+    let trimmed_start = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        let start = span.start + trimmed_start;
+        Some(Span::new(start, start + trimmed.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Top-level spans must tile the document without gaps or overlap
+    fn assert_lossless(text: &str, tree: &SyntaxTree) {
+        let mut offset = 0;
+        for node in &tree.nodes {
+            assert_eq!(node.span().start, offset, "gap before {:?}", node);
+            offset = node.span().end;
+        }
+        assert_eq!(offset, text.len());
+    }
+
+    #[test]
+    fn test_parse_elements_and_attributes() {
+        let text = r#"<div class="card" hidden><a href={url}>Go</a><br></div>"#;
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert!(tree.errors.is_empty());
+
+        let Node::Element(div) = &tree.nodes[0] else {
+            panic!("expected element");
+        };
+        assert_eq!(div.name, "div");
+        assert_eq!(div.attributes.len(), 2);
+        assert_eq!(div.attributes[1].name, "hidden");
+        assert!(div.end_tag.is_some());
+        assert_eq!(div.children.len(), 2);
+
+        let Node::Element(a) = &div.children[0] else {
+            panic!("expected element");
+        };
+        let Some(AttributeValue::Expression(expr)) = &a.attributes[0].value else {
+            panic!("expected expression attribute");
+        };
+        assert_eq!(expr.content.text(text), "url");
+    }
+
+    #[test]
+    fn test_parse_component_named_like_void_element() {
+        let text = "<Link href=\"/\"><b>Home</b></Link><link rel=\"icon\">";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert!(tree.errors.is_empty());
+
+        assert_eq!(tree.nodes.len(), 2);
+        let Node::Element(link) = &tree.nodes[0] else {
+            panic!("expected element");
+        };
+        assert!(!link.is_void());
+        assert_eq!(link.children.len(), 1);
+        assert!(link.end_tag.is_some());
+        let Node::Element(void) = &tree.nodes[1] else {
+            panic!("expected element");
+        };
+        assert!(void.is_void());
+    }
+
+    #[test]
+    fn test_parse_optional_end_tags() {
+        let text = "<ul><li>a<li>b</ul><p>intro<div>x</div><table><tr><td>1<td>2<tr><td>3</table>";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+
+        let names = |nodes: &[Node]| -> Vec<String> {
+            nodes
+                .iter()
+                .filter_map(|node| match node {
+                    Node::Element(element) => Some(element.name.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let Node::Element(ul) = &tree.nodes[0] else {
+            panic!("expected element");
+        };
+        assert_eq!(names(&ul.children), vec!["li", "li"]);
+        assert!(ul.end_tag.is_some());
+        // A block element ends the paragraph
+        assert_eq!(names(&tree.nodes), vec!["ul", "p", "div", "table"]);
+
+        let Node::Element(table) = &tree.nodes[3] else {
+            panic!("expected element");
+        };
+        assert_eq!(names(&table.children), vec!["tr", "tr"]);
+        let Node::Element(row) = &table.children[0] else {
+            panic!("expected element");
+        };
+        assert_eq!(names(&row.children), vec!["td", "td"]);
+    }
+
+    #[test]
+    fn test_parse_attribute_kinds() {
+        let text = r#"<Card {title} {...props} class="btn {active and 'on' or ''}" />"#;
+        let tree = parse(text);
+        assert!(tree.errors.is_empty());
+
+        let card = tree.elements()[0];
+        let kinds: Vec<_> = card
+            .attributes
+            .iter()
+            .map(|a| (a.kind, a.name.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (AttributeKind::Shorthand, "title"),
+                (AttributeKind::Spread, ""),
+                (AttributeKind::Normal, "class"),
+            ]
+        );
+
+        let Some(AttributeValue::Expression(spread)) = &card.attributes[1].value else {
+            panic!("expected spread expression");
+        };
+        assert_eq!(spread.content.text(text), "props");
+
+        let Some(AttributeValue::Interpolated { parts, .. }) = &card.attributes[2].value else {
+            panic!("expected interpolated value");
+        };
+        assert!(matches!(parts[0], ValuePart::Text(s) if s.text(text) == "btn "));
+        assert!(
+            matches!(&parts[1], ValuePart::Expression(e) if e.content.text(text) == "active and 'on' or ''")
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_values() {
+        let text = "<div class=\"card\n  featured {extra}\" title='a > b'>x</div>";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert!(tree.errors.is_empty());
+        let div = tree.elements()[0];
+        assert_eq!(div.attributes.len(), 2);
+        assert_eq!(div.attributes[1].literal(), Some("a > b"));
+
+        // A missing quote ends the value at the `>` that was meant to end the tag
+        let text = "<div class=\"card>\n<p>text</p></div>";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert_eq!(tree.errors.len(), 1);
+        assert_eq!(tree.errors[0].message, "Unterminated attribute value");
+        assert_eq!(tree.errors[0].span.text(text), "\"card");
+        let div = tree.elements()[0];
+        assert_eq!(div.start_tag.text(text), "<div class=\"card>");
+        assert_eq!(div.children.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_blocks_with_branches() {
+        let text = "{#if a}<p>{x}</p>{:else if b}B{:else}C{/if}";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+
+        let Node::Block(block) = &tree.nodes[0] else {
+            panic!("expected block");
+        };
+        assert_eq!(block.keyword, "if");
+        assert_eq!(block.branches.len(), 3);
+        assert_eq!(block.branches[1].tag.keyword, "else if");
+        assert_eq!(
+            block.branches[1].tag.expression.map(|s| s.text(text)),
+            Some("b")
+        );
+        assert!(block.close.is_some());
+    }
+
+    #[test]
+    fn test_parse_scripts_are_raw() {
+        let text =
+            "<script module>\nlocal t = { a = 1 }\n</script>\n<style>.a { color: red }</style>";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+
+        let scripts = tree.scripts();
+        assert_eq!(scripts.len(), 1);
+        assert!(scripts[0].is_module());
+        assert_eq!(scripts[0].content.text(text), "\nlocal t = { a = 1 }\n");
+
+        let style = tree.elements()[0];
+        assert_eq!(style.name, "style");
+        assert!(matches!(style.children[..], [Node::Text(_)]));
+    }
+
+    #[test]
+    fn test_parse_lua_aware_braces() {
+        let text = r#"{#each {1,2,3} as n}{fmt({a=1})}{"}"}{/each}<p class="x {a and '}' or ''}">"#;
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert!(tree.errors.is_empty());
+
+        let Node::Block(block) = &tree.nodes[0] else {
+            panic!("expected block");
+        };
+        assert_eq!(
+            block.branches[0].tag.expression.map(|s| s.text(text)),
+            Some("{1,2,3} as n")
+        );
+        let exprs: Vec<_> = block.branches[0]
+            .children
+            .iter()
+            .filter_map(|n| match n {
+                Node::Expression(e) => Some(e.content.text(text)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(exprs, vec!["fmt({a=1})", r#""}""#]);
+    }
+
+    #[test]
+    fn test_parse_recovers_from_errors() {
+        let text = "<div>{#each items as item}<li>{item</li>\n{/if}</section></div>";
+        let tree = parse(text);
+        assert_lossless(text, &tree);
+        assert!(!tree.errors.is_empty());
+
+        let Node::Element(div) = &tree.nodes[0] else {
+            panic!("expected element");
+        };
+        // The unclosed block ends at `</div>`, which still closes the div
+        assert!(div.end_tag.is_some());
+        let mut strays = 0;
+        tree.walk(&mut |node| {
+            if matches!(node, Node::StrayTag(_) | Node::StrayEndTag(_)) {
+                strays += 1;
+            }
+        });
+        assert_eq!(strays, 2);
+    }
+}
@@ -0,0 +1,556 @@
+// Copyright 2026 Maravilla Labs
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Elements that never have children or an end tag
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose end tag may be left out: they end at the start of certain
+/// siblings (see [`closes_implicitly`]) or at their parent's end tag
+pub const OPTIONAL_END_TAGS: &[&str] = &[
+    "li", "dt", "dd", "p", "tr", "td", "th", "option", "optgroup", "thead", "tbody", "tfoot",
+];
+
+/// Elements whose start tag ends an open `<p>`
+pub const P_CLOSERS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "main",
+    "menu",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Whether a `<next>` start tag ends an open `<open>` element whose end tag
+/// was left out, e.g. `<li>` ends the previous `<li>`
+pub fn closes_implicitly(open: &str, next: &str) -> bool {
+    match open {
+        "li" => next == "li",
+        "dt" | "dd" => matches!(next, "dt" | "dd"),
+        "p" => P_CLOSERS.contains(&next),
+        "tr" => next == "tr",
+        "td" | "th" => matches!(next, "td" | "th" | "tr"),
+        "option" => matches!(next, "option" | "optgroup"),
+        "optgroup" => next == "optgroup",
+        "thead" | "tbody" => matches!(next, "tbody" | "tfoot"),
+        _ => false,
+    }
+}
+
+/// Whether a tag name names a component: it starts with an uppercase letter
+pub fn is_component_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+/// A byte range in the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Whether the offset falls inside the span (end exclusive)
+    pub fn contains(&self, offset: usize) -> bool {
+        offset >= self.start && offset < self.end
+    }
+
+    /// Slice the span out of the source it was parsed from
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start..self.end]
+    }
+
+    /// Move the ends at or after `from` by `delta`, following an edit that
+    /// ended at `from`
+    pub fn shift(&mut self, from: usize, delta: isize) {
+        for pos in [&mut self.start, &mut self.end] {
+            if *pos >= from {
+                *pos = pos.saturating_add_signed(delta);
+            }
+        }
+    }
+}
+
+/// A syntax error found while parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub span: Span,
+    pub message: String,
+}
+
+/// The lossless syntax tree of a .luat document.
+///
+/// Every byte of the source belongs to exactly one top-level node, so the
+/// spans of `nodes` tile the whole document.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SyntaxTree {
+    pub nodes: Vec<Node>,
+    pub errors: Vec<ParseError>,
+}
+
+impl SyntaxTree {
+    /// Visit every node in document order (parents before children)
+    pub fn walk<'a>(&'a self, f: &mut dyn FnMut(&'a Node)) {
+        walk_nodes(&self.nodes, f);
+    }
+
+    /// Iterate over all `<script>` blocks in the document
+    pub fn scripts(&self) -> Vec<&Script> {
+        let mut scripts = Vec::new();
+        self.walk(&mut |node| {
+            if let Node::Script(script) = node {
+                scripts.push(script);
+            }
+        });
+        scripts
+    }
+
+    /// Iterate over all Lua script blocks, skipping client-side and data scripts
+    pub fn lua_scripts(&self) -> Vec<&Script> {
+        self.scripts()
+            .into_iter()
+            .filter(|script| script.kind().is_lua())
+            .collect()
+    }
+
+    /// Iterate over all elements (including components) in the document
+    pub fn elements(&self) -> Vec<&Element> {
+        let mut elements = Vec::new();
+        self.walk(&mut |node| {
+            if let Node::Element(element) = node {
+                elements.push(element);
+            }
+        });
+        elements
+    }
+
+    /// The element whose start tag contains the offset
+    pub fn element_at_start_tag(&self, offset: usize) -> Option<&Element> {
+        self.elements()
+            .into_iter()
+            .find(|element| element.start_tag.contains(offset))
+    }
+}
+
+fn walk_nodes<'a>(nodes: &'a [Node], f: &mut dyn FnMut(&'a Node)) {
+    for node in nodes {
+        f(node);
+        match node {
+            Node::Element(element) => walk_nodes(&element.children, f),
+            Node::Block(block) => {
+                for branch in &block.branches {
+                    walk_nodes(&branch.children, f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A node in the syntax tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Literal markup text
+    Text(Span),
+    /// `<!-- -->`, `{/* */}` or `{-- --}`
+    Comment(Comment),
+    /// `{expression}`
+    Expression(Expression),
+    /// `<div ...>...</div>`, `<br>` or `<Card />`
+    Element(Element),
+    /// `<script ...>...</script>`
+    Script(Script),
+    /// `{#if}...{/if}`, `{#each}...{/each}`
+    Block(Block),
+    /// `{@html ...}`, `{@local ...}`, `{@render ...}`
+    Directive(Tag),
+    /// A `{:clause}` or `{/closer}` that has no enclosing block
+    StrayTag(Tag),
+    /// A `</name>` that has no matching open element
+    StrayEndTag(EndTag),
+}
+
+impl Node {
+    pub fn span(&self) -> Span {
+        match self {
+            Node::Text(span) => *span,
+            Node::Comment(comment) => comment.span,
+            Node::Expression(expr) => expr.span,
+            Node::Element(element) => element.span,
+            Node::Script(script) => script.span,
+            Node::Block(block) => block.span,
+            Node::Directive(tag) | Node::StrayTag(tag) => tag.span,
+            Node::StrayEndTag(end) => end.span,
+        }
+    }
+
+    /// Collect the spans of Lua in the node, in document order: Lua scripts
+    /// with their start tags, expressions, block and directive tags, and
+    /// component names, which refer to their imports. The tags also give
+    /// the blocks' scopes, so nodes whose pieces read the same analyze the
+    /// same.
+    pub fn lua_pieces(&self, pieces: &mut Vec<Span>) {
+        match self {
+            Node::Script(script) => {
+                // The start tag decides whether the script is Lua at all
+                pieces.push(script.start_tag);
+                if script.kind().is_lua() {
+                    pieces.push(script.content);
+                }
+            }
+            Node::Expression(expr) => pieces.push(expr.span),
+            Node::Directive(tag) | Node::StrayTag(tag) => pieces.push(tag.span),
+            Node::Block(block) => {
+                for branch in &block.branches {
+                    pieces.push(branch.tag.span);
+                    for child in &branch.children {
+                        child.lua_pieces(pieces);
+                    }
+                }
+                if let Some(close) = &block.close {
+                    pieces.push(close.span);
+                }
+            }
+            Node::Element(element) => {
+                if element.is_component() {
+                    pieces.push(element.name_span);
+                }
+                for attribute in &element.attributes {
+                    if let Some(value) = &attribute.value {
+                        pieces.extend(value.expressions().iter().map(|expr| expr.span));
+                    }
+                }
+                for child in &element.children {
+                    child.lua_pieces(pieces);
+                }
+            }
+            Node::Text(_) | Node::Comment(_) | Node::StrayEndTag(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `<!-- -->`
+    Html,
+    /// `{/* */}`
+    LuatBlock,
+    /// `{-- --}`
+    LuatLine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub span: Span,
+    pub kind: CommentKind,
+    pub closed: bool,
+}
+
+/// A braced Lua expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    /// The whole expression including braces
+    pub span: Span,
+    /// The Lua source between the braces
+    pub content: Span,
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub span: Span,
+    pub name: String,
+    pub name_span: Span,
+    /// The start tag, from `<` to `>`
+    pub start_tag: Span,
+    pub attributes: Vec<Attribute>,
+    /// Written as `<name ... />`
+    pub self_closing: bool,
+    pub children: Vec<Node>,
+    /// The matching `</name>`, if present
+    pub end_tag: Option<EndTag>,
+}
+
+impl Element {
+    /// Components are elements whose name starts with an uppercase letter
+    pub fn is_component(&self) -> bool {
+        is_component_name(&self.name)
+    }
+
+    /// Whether the element can't have content, so it never needs an end tag.
+    /// Tag names are case-sensitive, so a component like `<Link>` never is.
+    pub fn is_void(&self) -> bool {
+        self.self_closing || VOID_ELEMENTS.contains(&self.name.as_str())
+    }
+
+    /// The attribute with the given name, ignoring case
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|a| a.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether a `{...spread}` may set attributes not written out
+    pub fn has_spread(&self) -> bool {
+        self.attributes
+            .iter()
+            .any(|a| a.kind == AttributeKind::Spread)
+    }
+
+    /// Whether leaving out the end tag is valid HTML
+    pub fn has_optional_end_tag(&self) -> bool {
+        OPTIONAL_END_TAGS.contains(&self.name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndTag {
+    pub span: Span,
+    pub name: String,
+    pub name_span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// `name`, `name="value"` or `name={expression}`
+    Normal,
+    /// `{name}`, short for `name={name}`
+    Shorthand,
+    /// `{...expression}`
+    Spread,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub span: Span,
+    pub kind: AttributeKind,
+    /// Attribute name. Spread attributes have an empty name.
+    pub name: String,
+    pub name_span: Span,
+    /// Shorthand and spread attributes always have an expression value
+    pub value: Option<AttributeValue>,
+}
+
+impl Attribute {
+    /// The literal value, or `Some("")` for a bare attribute like `hidden`.
+    /// `None` when the value is computed.
+    pub fn literal(&self) -> Option<&str> {
+        match &self.value {
+            None if self.kind == AttributeKind::Normal => Some(""),
+            Some(AttributeValue::Text { value, .. }) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    /// A literal value, e.g. `class="card"` or `type=text`
+    Text {
+        /// The value including quotes
+        span: Span,
+        /// The value without quotes
+        value: String,
+    },
+    /// `name={expression}`
+    Expression(Expression),
+    /// A quoted value with interpolations, e.g. `class="btn {extra}"`
+    Interpolated {
+        /// The value including quotes
+        span: Span,
+        parts: Vec<ValuePart>,
+    },
+}
+
+impl AttributeValue {
+    /// The Lua expressions in this value
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            AttributeValue::Text { .. } => Vec::new(),
+            AttributeValue::Expression(expr) => vec![expr],
+            AttributeValue::Interpolated { parts, .. } => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ValuePart::Expression(expr) => Some(expr),
+                    ValuePart::Text(_) => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A piece of an interpolated attribute value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValuePart {
+    Text(Span),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    pub span: Span,
+    pub start_tag: Span,
+    pub attributes: Vec<Attribute>,
+    /// The raw source between the tags, never parsed as template markup
+    pub content: Span,
+    pub end_tag: Option<EndTag>,
+}
+
+/// What a `<script>` tag contains, decided by its attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// `<script>` or `<script lang="lua">`, run on every render
+    Lua,
+    /// `<script module>` or `<script context="module">`, run once on load
+    LuaModule,
+    /// Client-side JavaScript: `type="module"`, `type="text/javascript"`,
+    /// `lang="js"`, or any script with a `src`
+    JavaScript,
+    /// Data blocks such as `type="application/json"`, `type="application/ld+json"`
+    /// or `type="importmap"`
+    Json,
+    /// Any other `type` or `lang`, treated as opaque text
+    Other,
+}
+
+impl ScriptKind {
+    pub fn is_lua(self) -> bool {
+        matches!(self, ScriptKind::Lua | ScriptKind::LuaModule)
+    }
+}
+
+impl Script {
+    /// `<script module>` or `<script context="module">`
+    pub fn is_module(&self) -> bool {
+        self.attributes.iter().any(|a| {
+            a.name == "module" || (a.name == "context" && self.text_attribute(a) == Some("module"))
+        })
+    }
+
+    /// Classify the script by its `type`, `lang` and `src` attributes
+    pub fn kind(&self) -> ScriptKind {
+        let attribute = |name: &str| {
+            self.attributes
+                .iter()
+                .find(|a| a.name.eq_ignore_ascii_case(name))
+        };
+
+        if let Some(ty) = attribute("type") {
+            let ty = self
+                .text_attribute(ty)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            return match ty.as_str() {
+                "lua" | "text/lua" | "application/lua" | "text/x-lua" => self.lua_kind(),
+                "" | "module" | "text/javascript" | "application/javascript" => {
+                    ScriptKind::JavaScript
+                }
+                "importmap" | "speculationrules" => ScriptKind::Json,
+                _ if ty.ends_with("json") => ScriptKind::Json,
+                _ => ScriptKind::Other,
+            };
+        }
+
+        if let Some(lang) = attribute("lang") {
+            let lang = self
+                .text_attribute(lang)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            return match lang.as_str() {
+                "lua" => self.lua_kind(),
+                "js" | "javascript" | "ts" | "typescript" => ScriptKind::JavaScript,
+                "json" => ScriptKind::Json,
+                _ => ScriptKind::Other,
+            };
+        }
+
+        // Lua scripts never load from a URL
+        if attribute("src").is_some() {
+            return ScriptKind::JavaScript;
+        }
+
+        self.lua_kind()
+    }
+
+    fn lua_kind(&self) -> ScriptKind {
+        if self.is_module() {
+            ScriptKind::LuaModule
+        } else {
+            ScriptKind::Lua
+        }
+    }
+
+    fn text_attribute<'a>(&self, attribute: &'a Attribute) -> Option<&'a str> {
+        match &attribute.value {
+            Some(AttributeValue::Text { value, .. }) => Some(value.trim()),
+            _ => None,
+        }
+    }
+}
+
+/// A template tag such as `{#if cond}`, `{:else}`, `{/each}` or `{@html x}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub span: Span,
+    /// One of `#`, `:`, `/` or `@`
+    pub sigil: char,
+    /// The keyword after the sigil, e.g. `if`, `else if`, `html`
+    pub keyword: String,
+    pub keyword_span: Span,
+    /// The trimmed source following the keyword, if any
+    pub expression: Option<Span>,
+    pub closed: bool,
+}
+
+/// A control flow block with one or more branches
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub span: Span,
+    /// The keyword of the opening tag, e.g. `if` or `each`
+    pub keyword: String,
+    /// The first branch starts with the opening tag, the rest with `{:clause}` tags
+    pub branches: Vec<Branch>,
+    /// The closing `{/keyword}` tag, if present
+    pub close: Option<Tag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub tag: Tag,
+    pub children: Vec<Node>,
+}
@@ -69,7 +69,11 @@ export async function activate(context: ExtensionContext) {
   const clientOptions: LanguageClientOptions = {
     documentSelector: [{ scheme: "file", language: "luat" }],
     synchronize: {
-      fileEvents: workspace.createFileSystemWatcher("**/*.luat"),
+      configurationSection: "luat",
+      fileEvents: [
+        workspace.createFileSystemWatcher("**/*.luat"),
+        workspace.createFileSystemWatcher("**/{luat.toml,.luatrc}"),
+      ],
     },
     outputChannelName: "Luat Language Server",
   };